@@ -1,9 +1,28 @@
+import { timingSafeEqual } from "node:crypto";
 import { serve } from "@hono/node-server";
 import { Hono } from "hono";
 import { APP_NAME } from "@aios/shared";
 
 const app = new Hono();
 
+// Per-launch token generated by the Rust shell; every request must carry it.
+// Unset when running standalone in development.
+const token = process.env.AIOS_BACKEND_TOKEN;
+
+if (token) {
+  const expected = Buffer.from(`Bearer ${token}`);
+  app.use("*", async (c, next) => {
+    const provided = Buffer.from(c.req.header("Authorization") ?? "");
+    if (
+      provided.length !== expected.length ||
+      !timingSafeEqual(provided, expected)
+    ) {
+      return c.json({ error: "unauthorized" }, 401);
+    }
+    await next();
+  });
+}
+
 app.get("/health", (c) =>
   c.json({
     status: "ok",
@@ -15,7 +34,7 @@ app.get("/health", (c) =>
 
 const port = Number(process.env.PORT) || 0;
 
-const server = serve({ fetch: app.fetch, port }, (info) => {
+const server = serve({ fetch: app.fetch, port, hostname: "127.0.0.1" }, (info) => {
   console.log(`BACKEND_PORT:${info.port}`);
   console.error(`${APP_NAME} backend running on http://localhost:${info.port}`);
 });
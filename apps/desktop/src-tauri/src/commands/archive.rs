@@ -0,0 +1,53 @@
+use crate::services::run_archive;
+use crate::state::AppState;
+use crate::types::archive::ArchiveFormat;
+use crate::types::errors::AppError;
+use crate::types::run::Run;
+use std::path::PathBuf;
+use tauri::State;
+
+/// Export a run to a portable archive at `path`
+///
+/// The format defaults to zip for `.zip` paths and JSON otherwise.
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn export_run(
+    run_id: String,
+    path: PathBuf,
+    format: Option<ArchiveFormat>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let (database, run_store, attachment_store) = (
+        state.database.clone(),
+        state.run_store.clone(),
+        state.attachment_store.clone(),
+    );
+
+    let format = format.unwrap_or_else(|| match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("zip") => ArchiveFormat::Zip,
+        _ => ArchiveFormat::Json,
+    });
+
+    run_archive::export_run(
+        &database,
+        &run_store,
+        &attachment_store,
+        &run_id,
+        &path,
+        format,
+    )
+    .await
+}
+
+/// Import a run archive as a new run
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn import_run(path: PathBuf, state: State<'_, AppState>) -> Result<Run, AppError> {
+    let (database, run_store, attachment_store) = (
+        state.database.clone(),
+        state.run_store.clone(),
+        state.attachment_store.clone(),
+    );
+
+    run_archive::import_run(&database, &run_store, &attachment_store, &path).await
+}
@@ -0,0 +1,27 @@
+use crate::services::sidecar_manager::{BackendInfo, BackendResponse};
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use tauri::State;
+
+/// Get the backend sidecar status and the port it listens on
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_backend_info(state: State<'_, AppState>) -> Result<BackendInfo, AppError> {
+    let sidecar_manager = state.sidecar_manager.clone();
+
+    Ok(sidecar_manager.info().await)
+}
+
+/// Proxy a request to the backend sidecar, attaching the per-launch auth token
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn backend_request(
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<BackendResponse, AppError> {
+    let sidecar_manager = state.sidecar_manager.clone();
+
+    sidecar_manager.request(&method, &path, body).await
+}
@@ -2,6 +2,7 @@ use crate::types::errors::AppError;
 
 /// Health check command to validate IPC pipeline
 #[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
 pub async fn health_check() -> Result<String, AppError> {
     Ok("ok".to_string())
 }
@@ -0,0 +1,46 @@
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::logging::LogEntry;
+use tauri::State;
+use tracing::Level;
+
+/// Default number of entries returned by `get_recent_logs`
+const DEFAULT_LOGS_LIMIT: usize = 200;
+/// Maximum number of entries returned by `get_recent_logs`
+const MAX_LOGS_LIMIT: usize = 2000;
+
+/// Read recent log entries, newest first, optionally only those at or above
+/// `level`
+///
+/// Values of stored secrets and sensitive fields are redacted.
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_recent_logs(
+    limit: Option<usize>,
+    level: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LogEntry>, AppError> {
+    let min_level = level
+        .map(|level| {
+            level.parse::<Level>().map_err(|_| {
+                AppError::Validation(format!(
+                    "Invalid log level '{}', expected error, warn, info, debug or trace",
+                    level
+                ))
+            })
+        })
+        .transpose()?;
+
+    // Secrets that cannot be read are skipped; the field-name and bearer
+    // token rules still apply
+    let secrets = state
+        .keyring_service
+        .run_blocking(|keyring| Ok(keyring.redaction_values()))
+        .await?;
+    let logging = state.logging.clone();
+
+    let limit = limit.unwrap_or(DEFAULT_LOGS_LIMIT).clamp(1, MAX_LOGS_LIMIT);
+    tokio::task::spawn_blocking(move || logging.recent(limit, min_level, &secrets))
+        .await
+        .map_err(|e| AppError::Io(format!("Log reader failed: {}", e)))?
+}
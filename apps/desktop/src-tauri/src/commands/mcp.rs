@@ -1,62 +1,122 @@
 use crate::state::AppState;
-use crate::types::config::McpServerStatus;
+use crate::types::config::{McpServerEntry, SandboxReport};
 use crate::types::errors::AppError;
+use crate::types::metrics::{ServerHealth, ServerMetrics};
+use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::sync::Mutex;
 use tauri::State;
 
-/// Start an MCP server by name
+/// Start an MCP server by name, after the servers it depends on
 #[tauri::command]
-pub async fn start_mcp_server(
-    name: String,
-    state: State<'_, Mutex<AppState>>,
-) -> Result<(), AppError> {
-    // Retrieve all secrets from keyring to pass as environment variables
-    // and get reference to mcp_manager (both in same lock scope)
-    let (env_overrides, mcp_manager) = {
-        let app_state = state.lock().unwrap();
-        let keys = app_state.keyring_service.list_keys();
-
-        let mut env_vars = HashMap::new();
-        for key in keys {
-            if let Ok(value) = app_state.keyring_service.get_secret(&key) {
-                env_vars.insert(key, value);
-            }
-        }
-
-        // Clone the Arc to mcp_manager so we can use it outside the lock
-        (env_vars, app_state.mcp_manager.clone())
-    };
-
-    // Start the server with environment overrides (lock is now released)
-    mcp_manager.start_server(&name, env_overrides).await
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn start_mcp_server(name: String, state: State<'_, AppState>) -> Result<(), AppError> {
+    // Pass all secrets from the keyring as environment variables
+    let env_overrides: HashMap<String, String> = state
+        .keyring_service
+        .run_blocking(|keyring| Ok(keyring.read_available()))
+        .await?
+        .into_iter()
+        .collect();
+    let mcp_manager = state.mcp_manager.clone();
+
+    mcp_manager
+        .start_with_dependencies(&name, env_overrides)
+        .await
+}
+
+/// Call a tool on an MCP server, starting it first if it is configured as
+/// `on_demand`
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn call_mcp_tool(
+    server: String,
+    tool: String,
+    arguments: Option<Value>,
+    state: State<'_, AppState>,
+) -> Result<Value, AppError> {
+    let keyring_service = state.keyring_service.clone();
+    let mcp_manager = state.mcp_manager.clone();
+
+    // An on-demand server is started with the same environment as a manual
+    // start; the keyring is only read when that is needed
+    mcp_manager
+        .call_tool(
+            &server,
+            &tool,
+            arguments.unwrap_or_else(|| json!({})),
+            || async move {
+                Ok(keyring_service
+                    .run_blocking(|keyring| Ok(keyring.read_available()))
+                    .await?
+                    .into_iter()
+                    .collect())
+            },
+        )
+        .await
 }
 
 /// Stop an MCP server by name
 #[tauri::command]
-pub async fn stop_mcp_server(
-    name: String,
-    state: State<'_, Mutex<AppState>>,
-) -> Result<(), AppError> {
-    // Clone Arc outside the lock
-    let mcp_manager = {
-        let app_state = state.lock().unwrap();
-        app_state.mcp_manager.clone()
-    };
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn stop_mcp_server(name: String, state: State<'_, AppState>) -> Result<(), AppError> {
+    let mcp_manager = state.mcp_manager.clone();
 
     mcp_manager.stop_server(&name).await
 }
 
-/// List all MCP servers with their current status
+/// List all MCP servers sorted by name, with their status, process details,
+/// what they reported at startup and resource usage
+///
+/// Stored secrets are redacted from command lines.
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn list_mcp_servers(state: State<'_, AppState>) -> Result<Vec<McpServerEntry>, AppError> {
+    let secrets = state
+        .keyring_service
+        .run_blocking(|keyring| Ok(keyring.redaction_values()))
+        .await?;
+    let mcp_manager = state.mcp_manager.clone();
+
+    Ok(mcp_manager.describe_servers(&secrets).await)
+}
+
+/// Get the sandbox protections in effect for a running MCP server
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_mcp_server_sandbox(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<SandboxReport, AppError> {
+    state
+        .mcp_manager
+        .sandbox_report(&name)
+        .ok_or_else(|| AppError::NotFound(format!("MCP server '{}' is not running", name)))
+}
+
+/// Get the current and recent resource usage of a running MCP server's
+/// process tree
 #[tauri::command]
-pub async fn list_mcp_servers(
-    state: State<'_, Mutex<AppState>>,
-) -> Result<HashMap<String, McpServerStatus>, AppError> {
-    // Clone Arc outside the lock
-    let mcp_manager = {
-        let app_state = state.lock().unwrap();
-        app_state.mcp_manager.clone()
-    };
-
-    Ok(mcp_manager.list_servers().await)
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_mcp_server_metrics(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<ServerMetrics, AppError> {
+    state
+        .mcp_manager
+        .metrics(&name)
+        .ok_or_else(|| AppError::NotFound(format!("MCP server '{}' is not running", name)))
+}
+
+/// Get the health check results and recent ping latencies of a running MCP
+/// server
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_mcp_server_health(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<ServerHealth, AppError> {
+    state
+        .mcp_manager
+        .health(&name)
+        .ok_or_else(|| AppError::NotFound(format!("MCP server '{}' is not running", name)))
 }
@@ -0,0 +1,38 @@
+use crate::services::memory;
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::memory::{MemoryRecord, MemorySearchHit, MemorySearchQuery, MemoryUpsert};
+use tauri::State;
+
+/// Insert or update an agent memory
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn memory_upsert(
+    memory: MemoryUpsert,
+    state: State<'_, AppState>,
+) -> Result<MemoryRecord, AppError> {
+    let database = state.database.clone();
+
+    memory::upsert(database.pool(), &memory).await
+}
+
+/// Search agent memories by embedding and/or text
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn memory_search(
+    query: MemorySearchQuery,
+    state: State<'_, AppState>,
+) -> Result<Vec<MemorySearchHit>, AppError> {
+    let database = state.database.clone();
+
+    memory::search(database.pool(), &query).await
+}
+
+/// Delete an agent memory by id
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn memory_delete(id: i64, state: State<'_, AppState>) -> Result<(), AppError> {
+    let database = state.database.clone();
+
+    memory::delete(database.pool(), id).await
+}
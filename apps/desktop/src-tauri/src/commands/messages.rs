@@ -0,0 +1,54 @@
+use crate::services::messages;
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::message::{Attachment, MessagePart, NewAttachment, StoredMessage};
+use base64::Engine;
+use tauri::State;
+
+/// Append a structured message to a run
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn append_message(
+    run_id: String,
+    role: String,
+    parts: Vec<MessagePart>,
+    state: State<'_, AppState>,
+) -> Result<StoredMessage, AppError> {
+    let database = state.database.clone();
+
+    messages::append(database.pool(), &run_id, &role, parts).await
+}
+
+/// Get all messages of a run with their structured parts
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_run_messages(
+    run_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<StoredMessage>, AppError> {
+    let database = state.database.clone();
+
+    messages::list_for_run(database.pool(), &run_id).await
+}
+
+/// Store an attachment so it can be referenced from message parts
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn store_attachment(
+    attachment: NewAttachment,
+    state: State<'_, AppState>,
+) -> Result<Attachment, AppError> {
+    let (database, attachment_store) = (state.database.clone(), state.attachment_store.clone());
+
+    attachment_store.store(database.pool(), &attachment).await
+}
+
+/// Read an attachment's contents, base64 encoded
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn read_attachment(hash: String, state: State<'_, AppState>) -> Result<String, AppError> {
+    let attachment_store = state.attachment_store.clone();
+
+    let bytes = attachment_store.read(&hash).await?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
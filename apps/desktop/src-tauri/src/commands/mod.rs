@@ -1,3 +1,15 @@
+pub mod archive;
+pub mod backend;
 pub mod health;
+pub mod logs;
 pub mod mcp;
+pub mod memory;
+pub mod messages;
+pub mod notifications;
+pub mod queue;
+pub mod runs;
+pub mod search;
 pub mod secrets;
+pub mod settings;
+pub mod shortcuts;
+pub mod triggers;
@@ -0,0 +1,75 @@
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::notification::{Notification, NotificationAction, NotificationSettings};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Default number of notifications returned by `list_notifications`
+const DEFAULT_NOTIFICATIONS_LIMIT: i64 = 50;
+/// Maximum number of notifications returned by `list_notifications`
+const MAX_NOTIFICATIONS_LIMIT: i64 = 500;
+
+/// List recorded notifications, newest first
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn list_notifications(
+    limit: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Notification>, AppError> {
+    let notification_service = state.notification_service.clone();
+
+    let limit = limit
+        .unwrap_or(DEFAULT_NOTIFICATIONS_LIMIT)
+        .clamp(1, MAX_NOTIFICATIONS_LIMIT);
+    notification_service.list(limit).await
+}
+
+/// Handle an action chosen on a notification
+///
+/// `open` shows the main window and emits `open-run` with the run id (`null`
+/// for summaries) so the frontend can navigate to it.
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn handle_notification_action(
+    id: i64,
+    action: NotificationAction,
+    reply: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Notification, AppError> {
+    let notification_service = state.notification_service.clone();
+
+    let notification = notification_service
+        .handle_action(id, action, reply)
+        .await?;
+    if action == NotificationAction::Open {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        let _ = app.emit("open-run", notification.run_id.clone());
+    }
+
+    Ok(notification)
+}
+
+/// Get notification preferences
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub fn get_notification_settings(
+    state: State<'_, AppState>,
+) -> Result<NotificationSettings, AppError> {
+    Ok(state.notification_service.settings())
+}
+
+/// Replace notification preferences
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub fn update_notification_settings(
+    settings: NotificationSettings,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    state
+        .settings_store
+        .modify(|s| s.notifications = settings)?;
+    Ok(())
+}
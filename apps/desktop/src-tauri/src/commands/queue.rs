@@ -0,0 +1,60 @@
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::queue::{QueueLimits, QueueSnapshot};
+use crate::types::run::{NewRun, Run};
+use tauri::State;
+
+/// Submit a task to the background queue
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn submit_task(task: NewRun, state: State<'_, AppState>) -> Result<Run, AppError> {
+    let task_queue = state.task_queue.clone();
+
+    task_queue.submit(&task).await
+}
+
+/// Cancel a queued or executing task
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn cancel_task(id: String, state: State<'_, AppState>) -> Result<Run, AppError> {
+    let task_queue = state.task_queue.clone();
+
+    task_queue.cancel(&id).await
+}
+
+/// List executing and queued tasks
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn list_queue(state: State<'_, AppState>) -> Result<QueueSnapshot, AppError> {
+    let task_queue = state.task_queue.clone();
+
+    task_queue.snapshot().await
+}
+
+/// Stop dispatching queued tasks
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn pause_queue(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.task_queue.pause();
+    Ok(())
+}
+
+/// Resume dispatching queued tasks
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn resume_queue(state: State<'_, AppState>) -> Result<(), AppError> {
+    let task_queue = state.task_queue.clone();
+
+    task_queue.resume().await
+}
+
+/// Change the global and per-agent concurrency limits
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn set_queue_limits(
+    limits: QueueLimits,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    state.settings_store.modify(|s| s.queue = limits)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::run::{NewRun, Run, RunStatus, RunTransition};
+use tauri::State;
+
+/// Create a new pending run for a task
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn create_run(run: NewRun, state: State<'_, AppState>) -> Result<Run, AppError> {
+    let run_store = state.run_store.clone();
+
+    run_store.create_run(&run).await
+}
+
+/// Get a run by id
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_run(id: String, state: State<'_, AppState>) -> Result<Run, AppError> {
+    let run_store = state.run_store.clone();
+
+    run_store.get_run(&id).await
+}
+
+/// List runs, optionally filtered by status
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn list_runs(
+    statuses: Option<Vec<RunStatus>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Run>, AppError> {
+    let run_store = state.run_store.clone();
+
+    run_store.list_runs(&statuses.unwrap_or_default()).await
+}
+
+/// Move a run to a new status; illegal transitions are rejected
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn update_run_status(
+    id: String,
+    status: RunStatus,
+    reason: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<RunTransition, AppError> {
+    let run_store = state.run_store.clone();
+
+    run_store.transition(&id, status, reason).await
+}
+
+/// Get the status history of a run
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_run_events(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<RunTransition>, AppError> {
+    let run_store = state.run_store.clone();
+
+    run_store.list_events(&id).await
+}
@@ -0,0 +1,17 @@
+use crate::services::search;
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::search::{MessageSearchHit, MessageSearchQuery};
+use tauri::State;
+
+/// Full-text search over run messages
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn search_messages(
+    query: MessageSearchQuery,
+    state: State<'_, AppState>,
+) -> Result<Vec<MessageSearchHit>, AppError> {
+    let database = state.database.clone();
+
+    search::search_messages(database.pool(), &query).await
+}
@@ -1,7 +1,6 @@
 use crate::state::AppState;
 use crate::types::errors::AppError;
 use serde::Serialize;
-use std::sync::Mutex;
 use tauri::State;
 
 #[derive(Serialize)]
@@ -12,52 +11,66 @@ pub struct SecretEntry {
 
 /// Get all secrets as key-value pairs
 #[tauri::command]
-pub fn get_secrets(state: State<'_, Mutex<AppState>>) -> Result<Vec<SecretEntry>, AppError> {
-    let mut app_state = state.lock().unwrap();
-    let keys = app_state.keyring_service.list_keys();
-
-    let mut secrets = Vec::new();
-    let mut keys_to_remove = Vec::new();
-
-    for key in keys {
-        match app_state.keyring_service.get_secret(&key) {
-            Ok(value) => secrets.push(SecretEntry { key, value }),
-            Err(_) => {
-                // Track keys that can't be retrieved (might have been deleted outside app)
-                keys_to_remove.push(key);
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_secrets(state: State<'_, AppState>) -> Result<Vec<SecretEntry>, AppError> {
+    state
+        .keyring_service
+        .run_blocking(|keyring| {
+            let mut secrets = Vec::new();
+            let mut keys_to_remove = Vec::new();
+
+            for key in keyring.list_keys() {
+                match keyring.get_secret(&key) {
+                    Ok(value) => secrets.push(SecretEntry { key, value }),
+                    Err(AppError::KeyringNoEntry(_)) => {
+                        // Track keys that were deleted outside the app
+                        keys_to_remove.push(key);
+                    }
+                    // A locked or unavailable keyring says nothing about the key itself
+                    Err(e) => return Err(e),
+                }
             }
-        }
-    }
 
-    // Clean up stale keys
-    for key in keys_to_remove {
-        let _ = app_state.keyring_service.remove_key(&key);
-    }
+            // Clean up stale keys
+            for key in keys_to_remove {
+                let _ = keyring.remove_key(&key);
+            }
 
-    Ok(secrets)
+            Ok(secrets)
+        })
+        .await
 }
 
 /// Get a single secret by key
 #[tauri::command]
-pub fn get_secret(state: State<'_, Mutex<AppState>>, key: String) -> Result<String, AppError> {
-    let app_state = state.lock().unwrap();
-    app_state.keyring_service.get_secret(&key)
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_secret(state: State<'_, AppState>, key: String) -> Result<String, AppError> {
+    state
+        .keyring_service
+        .run_blocking(move |keyring| keyring.get_secret(&key))
+        .await
 }
 
 /// Set a secret
 #[tauri::command]
-pub fn set_secret(
-    state: State<'_, Mutex<AppState>>,
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn set_secret(
+    state: State<'_, AppState>,
     key: String,
     value: String,
 ) -> Result<(), AppError> {
-    let mut app_state = state.lock().unwrap();
-    app_state.keyring_service.set_secret(&key, &value)
+    state
+        .keyring_service
+        .run_blocking(move |keyring| keyring.set_secret(&key, &value))
+        .await
 }
 
 /// Delete a secret
 #[tauri::command]
-pub fn delete_secret(state: State<'_, Mutex<AppState>>, key: String) -> Result<(), AppError> {
-    let mut app_state = state.lock().unwrap();
-    app_state.keyring_service.delete_secret(&key)
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn delete_secret(state: State<'_, AppState>, key: String) -> Result<(), AppError> {
+    state
+        .keyring_service
+        .run_blocking(move |keyring| keyring.delete_secret(&key))
+        .await
 }
@@ -0,0 +1,64 @@
+use crate::services::global_shortcut::GlobalShortcutService;
+use crate::services::settings::{self, SettingsStore};
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::settings::Settings;
+use tauri::State;
+
+/// Get the application settings
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<Settings, AppError> {
+    Ok(state.settings_store.get())
+}
+
+/// Replace the application settings
+///
+/// Services pick up the change from the settings store; fields documented as
+/// applying after restart are only saved. `secrets_service` and
+/// `mcp_config_path` keep their current values: they are only set by editing
+/// the settings file.
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn update_settings(
+    settings: Settings,
+    state: State<'_, AppState>,
+) -> Result<Settings, AppError> {
+    let (settings_store, global_shortcut) =
+        (state.settings_store.clone(), state.global_shortcut.clone());
+
+    save_settings(&settings_store, &global_shortcut, move |current| {
+        *current = Settings {
+            secrets_service: std::mem::take(&mut current.secrets_service),
+            mcp_config_path: current.mcp_config_path.take(),
+            ..settings
+        };
+    })
+}
+
+/// Change, validate and save settings, registering the configured shortcut
+/// first so a conflicting accelerator is rejected before anything is persisted
+///
+/// Runs under the settings store's write lock, so concurrent saves cannot
+/// leave a registered shortcut that differs from the saved one; if saving
+/// fails, the previous shortcut is registered again.
+pub(crate) fn save_settings(
+    settings_store: &SettingsStore,
+    global_shortcut: &GlobalShortcutService,
+    change: impl FnOnce(&mut Settings),
+) -> Result<Settings, AppError> {
+    settings_store.modify_with(
+        |settings| {
+            change(settings);
+            settings::validate(settings)?;
+            let status = global_shortcut.apply(settings.global_shortcut.clone())?;
+            settings.global_shortcut = status.settings;
+            Ok(())
+        },
+        |previous| {
+            if let Err(e) = global_shortcut.apply(previous.global_shortcut.clone()) {
+                tracing::warn!("Failed to restore the previous global shortcut: {}", e);
+            }
+        },
+    )
+}
@@ -0,0 +1,31 @@
+use crate::commands::settings::save_settings;
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::shortcut::{ShortcutSettings, ShortcutStatus};
+use tauri::State;
+
+/// Get the global shortcut preferences and registration state
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_global_shortcut(state: State<'_, AppState>) -> Result<ShortcutStatus, AppError> {
+    let global_shortcut = state.global_shortcut.clone();
+
+    Ok(global_shortcut.status())
+}
+
+/// Change the global shortcut, failing if the accelerator is invalid or taken
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn update_global_shortcut(
+    settings: ShortcutSettings,
+    state: State<'_, AppState>,
+) -> Result<ShortcutStatus, AppError> {
+    let (settings_store, global_shortcut) =
+        (state.settings_store.clone(), state.global_shortcut.clone());
+
+    save_settings(&settings_store, &global_shortcut, move |all_settings| {
+        all_settings.global_shortcut = settings;
+    })?;
+
+    Ok(global_shortcut.status())
+}
@@ -0,0 +1,143 @@
+use crate::services::cron_schedule::CronSchedule;
+use crate::state::AppState;
+use crate::types::errors::AppError;
+use crate::types::trigger::{
+    Trigger, TriggerDefinition, TriggerFiring, TriggerSpec, WebhookDelivery,
+};
+use tauri::State;
+
+/// Default number of fire times returned by `preview_schedule`
+const DEFAULT_PREVIEW_COUNT: usize = 5;
+/// Maximum number of fire times returned by `preview_schedule`
+const MAX_PREVIEW_COUNT: usize = 100;
+/// Default number of firings or deliveries returned by the log commands
+const DEFAULT_FIRINGS_LIMIT: i64 = 50;
+/// Maximum number of firings or deliveries returned by the log commands
+const MAX_FIRINGS_LIMIT: i64 = 500;
+
+/// List all triggers
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn list_triggers(state: State<'_, AppState>) -> Result<Vec<Trigger>, AppError> {
+    let trigger_store = state.trigger_store.clone();
+
+    trigger_store.list(None).await
+}
+
+/// Get a trigger by id
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_trigger(id: String, state: State<'_, AppState>) -> Result<Trigger, AppError> {
+    let trigger_store = state.trigger_store.clone();
+
+    trigger_store.get(&id).await
+}
+
+/// Create a trigger
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn create_trigger(
+    definition: TriggerDefinition,
+    state: State<'_, AppState>,
+) -> Result<Trigger, AppError> {
+    let trigger_store = state.trigger_store.clone();
+
+    trigger_store.create(&definition).await
+}
+
+/// Replace a trigger's definition
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn update_trigger(
+    id: String,
+    definition: TriggerDefinition,
+    state: State<'_, AppState>,
+) -> Result<Trigger, AppError> {
+    let trigger_store = state.trigger_store.clone();
+
+    trigger_store.update(&id, &definition).await
+}
+
+/// Delete a trigger
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn delete_trigger(id: String, state: State<'_, AppState>) -> Result<(), AppError> {
+    let trigger_store = state.trigger_store.clone();
+
+    trigger_store.delete(&id).await
+}
+
+/// List recent trigger firings, newest first, optionally for one trigger
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn list_trigger_firings(
+    trigger_id: Option<String>,
+    limit: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<TriggerFiring>, AppError> {
+    let trigger_store = state.trigger_store.clone();
+
+    let limit = limit
+        .unwrap_or(DEFAULT_FIRINGS_LIMIT)
+        .clamp(1, MAX_FIRINGS_LIMIT);
+    trigger_store
+        .list_firings(trigger_id.as_deref(), limit)
+        .await
+}
+
+/// Get the local URL that delivers to a webhook trigger
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn get_webhook_url(id: String, state: State<'_, AppState>) -> Result<String, AppError> {
+    let (trigger_store, webhook_server) =
+        (state.trigger_store.clone(), state.webhook_server.clone());
+
+    let trigger = trigger_store.get(&id).await?;
+    if !matches!(trigger.spec, TriggerSpec::Webhook { .. }) {
+        return Err(AppError::Validation(format!(
+            "Trigger '{}' is not a webhook trigger",
+            trigger.name
+        )));
+    }
+    webhook_server
+        .url_for(&trigger.id)
+        .ok_or_else(|| AppError::InvalidState("Webhook listener is not running".to_string()))
+}
+
+/// List recent webhook deliveries, newest first, optionally for one trigger
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub async fn list_webhook_deliveries(
+    trigger_id: Option<String>,
+    limit: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<WebhookDelivery>, AppError> {
+    let webhook_server = state.webhook_server.clone();
+
+    let limit = limit
+        .unwrap_or(DEFAULT_FIRINGS_LIMIT)
+        .clamp(1, MAX_FIRINGS_LIMIT);
+    webhook_server
+        .list_deliveries(trigger_id.as_deref(), limit)
+        .await
+}
+
+/// List the next fire times of a cron expression as RFC 3339 timestamps in its timezone
+#[tauri::command]
+#[tracing::instrument(skip_all, err(level = "warn"))]
+pub fn preview_schedule(
+    expression: String,
+    timezone: Option<String>,
+    count: Option<usize>,
+) -> Result<Vec<String>, AppError> {
+    let schedule = CronSchedule::parse(&expression, timezone.as_deref().unwrap_or("UTC"))?;
+    let count = count
+        .unwrap_or(DEFAULT_PREVIEW_COUNT)
+        .min(MAX_PREVIEW_COUNT);
+
+    Ok(schedule
+        .upcoming(chrono::Utc::now())
+        .take(count)
+        .map(|time| schedule.to_local_rfc3339(time))
+        .collect())
+}
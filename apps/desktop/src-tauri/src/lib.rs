@@ -1,128 +1,344 @@
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    use tauri::{Manager, menu::{Menu, MenuItem}};
-    use tauri::tray::{TrayIconBuilder, TrayIconEvent};
+    use tauri::{Emitter, Manager};
 
     #[cfg(target_os = "macos")]
     use tauri::ActivationPolicy;
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(
             tauri_plugin_sql::Builder::default()
                 .add_migrations(
-                    "sqlite:aios.db",
-                    vec![tauri_plugin_sql::Migration {
-                        version: 1,
-                        description: "create_initial_tables",
-                        sql: r#"
-                            CREATE TABLE IF NOT EXISTS runs (
-                                id TEXT PRIMARY KEY,
-                                task TEXT NOT NULL,
-                                status TEXT NOT NULL DEFAULT 'pending',
-                                started_at TEXT NOT NULL,
-                                completed_at TEXT,
-                                error TEXT
-                            );
-                            CREATE TABLE IF NOT EXISTS messages (
-                                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                                run_id TEXT NOT NULL,
-                                role TEXT NOT NULL,
-                                content TEXT NOT NULL,
-                                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                                FOREIGN KEY(run_id) REFERENCES runs(id)
-                            );
-                        "#,
-                        kind: tauri_plugin_sql::MigrationKind::Up,
-                    }],
+                    services::database::DB_URL,
+                    services::database::MIGRATIONS
+                        .iter()
+                        .map(|m| tauri_plugin_sql::Migration {
+                            version: m.version,
+                            description: m.description,
+                            sql: m.sql,
+                            kind: tauri_plugin_sql::MigrationKind::Up,
+                        })
+                        .collect(),
                 )
                 .build(),
         )
         .setup(|app| {
-            use std::sync::Mutex;
+            use std::sync::Arc;
             use crate::services::keyring_service::KeyringService;
             use crate::services::mcp_manager::McpManager;
+            use crate::services::run_store::RunStore;
+            use crate::services::sidecar_manager::{SidecarConfig, SidecarManager};
+            use crate::services::task_queue::TaskQueue;
+            use crate::services::triggers::TriggerStore;
+            use crate::services::scheduler::Scheduler;
+            use crate::services::file_watcher::FileWatcher;
+            use crate::services::webhook_server::WebhookServer;
+            use crate::services::notifications::{NotificationService, TauriNotificationSink};
+            use crate::services::global_shortcut::GlobalShortcutService;
+            use crate::services::settings::{self, SettingsStore};
+            use crate::services::logging::{self, Logging};
+            use crate::services::attachment_store::AttachmentStore;
             use crate::services::config_loader;
+            use crate::services::database::{self, Database};
             use crate::state::AppState;
 
+            let app_data_dir = app.path().app_data_dir()
+                .expect("Failed to get app data directory");
+
+            // Load settings, migrating files written by older versions and falling
+            // back to defaults when the file is invalid
+            let settings_store = SettingsStore::load(settings::default_settings_path(&app_data_dir))
+                .expect("Failed to load settings");
+            let settings = settings_store.get();
+
+            // Log to a rolling JSON file in the app data directory
+            let logging = Arc::new(
+                Logging::init(logging::default_log_dir(&app_data_dir), &settings.logging)
+                    .expect("Failed to initialize logging"),
+            );
+            if let Some(error) = settings_store.load_error() {
+                tracing::error!("Ignoring settings file and using defaults: {}", error);
+            }
+
             // Set activation policy on macOS to hide from dock (tray-only)
             #[cfg(target_os = "macos")]
-            app.set_activation_policy(ActivationPolicy::Accessory);
+            if !settings.window.show_in_dock {
+                app.set_activation_policy(ActivationPolicy::Accessory);
+            }
+
+            if settings.window.start_hidden {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+            }
 
             // Initialize KeyringService with app data directory
-            let app_data_dir = app.path().app_data_dir()
-                .expect("Failed to get app data directory");
-            let keyring_service = KeyringService::new("com.aios.secrets", app_data_dir.clone())
-                .expect("Failed to initialize KeyringService");
+            let keyring_service = Arc::new(
+                KeyringService::new(&settings.secrets_service, app_data_dir.clone())
+                    .expect("Failed to initialize KeyringService"),
+            );
 
             // Load MCP configuration
-            let mcp_config_path = config_loader::default_config_path(&app_data_dir);
+            let mcp_config_path = settings
+                .mcp_config_path
+                .clone()
+                .unwrap_or_else(|| config_loader::default_config_path(&app_data_dir));
             let mcp_config = config_loader::load(&mcp_config_path)
                 .expect("Failed to load MCP configuration");
 
             // Initialize McpManager
             let mcp_manager = McpManager::new(mcp_config);
 
-            // Initialize and manage AppState
-            let app_state = AppState::new(keyring_service, mcp_manager);
-            app.manage(Mutex::new(app_state));
-
-            // Note: MCP processes are automatically cleaned up via kill_on_drop(true)
-            // when the app exits. No explicit shutdown hook needed.
-
-            // Create system tray menu
-            let show_item = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
-
-            // Build tray icon with menu
-            let _tray = TrayIconBuilder::new()
-                .icon(app.default_window_icon().cloned().expect("no app icon"))
-                .icon_as_template(true)
-                .menu(&menu)
-                .on_menu_event(|app, event| {
-                    match event.id.as_ref() {
-                        "show" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                        "quit" => {
-                            app.exit(0);
+            // Open the same database the SQL plugin uses (it lives in the app config dir)
+            let app_config_dir = app.path().app_config_dir()
+                .expect("Failed to get app config directory");
+            let database = tauri::async_runtime::block_on(Database::connect(
+                &database::default_db_path(&app_config_dir),
+            ))
+            .expect("Failed to open database");
+            let attachment_store = AttachmentStore::new(&app_data_dir)
+                .expect("Failed to initialize attachment store");
+            let run_store = RunStore::new(database.clone());
+
+            // Forward run status transitions to the frontend
+            let mut transitions = run_store.subscribe();
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                use tokio::sync::broadcast::error::RecvError;
+                loop {
+                    match transitions.recv().await {
+                        Ok(transition) => {
+                            let _ = app_handle.emit("run-status-changed", transition);
                         }
-                        _ => {}
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
                     }
-                })
-                .on_tray_icon_event(|tray, event| {
-                    // Toggle window visibility on tray icon click
-                    if let TrayIconEvent::Click { .. } = event {
-                        if let Some(app) = tray.app_handle().get_webview_window("main") {
-                            if app.is_visible().unwrap_or(false) {
-                                let _ = app.hide();
-                            } else {
-                                let _ = app.show();
-                                let _ = app.set_focus();
-                            }
-                        }
+                }
+            });
+
+            // Background agent runs are dispatched from the persistent queue
+            let task_queue = TaskQueue::new(
+                database.clone(),
+                run_store.clone(),
+                settings.queue.clone(),
+            );
+            let trigger_store = TriggerStore::new(database.clone(), task_queue.clone());
+
+            // Webhook signatures are verified with secrets from the keyring
+            let webhook_server = WebhookServer::new(
+                settings.webhooks.clone(),
+                database.clone(),
+                trigger_store.clone(),
+                keyring_service.clone(),
+            );
+
+            // Notify when runs finish or need input
+            let notification_service = NotificationService::new(
+                database.clone(),
+                run_store.clone(),
+                Arc::new(TauriNotificationSink::new(app.handle().clone())),
+                settings.notifications.clone(),
+            );
+
+            // Summon the launcher from anywhere; a conflicting accelerator is
+            // reported through get_global_shortcut instead of failing startup
+            let global_shortcut =
+                GlobalShortcutService::new(app.handle().clone(), settings.global_shortcut.clone());
+            global_shortcut.register();
+
+            // Initialize the backend sidecar supervisor
+            let sidecar_config = SidecarConfig::bundled("backend")
+                .expect("Failed to resolve backend sidecar path");
+            let sidecar_manager = SidecarManager::new(sidecar_config);
+
+            // Initialize and manage AppState
+            let app_state = AppState::new(
+                keyring_service,
+                mcp_manager,
+                database,
+                attachment_store,
+                run_store,
+                sidecar_manager,
+                task_queue.clone(),
+                trigger_store.clone(),
+                webhook_server,
+                notification_service.clone(),
+                global_shortcut,
+                settings_store.clone(),
+                logging.clone(),
+            );
+            let sidecar_manager = app_state.sidecar_manager.clone();
+            let mcp_manager = app_state.mcp_manager.clone();
+            let mcp_keyring = app_state.keyring_service.clone();
+            let webhook_server = app_state.webhook_server.clone();
+            app.manage(app_state);
+
+            // Apply settings changes to running services and the frontend
+            let mut settings_changes = settings_store.subscribe();
+            let settings_queue = task_queue.clone();
+            let settings_notifications = notification_service.clone();
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                while settings_changes.changed().await.is_ok() {
+                    let settings = settings_changes.borrow_and_update().clone();
+                    if let Err(e) = logging.set_levels(&settings.logging) {
+                        tracing::warn!("Failed to apply log levels: {}", e);
+                    }
+                    if let Err(e) = settings_notifications.set_settings(settings.notifications.clone()) {
+                        tracing::warn!("Failed to apply notification settings: {}", e);
                     }
-                })
-                .build(app)?;
+                    if let Err(e) = settings_queue.set_limits(settings.queue.clone()).await {
+                        tracing::warn!("Failed to apply queue limits: {}", e);
+                    }
+                    let _ = app_handle.emit("settings-changed", settings);
+                }
+            });
+
+            // Launch the backend and keep it running
+            tauri::async_runtime::spawn(async move { sidecar_manager.supervise().await });
+
+            // Sample MCP server resource usage for get_mcp_server_metrics
+            let metrics_manager = mcp_manager.clone();
+            tauri::async_runtime::spawn(async move { metrics_manager.run_metrics().await });
+
+            // Ping running MCP servers and restart unhealthy or exited ones
+            let health_manager = mcp_manager.clone();
+            tauri::async_runtime::spawn(async move { health_manager.run_health_checks().await });
+
+            // Stop on-demand MCP servers once idle
+            let idle_manager = mcp_manager.clone();
+            tauri::async_runtime::spawn(async move { idle_manager.run_idle_shutdown().await });
+
+            // Start autostart MCP servers, dependencies first, with keyring
+            // secrets in their environment like a manual start; the keyring
+            // is only read when there is a server to start
+            tauri::async_runtime::spawn(async move {
+                mcp_manager
+                    .autostart(|| async move {
+                        Ok(mcp_keyring
+                            .run_blocking(|keyring| Ok(keyring.read_available()))
+                            .await?
+                            .into_iter()
+                            .collect())
+                    })
+                    .await;
+            });
+
+            // Recover runs interrupted by the last shutdown and start dispatching
+            tauri::async_runtime::spawn(async move { task_queue.run().await });
+
+            // Fire cron triggers, catching up on fire times missed while closed
+            let scheduler = Scheduler::new(trigger_store.clone());
+            tauri::async_runtime::spawn(async move { scheduler.run().await });
+
+            // Fire file watch triggers on matching file changes
+            let file_watcher = FileWatcher::new(trigger_store);
+            tauri::async_runtime::spawn(async move { file_watcher.run().await });
+
+            tauri::async_runtime::spawn(async move { notification_service.run().await });
+
+            // Accept webhook deliveries on localhost
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = webhook_server.serve().await {
+                    tracing::error!("Webhook listener stopped: {}", e);
+                }
+            });
+
+            // Tray menu mirrors active runs and MCP servers
+            tray::create(app.handle())?;
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::health::health_check,
+            commands::backend::get_backend_info,
+            commands::backend::backend_request,
             commands::secrets::get_secrets,
             commands::secrets::get_secret,
             commands::secrets::set_secret,
             commands::secrets::delete_secret,
             commands::mcp::start_mcp_server,
             commands::mcp::stop_mcp_server,
-            commands::mcp::list_mcp_servers
+            commands::mcp::call_mcp_tool,
+            commands::mcp::list_mcp_servers,
+            commands::mcp::get_mcp_server_sandbox,
+            commands::mcp::get_mcp_server_metrics,
+            commands::mcp::get_mcp_server_health,
+            commands::memory::memory_upsert,
+            commands::memory::memory_search,
+            commands::memory::memory_delete,
+            commands::messages::append_message,
+            commands::messages::get_run_messages,
+            commands::messages::store_attachment,
+            commands::messages::read_attachment,
+            commands::runs::create_run,
+            commands::runs::get_run,
+            commands::runs::list_runs,
+            commands::runs::update_run_status,
+            commands::runs::get_run_events,
+            commands::queue::submit_task,
+            commands::queue::cancel_task,
+            commands::queue::list_queue,
+            commands::queue::pause_queue,
+            commands::queue::resume_queue,
+            commands::queue::set_queue_limits,
+            commands::triggers::list_triggers,
+            commands::triggers::get_trigger,
+            commands::triggers::create_trigger,
+            commands::triggers::update_trigger,
+            commands::triggers::delete_trigger,
+            commands::triggers::preview_schedule,
+            commands::triggers::list_trigger_firings,
+            commands::triggers::get_webhook_url,
+            commands::triggers::list_webhook_deliveries,
+            commands::notifications::list_notifications,
+            commands::notifications::handle_notification_action,
+            commands::notifications::get_notification_settings,
+            commands::notifications::update_notification_settings,
+            commands::shortcuts::get_global_shortcut,
+            commands::shortcuts::update_global_shortcut,
+            commands::settings::get_settings,
+            commands::settings::update_settings,
+            commands::archive::export_run,
+            commands::archive::import_run,
+            commands::search::search_messages,
+            commands::logs::get_recent_logs
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|window, event| {
+            // Spotlight-style launchers get out of the way when focus moves elsewhere
+            if let tauri::WindowEvent::Focused(false) = event {
+                if window.label() != "main" {
+                    return;
+                }
+                let hide_on_blur = window
+                    .try_state::<crate::state::AppState>()
+                    .is_some_and(|state| state.settings_store.get().window.hide_on_blur);
+                if hide_on_blur {
+                    let _ = window.hide();
+                }
+            }
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Stop the backend sidecar and MCP servers before the process exits;
+            // the background loops keep the MCP manager alive, so its children
+            // would not be killed on drop
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<crate::state::AppState>();
+                let sidecar_manager = state.sidecar_manager.clone();
+                let mcp_manager = state.mcp_manager.clone();
+                tauri::async_runtime::block_on(async move {
+                    sidecar_manager.stop().await;
+                    if let Err(e) = mcp_manager.stop_all().await {
+                        tracing::warn!("Failed to stop MCP servers: {}", e);
+                    }
+                });
+            }
+        });
 }
 
 // Module declarations
@@ -130,3 +346,4 @@ mod commands;
 mod types;
 mod state;
 mod services;
+mod tray;
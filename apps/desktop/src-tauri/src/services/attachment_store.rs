@@ -0,0 +1,225 @@
+use crate::types::errors::AppError;
+use crate::types::message::{Attachment, NewAttachment};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Content-addressed storage for message attachments
+///
+/// Files live on disk under `<app data>/attachments/<hash[..2]>/<hash>`; the
+/// `attachments` table records their MIME type and size.
+#[derive(Clone)]
+pub struct AttachmentStore {
+    root: PathBuf,
+}
+
+impl AttachmentStore {
+    /// Create a store rooted in the app data directory
+    pub fn new(app_data_dir: &Path) -> Result<Self, AppError> {
+        let root = app_data_dir.join("attachments");
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// Get the on-disk path for an attachment hash
+    pub fn path_for(&self, hash: &str) -> Result<PathBuf, AppError> {
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(AppError::Validation(format!(
+                "Invalid attachment hash '{}'",
+                hash
+            )));
+        }
+        let hash = hash.to_ascii_lowercase();
+        Ok(self.root.join(&hash[..2]).join(hash))
+    }
+
+    /// Store attachment bytes, deduplicating by content hash
+    pub async fn store(
+        &self,
+        pool: &SqlitePool,
+        attachment: &NewAttachment,
+    ) -> Result<Attachment, AppError> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&attachment.data_base64)
+            .map_err(|e| AppError::Validation(format!("Invalid attachment data: {}", e)))?;
+        self.store_bytes(pool, &bytes, &attachment.mime_type, attachment.name.clone())
+            .await
+    }
+
+    /// Store raw attachment bytes, deduplicating by content hash
+    pub async fn store_bytes(
+        &self,
+        pool: &SqlitePool,
+        bytes: &[u8],
+        mime_type: &str,
+        name: Option<String>,
+    ) -> Result<Attachment, AppError> {
+        let (hash, _) = self.write_file(bytes).await?;
+        insert_row(pool, &hash, mime_type, bytes.len()).await?;
+
+        Ok(Attachment {
+            hash,
+            mime_type: mime_type.to_string(),
+            size: bytes.len() as i64,
+            name,
+        })
+    }
+
+    /// Store raw attachment bytes, recording the attachment inside `tx`
+    ///
+    /// Also returns the path of the file when this call created it, for the
+    /// caller to remove if the transaction is rolled back.
+    pub async fn store_bytes_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        bytes: &[u8],
+        mime_type: &str,
+        name: Option<String>,
+    ) -> Result<(Attachment, Option<PathBuf>), AppError> {
+        let (hash, created) = self.write_file(bytes).await?;
+        insert_row(&mut **tx, &hash, mime_type, bytes.len()).await?;
+
+        let attachment = Attachment {
+            hash,
+            mime_type: mime_type.to_string(),
+            size: bytes.len() as i64,
+            name,
+        };
+        Ok((attachment, created))
+    }
+
+    /// Write `bytes` under their hash unless already stored, returning the
+    /// hash and, when this call created the file, its path
+    ///
+    /// An existing file of the wrong size, such as one truncated by a crash, is
+    /// replaced.
+    async fn write_file(&self, bytes: &[u8]) -> Result<(String, Option<PathBuf>), AppError> {
+        let hash = hex::encode(Sha256::digest(bytes));
+        let path = self.path_for(&hash)?;
+
+        let existing = match tokio::fs::metadata(&path).await {
+            Ok(metadata) => Some(metadata.len()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        match existing {
+            Some(size) if size == bytes.len() as u64 => return Ok((hash, None)),
+            Some(size) => tracing::warn!(
+                "Replacing attachment '{}': stored file has {} bytes, expected {}",
+                hash,
+                size,
+                bytes.len()
+            ),
+            None => {}
+        }
+        let created = existing.is_none().then(|| path.clone());
+        let bytes = bytes.to_vec();
+        tokio::task::spawn_blocking(move || write_atomically(&path, &bytes))
+            .await
+            .map_err(|e| AppError::Io(format!("Attachment writer failed: {}", e)))??;
+        Ok((hash, created))
+    }
+
+    /// Read the contents of a stored attachment
+    pub async fn read(&self, hash: &str) -> Result<Vec<u8>, AppError> {
+        let path = self.path_for(hash)?;
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(AppError::NotFound(format!(
+                "Attachment '{}' not found",
+                hash
+            ))),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Record an attachment's metadata; content-addressed, so existing rows stay
+async fn insert_row<'e>(
+    executor: impl sqlx::SqliteExecutor<'e>,
+    hash: &str,
+    mime_type: &str,
+    size: usize,
+) -> Result<(), AppError> {
+    sqlx::query("INSERT OR IGNORE INTO attachments (hash, mime_type, size) VALUES (?, ?, ?)")
+        .bind(hash)
+        .bind(mime_type)
+        .bind(size as i64)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Write `bytes` to a uniquely named temporary file beside `path` and rename
+/// it into place, so readers never see partial content and concurrent writers
+/// of the same content never truncate each other's files
+fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), AppError> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| AppError::Io(format!("Invalid attachment path '{}'", path.display())))?;
+    std::fs::create_dir_all(parent)?;
+
+    let mut file = tempfile::NamedTempFile::new_in(parent)?;
+    file.write_all(bytes)?;
+    file.as_file().sync_all()?;
+    file.persist(path).map_err(|e| AppError::from(e.error))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::Database;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_stores_of_same_content_leave_one_complete_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(dir.path()).unwrap();
+        let db = Database::in_memory().await.unwrap();
+        let bytes = vec![7u8; 256 * 1024];
+
+        let mut writers = tokio::task::JoinSet::new();
+        for _ in 0..16 {
+            let (store, pool, bytes) = (store.clone(), db.pool().clone(), bytes.clone());
+            writers.spawn(async move { store.store_bytes(&pool, &bytes, "image/png", None).await });
+        }
+        let mut hashes = Vec::new();
+        while let Some(stored) = writers.join_next().await {
+            hashes.push(stored.unwrap().unwrap().hash);
+        }
+        hashes.dedup();
+        assert_eq!(hashes.len(), 1);
+
+        assert_eq!(store.read(&hashes[0]).await.unwrap(), bytes);
+        // No temporary files are left beside the attachment
+        let path = store.path_for(&hashes[0]).unwrap();
+        let files: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files, [path]);
+    }
+
+    #[tokio::test]
+    async fn truncated_files_are_rewritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(dir.path()).unwrap();
+        let db = Database::in_memory().await.unwrap();
+        let bytes = b"complete contents".to_vec();
+
+        let stored = store
+            .store_bytes(db.pool(), &bytes, "text/plain", None)
+            .await
+            .unwrap();
+        let path = store.path_for(&stored.hash).unwrap();
+        std::fs::write(&path, &bytes[..4]).unwrap();
+
+        store
+            .store_bytes(db.pool(), &bytes, "text/plain", None)
+            .await
+            .unwrap();
+        assert_eq!(store.read(&stored.hash).await.unwrap(), bytes);
+    }
+}
@@ -14,7 +14,7 @@ pub fn load(path: &Path) -> Result<McpConfig, AppError> {
 
     let contents = fs::read_to_string(path)?;
     let config: McpConfig = serde_json::from_str(&contents)
-        .map_err(|e| AppError::Io(format!("Failed to parse MCP config: {}", e)))?;
+        .map_err(|e| AppError::Config(format!("Failed to parse MCP config: {}", e)))?;
 
     Ok(config)
 }
@@ -0,0 +1,165 @@
+use crate::types::errors::AppError;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use std::str::FromStr;
+
+/// Timestamp format SQLite's `datetime()` produces; used for all stored times
+const SQLITE_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A parsed cron expression evaluated in a timezone
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    schedule: cron::Schedule,
+    timezone: Tz,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression and IANA timezone name
+    ///
+    /// Standard 5-field expressions are accepted and fire at second zero.
+    pub fn parse(expression: &str, timezone: &str) -> Result<Self, AppError> {
+        let fields = expression.split_whitespace().count();
+        let normalized = match fields {
+            5 => format!("0 {}", expression.trim()),
+            6 | 7 => expression.trim().to_string(),
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "Cron expression must have 5, 6 or 7 fields, got {}",
+                    fields
+                )))
+            }
+        };
+
+        let schedule = cron::Schedule::from_str(&normalized).map_err(|e| {
+            AppError::Validation(format!("Invalid cron expression '{}': {}", expression, e))
+        })?;
+        let timezone = timezone
+            .parse::<Tz>()
+            .map_err(|_| AppError::Validation(format!("Unknown timezone '{}'", timezone)))?;
+
+        Ok(Self { schedule, timezone })
+    }
+
+    /// Fire times strictly after `after`
+    pub fn upcoming(&self, after: DateTime<Utc>) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        self.schedule
+            .after(&after.with_timezone(&self.timezone))
+            .map(|time| time.with_timezone(&Utc))
+    }
+
+    /// First fire time strictly after `after`, if the schedule has one
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.upcoming(after).next()
+    }
+
+    /// Format a time as RFC 3339 in the schedule's timezone
+    pub fn to_local_rfc3339(&self, time: DateTime<Utc>) -> String {
+        time.with_timezone(&self.timezone).to_rfc3339()
+    }
+}
+
+/// Format a UTC time the way SQLite's `datetime()` does
+pub fn format_sqlite_datetime(time: DateTime<Utc>) -> String {
+    time.format(SQLITE_DATETIME_FORMAT).to_string()
+}
+
+/// Parse a UTC time stored by SQLite's `datetime()`
+pub fn parse_sqlite_datetime(value: &str) -> Result<DateTime<Utc>, AppError> {
+    NaiveDateTime::parse_from_str(value, SQLITE_DATETIME_FORMAT)
+        .map(|time| time.and_utc())
+        .map_err(|e| AppError::Database(format!("Invalid timestamp '{}': {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        parse_sqlite_datetime(value).unwrap()
+    }
+
+    fn upcoming(schedule: &CronSchedule, after: &str, count: usize) -> Vec<String> {
+        schedule
+            .upcoming(utc(after))
+            .take(count)
+            .map(format_sqlite_datetime)
+            .collect()
+    }
+
+    #[test]
+    fn five_field_expressions_fire_at_second_zero() {
+        let five = CronSchedule::parse("30 9 * * *", "UTC").unwrap();
+        let six = CronSchedule::parse("0 30 9 * * *", "UTC").unwrap();
+        assert_eq!(
+            upcoming(&five, "2024-01-01 09:30:00", 2),
+            ["2024-01-02 09:30:00", "2024-01-03 09:30:00"]
+        );
+        assert_eq!(
+            upcoming(&five, "2024-01-01 00:00:00", 3),
+            upcoming(&six, "2024-01-01 00:00:00", 3)
+        );
+        // Surrounding whitespace is ignored
+        assert!(CronSchedule::parse("  */5 * * * *  ", "UTC").is_ok());
+    }
+
+    #[test]
+    fn bad_expressions_and_timezones_are_rejected() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * * * *",
+            "61 * * * *",
+            "* 25 * * *",
+            "not a cron expression",
+        ] {
+            assert!(
+                matches!(
+                    CronSchedule::parse(expression, "UTC"),
+                    Err(AppError::Validation(_))
+                ),
+                "{:?} was accepted",
+                expression
+            );
+        }
+        for timezone in ["", "Mars/Olympus_Mons", "utc+2"] {
+            assert!(
+                matches!(
+                    CronSchedule::parse("0 9 * * *", timezone),
+                    Err(AppError::Validation(_))
+                ),
+                "{:?} was accepted",
+                timezone
+            );
+        }
+    }
+
+    #[test]
+    fn local_times_follow_daylight_saving_changes() {
+        let schedule = CronSchedule::parse("0 9 * * *", "America/New_York").unwrap();
+        // Clocks go forward on 2024-03-10 and back on 2024-11-03
+        assert_eq!(
+            upcoming(&schedule, "2024-03-09 00:00:00", 2),
+            ["2024-03-09 14:00:00", "2024-03-10 13:00:00"]
+        );
+        assert_eq!(
+            upcoming(&schedule, "2024-11-02 00:00:00", 2),
+            ["2024-11-02 13:00:00", "2024-11-03 14:00:00"]
+        );
+        assert_eq!(
+            schedule.to_local_rfc3339(utc("2024-03-10 13:00:00")),
+            "2024-03-10T09:00:00-04:00"
+        );
+    }
+
+    #[test]
+    fn sqlite_datetimes_round_trip() {
+        let time = Utc.with_ymd_and_hms(2024, 2, 29, 23, 59, 58).unwrap();
+        assert_eq!(format_sqlite_datetime(time), "2024-02-29 23:59:58");
+        assert_eq!(parse_sqlite_datetime("2024-02-29 23:59:58").unwrap(), time);
+        assert!(matches!(
+            parse_sqlite_datetime("2024-02-29T23:59:58Z"),
+            Err(AppError::Database(_))
+        ));
+    }
+}
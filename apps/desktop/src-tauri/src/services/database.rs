@@ -0,0 +1,482 @@
+use crate::types::errors::AppError;
+use sqlx::migrate::{Migration, MigrationSource, MigrationType, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::Duration;
+
+/// Connection string used by `tauri-plugin-sql` for the app database
+pub const DB_URL: &str = "sqlite:aios.db";
+
+/// A schema migration shared by the SQL plugin (frontend) and the Rust services
+#[derive(Debug)]
+pub struct SchemaMigration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// Ordered list of schema migrations.
+///
+/// Both `tauri-plugin-sql` and [`Database::connect`] apply this list through sqlx's
+/// migrator, so they share the `_sqlx_migrations` bookkeeping table. Never edit an
+/// entry once released; append a new one instead.
+pub const MIGRATIONS: &[SchemaMigration] = &[
+    SchemaMigration {
+        version: 1,
+        description: "create_initial_tables",
+        sql: r#"
+                            CREATE TABLE IF NOT EXISTS runs (
+                                id TEXT PRIMARY KEY,
+                                task TEXT NOT NULL,
+                                status TEXT NOT NULL DEFAULT 'pending',
+                                started_at TEXT NOT NULL,
+                                completed_at TEXT,
+                                error TEXT
+                            );
+                            CREATE TABLE IF NOT EXISTS messages (
+                                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                                run_id TEXT NOT NULL,
+                                role TEXT NOT NULL,
+                                content TEXT NOT NULL,
+                                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                                FOREIGN KEY(run_id) REFERENCES runs(id)
+                            );
+                        "#,
+    },
+    SchemaMigration {
+        version: 2,
+        description: "create_messages_fts",
+        sql: r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+                content,
+                content='messages',
+                content_rowid='id',
+                tokenize='porter unicode61'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+                INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content)
+                VALUES ('delete', old.id, old.content);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content)
+                VALUES ('delete', old.id, old.content);
+                INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+            END;
+
+            -- Index messages written before this migration
+            INSERT INTO messages_fts(messages_fts) VALUES ('rebuild');
+        "#,
+    },
+    SchemaMigration {
+        version: 3,
+        description: "create_memories",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS memories (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                namespace TEXT NOT NULL DEFAULT 'default',
+                key TEXT,
+                run_id TEXT,
+                content TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                dimensions INTEGER NOT NULL,
+                metadata TEXT NOT NULL DEFAULT '{}',
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(namespace, key),
+                FOREIGN KEY(run_id) REFERENCES runs(id) ON DELETE SET NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_memories_namespace ON memories(namespace, dimensions);
+
+            CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(
+                content,
+                content='memories',
+                content_rowid='id',
+                tokenize='porter unicode61'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS memories_fts_insert AFTER INSERT ON memories BEGIN
+                INSERT INTO memories_fts(rowid, content) VALUES (new.id, new.content);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS memories_fts_delete AFTER DELETE ON memories BEGIN
+                INSERT INTO memories_fts(memories_fts, rowid, content)
+                VALUES ('delete', old.id, old.content);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS memories_fts_update AFTER UPDATE OF content ON memories BEGIN
+                INSERT INTO memories_fts(memories_fts, rowid, content)
+                VALUES ('delete', old.id, old.content);
+                INSERT INTO memories_fts(rowid, content) VALUES (new.id, new.content);
+            END;
+        "#,
+    },
+    SchemaMigration {
+        version: 4,
+        description: "create_message_parts",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS attachments (
+                hash TEXT PRIMARY KEY,
+                mime_type TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS message_parts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                message_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                text TEXT,
+                tool_use_id TEXT,
+                server TEXT,
+                tool TEXT,
+                data TEXT,
+                is_error INTEGER,
+                attachment_hash TEXT,
+                name TEXT,
+                UNIQUE(message_id, position),
+                FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE,
+                FOREIGN KEY(attachment_hash) REFERENCES attachments(hash)
+            );
+            CREATE INDEX IF NOT EXISTS idx_message_parts_tool_use ON message_parts(tool_use_id);
+            CREATE INDEX IF NOT EXISTS idx_message_parts_tool ON message_parts(server, tool);
+
+            -- Existing messages become a single text part
+            INSERT INTO message_parts (message_id, position, kind, text)
+            SELECT id, 0, 'text', content FROM messages;
+        "#,
+    },
+    SchemaMigration {
+        version: 5,
+        description: "create_run_events",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS run_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                run_id TEXT NOT NULL,
+                from_status TEXT,
+                to_status TEXT NOT NULL,
+                reason TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY(run_id) REFERENCES runs(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_run_events_run ON run_events(run_id, id);
+            CREATE INDEX IF NOT EXISTS idx_runs_status ON runs(status);
+
+            -- Statuses were free-form before the state machine
+            UPDATE runs SET status = CASE lower(trim(status))
+                WHEN 'canceled' THEN 'cancelled'
+                WHEN 'done' THEN 'completed'
+                WHEN 'complete' THEN 'completed'
+                WHEN 'succeeded' THEN 'completed'
+                WHEN 'success' THEN 'completed'
+                WHEN 'error' THEN 'failed'
+                ELSE lower(trim(status))
+            END;
+            UPDATE runs SET
+                error = COALESCE(error, 'Unknown status from an older version: ' || status),
+                completed_at = COALESCE(completed_at, datetime('now')),
+                status = 'failed'
+            WHERE status NOT IN
+                ('pending', 'queued', 'running', 'awaiting_input', 'completed', 'failed', 'cancelled');
+
+            -- Adding a CHECK constraint means rebuilding the table, and dropping
+            -- the old one would fire the foreign key actions of every table that
+            -- references runs; these triggers enforce the same check
+            CREATE TRIGGER IF NOT EXISTS runs_status_insert BEFORE INSERT ON runs
+            WHEN NEW.status NOT IN
+                ('pending', 'queued', 'running', 'awaiting_input', 'completed', 'failed', 'cancelled')
+            BEGIN
+                SELECT RAISE(ABORT, 'invalid run status');
+            END;
+            CREATE TRIGGER IF NOT EXISTS runs_status_update BEFORE UPDATE OF status ON runs
+            WHEN NEW.status NOT IN
+                ('pending', 'queued', 'running', 'awaiting_input', 'completed', 'failed', 'cancelled')
+            BEGIN
+                SELECT RAISE(ABORT, 'invalid run status');
+            END;
+        "#,
+    },
+    SchemaMigration {
+        version: 6,
+        description: "add_run_queue_columns",
+        sql: r#"
+            ALTER TABLE runs ADD COLUMN agent TEXT NOT NULL DEFAULT 'default';
+            ALTER TABLE runs ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE runs ADD COLUMN queued_at TEXT;
+            CREATE INDEX IF NOT EXISTS idx_runs_queue ON runs(status, priority DESC, queued_at);
+        "#,
+    },
+    SchemaMigration {
+        version: 7,
+        description: "create_triggers",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS triggers (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                task_template TEXT NOT NULL,
+                agent TEXT NOT NULL DEFAULT 'default',
+                priority INTEGER NOT NULL DEFAULT 0,
+                spec TEXT NOT NULL,
+                last_fired_at TEXT,
+                next_fire_at TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_triggers_kind ON triggers(kind, enabled);
+
+            CREATE TABLE IF NOT EXISTS trigger_firings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                trigger_id TEXT NOT NULL,
+                run_id TEXT,
+                scheduled_for TEXT,
+                detail TEXT,
+                error TEXT,
+                fired_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY(trigger_id) REFERENCES triggers(id) ON DELETE CASCADE,
+                FOREIGN KEY(run_id) REFERENCES runs(id) ON DELETE SET NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_trigger_firings_trigger ON trigger_firings(trigger_id, id);
+        "#,
+    },
+    SchemaMigration {
+        version: 8,
+        description: "create_webhook_deliveries",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                trigger_id TEXT NOT NULL,
+                outcome TEXT NOT NULL,
+                status_code INTEGER NOT NULL,
+                body_size INTEGER NOT NULL,
+                run_id TEXT,
+                error TEXT,
+                received_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY(trigger_id) REFERENCES triggers(id) ON DELETE CASCADE,
+                FOREIGN KEY(run_id) REFERENCES runs(id) ON DELETE SET NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_trigger
+                ON webhook_deliveries(trigger_id, id);
+        "#,
+    },
+    SchemaMigration {
+        version: 9,
+        description: "create_notifications",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS notifications (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                run_id TEXT,
+                kind TEXT NOT NULL,
+                title TEXT NOT NULL,
+                body TEXT NOT NULL,
+                actions TEXT NOT NULL DEFAULT '[]',
+                status TEXT NOT NULL,
+                error TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                dismissed_at TEXT,
+                FOREIGN KEY(run_id) REFERENCES runs(id) ON DELETE SET NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_notifications_run ON notifications(run_id);
+        "#,
+    },
+    SchemaMigration {
+        version: 10,
+        description: "add_trigger_revision",
+        sql: r#"
+            ALTER TABLE triggers ADD COLUMN revision INTEGER NOT NULL DEFAULT 0;
+        "#,
+    },
+];
+
+/// Adapter exposing schema migrations, normally all of [`MIGRATIONS`], as a
+/// sqlx migration source
+#[derive(Debug)]
+struct SchemaMigrations(&'static [SchemaMigration]);
+
+impl MigrationSource<'static> for SchemaMigrations {
+    #[allow(clippy::type_complexity)]
+    fn resolve(
+        self,
+    ) -> Pin<
+        Box<
+            dyn Future<Output = Result<Vec<Migration>, Box<dyn std::error::Error + Send + Sync>>>
+                + Send,
+        >,
+    > {
+        Box::pin(async move {
+            Ok(self
+                .0
+                .iter()
+                .map(|m| {
+                    Migration::new(
+                        m.version,
+                        m.description.into(),
+                        MigrationType::Simple,
+                        m.sql.into(),
+                        false,
+                    )
+                })
+                .collect())
+        })
+    }
+}
+
+/// Handle to the app SQLite database used by Rust-side services
+///
+/// Cloning is cheap: the underlying pool is reference counted.
+#[derive(Clone)]
+pub struct Database {
+    pool: SqlitePool,
+}
+
+impl Database {
+    /// Open (creating if needed) the database at `path` and apply pending migrations
+    #[tracing::instrument(skip_all, fields(path = %path.display()), err)]
+    pub async fn connect(path: &Path) -> Result<Self, AppError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(Duration::from_secs(5))
+            .foreign_keys(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await?;
+
+        Self::migrate(pool).await
+    }
+
+    /// Open a private in-memory database with all migrations applied
+    #[cfg(test)]
+    pub async fn in_memory() -> Result<Self, AppError> {
+        Self::migrate(Self::memory_pool().await?).await
+    }
+
+    /// Open a private in-memory database with the migrations up to `version`
+    /// applied, to test later ones against data written by older releases
+    #[cfg(test)]
+    pub async fn in_memory_at(version: i64) -> Result<Self, AppError> {
+        let pool = Self::memory_pool().await?;
+        let applied = MIGRATIONS
+            .iter()
+            .take_while(|m| m.version <= version)
+            .count();
+        Migrator::new(SchemaMigrations(&MIGRATIONS[..applied]))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to load migrations: {}", e)))?
+            .run(&pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to run migrations: {}", e)))?;
+        Ok(Self { pool })
+    }
+
+    /// Apply the remaining migrations to a database from [`Database::in_memory_at`]
+    #[cfg(test)]
+    pub async fn migrate_to_latest(self) -> Result<Self, AppError> {
+        Self::migrate(self.pool).await
+    }
+
+    #[cfg(test)]
+    async fn memory_pool() -> Result<SqlitePool, AppError> {
+        use std::str::FromStr;
+
+        let options = SqliteConnectOptions::from_str("sqlite::memory:")?.foreign_keys(true);
+        // Each connection to `:memory:` is a separate database, so keep exactly one
+        Ok(SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await?)
+    }
+
+    async fn migrate(pool: SqlitePool) -> Result<Self, AppError> {
+        let mut migrator = Migrator::new(SchemaMigrations(MIGRATIONS))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to load migrations: {}", e)))?;
+        // The SQL plugin may have applied migrations from a newer build
+        migrator.set_ignore_missing(true);
+        migrator
+            .run(&pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to run migrations: {}", e)))?;
+
+        Ok(Self { pool })
+    }
+
+    /// Get the underlying connection pool
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+/// Get the database path used by `tauri-plugin-sql` for [`DB_URL`]
+pub fn default_db_path(app_config_dir: &Path) -> PathBuf {
+    app_config_dir.join("aios.db")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn free_form_run_statuses_are_normalized_and_then_rejected() {
+        // Runs written before the state machine existed
+        let database = Database::in_memory_at(4).await.unwrap();
+        for (id, status) in [
+            ("a", "completed"),
+            ("b", " Running"),
+            ("c", "done"),
+            ("d", "canceled"),
+            ("e", "stuck"),
+        ] {
+            sqlx::query(
+                "INSERT INTO runs (id, task, status, started_at) VALUES (?, 'task', ?, datetime('now'))",
+            )
+            .bind(id)
+            .bind(status)
+            .execute(database.pool())
+            .await
+            .unwrap();
+        }
+
+        let database = database.migrate_to_latest().await.unwrap();
+        let rows: Vec<(String, String, Option<String>)> =
+            sqlx::query_as("SELECT id, status, error FROM runs ORDER BY id")
+                .fetch_all(database.pool())
+                .await
+                .unwrap();
+        let statuses: Vec<_> = rows.iter().map(|(_, status, _)| status.as_str()).collect();
+        assert_eq!(
+            statuses,
+            ["completed", "running", "completed", "cancelled", "failed"]
+        );
+        assert!(rows[4].2.as_deref().unwrap().contains("stuck"));
+
+        let insert = sqlx::query(
+            "INSERT INTO runs (id, task, status, started_at) VALUES ('f', 'task', 'paused', datetime('now'))",
+        )
+        .execute(database.pool())
+        .await;
+        assert!(insert.is_err());
+        let update = sqlx::query("UPDATE runs SET status = 'Completed' WHERE id = 'a'")
+            .execute(database.pool())
+            .await;
+        assert!(update.is_err());
+    }
+}
@@ -0,0 +1,534 @@
+use crate::services::triggers::TriggerStore;
+use crate::types::errors::AppError;
+use crate::types::trigger::{FileEventKind, Trigger, TriggerSpec};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// Upper bound on a file watch trigger's debounce window
+pub const MAX_DEBOUNCE_MS: u64 = 60_000;
+/// A burst is cut off after this many debounce windows even if changes keep coming
+const MAX_DEBOUNCE_WINDOWS: u32 = 10;
+
+/// A watched path or glob pattern from a `file_watch` trigger
+#[derive(Debug, Clone)]
+pub struct WatchPattern {
+    /// Directory or file handed to the OS watcher
+    root: PathBuf,
+    recursive: bool,
+    glob: Option<glob::Pattern>,
+}
+
+impl WatchPattern {
+    /// Parse an absolute path or glob pattern
+    ///
+    /// Globs are watched from their longest literal prefix, recursively when the
+    /// pattern spans more than one directory level.
+    pub fn parse(pattern: &str, recursive: bool) -> Result<Self, AppError> {
+        let path = Path::new(pattern);
+        if !path.is_absolute() {
+            return Err(AppError::Validation(format!(
+                "Watch path '{}' must be absolute",
+                pattern
+            )));
+        }
+        if !is_glob(pattern) {
+            return Ok(Self {
+                root: path.to_path_buf(),
+                recursive,
+                glob: None,
+            });
+        }
+
+        let glob = glob::Pattern::new(pattern).map_err(|e| {
+            AppError::Validation(format!("Invalid glob pattern '{}': {}", pattern, e))
+        })?;
+        let mut root = PathBuf::new();
+        let mut components = path.components().peekable();
+        while let Some(component) =
+            components.next_if(|c| !is_glob(&c.as_os_str().to_string_lossy()))
+        {
+            root.push(component);
+        }
+        let recursive = components.count() > 1 || pattern.contains("**");
+
+        Ok(Self {
+            root,
+            recursive,
+            glob: Some(glob),
+        })
+    }
+
+    /// Whether a changed path is covered by this pattern
+    pub fn matches(&self, path: &Path) -> bool {
+        match &self.glob {
+            Some(glob) => glob.matches_path_with(
+                path,
+                glob::MatchOptions {
+                    require_literal_separator: true,
+                    ..Default::default()
+                },
+            ),
+            None => path.starts_with(&self.root),
+        }
+    }
+}
+
+fn is_glob(value: &str) -> bool {
+    value.contains(['*', '?', '['])
+}
+
+/// Map a notify event to the change kind triggers filter on
+fn event_kind(kind: &EventKind) -> Option<FileEventKind> {
+    match kind {
+        EventKind::Create(_) => Some(FileEventKind::Create),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => Some(FileEventKind::Delete),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => Some(FileEventKind::Create),
+        EventKind::Modify(_) => Some(FileEventKind::Modify),
+        EventKind::Remove(_) => Some(FileEventKind::Delete),
+        _ => None,
+    }
+}
+
+/// A raw change reported by the OS watcher of one trigger
+struct WatchEvent {
+    trigger_id: String,
+    kind: FileEventKind,
+    paths: Vec<PathBuf>,
+}
+
+/// Which changes a trigger reacts to, and how long it waits for a burst to settle
+struct WatchFilter {
+    patterns: Vec<WatchPattern>,
+    events: Vec<FileEventKind>,
+    debounce: Duration,
+}
+
+/// An enabled `file_watch` trigger and its OS watcher
+struct ActiveWatch {
+    trigger: Trigger,
+    filter: WatchFilter,
+    // Dropping the watcher stops it
+    _watcher: RecommendedWatcher,
+}
+
+/// Changes collected during a burst, fired once the burst settles
+struct PendingFiring {
+    paths: BTreeSet<PathBuf>,
+    kinds: BTreeSet<FileEventKind>,
+    deadline: Instant,
+    cutoff: Instant,
+}
+
+/// Fires `file_watch` triggers when matching files change
+///
+/// Changes are debounced per trigger: a trigger fires once its debounce window
+/// passes without further matching changes, with every path changed during the
+/// burst available to the task template.
+#[derive(Clone)]
+pub struct FileWatcher {
+    trigger_store: TriggerStore,
+}
+
+impl FileWatcher {
+    /// Create a new FileWatcher for the triggers in `trigger_store`
+    pub fn new(trigger_store: TriggerStore) -> Self {
+        Self { trigger_store }
+    }
+
+    /// Watch and fire triggers until the trigger store is dropped
+    pub async fn run(&self) {
+        let mut changes = self.trigger_store.subscribe();
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let mut watches = self.load(&events_tx).await;
+        let mut debouncer = Debouncer::default();
+
+        loop {
+            let next_deadline = debouncer.next_deadline();
+
+            tokio::select! {
+                changed = changes.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    watches = self.load(&events_tx).await;
+                    debouncer.pending.retain(|id, _| watches.contains_key(id));
+                }
+                Some(event) = events_rx.recv() => {
+                    if let Some(watch) = watches.get(&event.trigger_id) {
+                        debouncer.collect(&watch.filter, event);
+                    }
+                }
+                _ = tokio::time::sleep_until(next_deadline.unwrap_or_else(Instant::now)),
+                    if next_deadline.is_some() =>
+                {
+                    for (id, firing) in debouncer.take_due() {
+                        if let Some(watch) = watches.get(&id) {
+                            self.fire(&watch.trigger, firing).await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start OS watchers for all enabled `file_watch` triggers
+    async fn load(
+        &self,
+        events_tx: &mpsc::UnboundedSender<WatchEvent>,
+    ) -> HashMap<String, ActiveWatch> {
+        let triggers = match self.trigger_store.list(Some("file_watch")).await {
+            Ok(triggers) => triggers,
+            Err(e) => {
+                tracing::warn!("Failed to load file watch triggers: {}", e);
+                return HashMap::new();
+            }
+        };
+
+        let mut watches = HashMap::new();
+        for trigger in triggers.into_iter().filter(|t| t.enabled) {
+            let id = trigger.id.clone();
+            let name = trigger.name.clone();
+            match watch_trigger(trigger, events_tx.clone()) {
+                Ok(watch) => {
+                    watches.insert(id, watch);
+                }
+                Err(e) => tracing::warn!("Failed to watch files for trigger '{}': {}", name, e),
+            }
+        }
+        watches
+    }
+
+    async fn fire(&self, trigger: &Trigger, firing: PendingFiring) {
+        let paths: Vec<String> = firing
+            .paths
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        let kinds: Vec<&str> = firing.kinds.iter().map(|k| k.as_str()).collect();
+        let detail = serde_json::json!({ "paths": paths, "events": kinds });
+
+        let result = self
+            .trigger_store
+            .fire(
+                trigger,
+                |key| match key {
+                    "trigger_name" => Some(trigger.name.clone()),
+                    "path" => paths.first().cloned(),
+                    "paths" => Some(paths.join("\n")),
+                    "events" => Some(kinds.join(", ")),
+                    _ => None,
+                },
+                None,
+                Some(detail),
+            )
+            .await;
+        match result {
+            Ok(firing) => {
+                if let Some(error) = firing.error {
+                    tracing::warn!(
+                        "Trigger '{}' failed to submit a run: {}",
+                        trigger.name,
+                        error
+                    );
+                }
+            }
+            Err(e) => tracing::warn!("Failed to fire trigger '{}': {}", trigger.name, e),
+        }
+    }
+}
+
+/// Create the OS watcher for one trigger, forwarding its events to `events_tx`
+fn watch_trigger(
+    trigger: Trigger,
+    events_tx: mpsc::UnboundedSender<WatchEvent>,
+) -> Result<ActiveWatch, AppError> {
+    let TriggerSpec::FileWatch {
+        paths,
+        recursive,
+        events,
+        debounce_ms,
+    } = &trigger.spec
+    else {
+        return Err(AppError::InvalidState(format!(
+            "Trigger '{}' is not a file watch trigger",
+            trigger.name
+        )));
+    };
+    let patterns = paths
+        .iter()
+        .map(|path| WatchPattern::parse(path, *recursive))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let trigger_id = trigger.id.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if let Some(kind) = event_kind(&event.kind) {
+            let _ = events_tx.send(WatchEvent {
+                trigger_id: trigger_id.clone(),
+                kind,
+                paths: event.paths,
+            });
+        }
+    })
+    .map_err(|e| AppError::Io(format!("Failed to create file watcher: {}", e)))?;
+
+    for pattern in &patterns {
+        let mode = if pattern.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher.watch(&pattern.root, mode).map_err(|e| {
+            AppError::Io(format!(
+                "Failed to watch '{}': {}",
+                pattern.root.display(),
+                e
+            ))
+        })?;
+    }
+
+    Ok(ActiveWatch {
+        filter: WatchFilter {
+            patterns,
+            events: events.clone(),
+            debounce: Duration::from_millis(*debounce_ms),
+        },
+        trigger,
+        _watcher: watcher,
+    })
+}
+
+/// Bursts of changes per trigger that have not fired yet
+#[derive(Default)]
+struct Debouncer {
+    pending: HashMap<String, PendingFiring>,
+}
+
+impl Debouncer {
+    /// Add a matching change to the trigger's pending burst
+    fn collect(&mut self, filter: &WatchFilter, event: WatchEvent) {
+        if !filter.events.is_empty() && !filter.events.contains(&event.kind) {
+            return;
+        }
+        let matched: Vec<PathBuf> = event
+            .paths
+            .into_iter()
+            .filter(|path| filter.patterns.iter().any(|p| p.matches(path)))
+            .collect();
+        if matched.is_empty() {
+            return;
+        }
+
+        let now = Instant::now();
+        let firing = self
+            .pending
+            .entry(event.trigger_id)
+            .or_insert_with(|| PendingFiring {
+                paths: BTreeSet::new(),
+                kinds: BTreeSet::new(),
+                deadline: now,
+                cutoff: now + filter.debounce * MAX_DEBOUNCE_WINDOWS,
+            });
+        firing.paths.extend(matched);
+        firing.kinds.insert(event.kind);
+        firing.deadline = now + filter.debounce;
+    }
+
+    /// When the next pending burst is due to fire
+    fn next_deadline(&self) -> Option<Instant> {
+        self.pending
+            .values()
+            .map(|p| p.deadline.min(p.cutoff))
+            .min()
+    }
+
+    /// Remove and return the bursts that are due to fire
+    fn take_due(&mut self) -> Vec<(String, PendingFiring)> {
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| p.deadline.min(p.cutoff) <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        due.into_iter()
+            .filter_map(|id| self.pending.remove_entry(&id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, DataChange, RemoveKind};
+
+    #[test]
+    fn relative_paths_are_rejected() {
+        assert!(matches!(
+            WatchPattern::parse("notes/*.md", false),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            WatchPattern::parse("notes", true),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn plain_paths_watch_themselves() {
+        let pattern = WatchPattern::parse("/home/me/notes", true).unwrap();
+        assert_eq!(pattern.root, Path::new("/home/me/notes"));
+        assert!(pattern.recursive);
+        assert!(pattern.glob.is_none());
+        assert!(pattern.matches(Path::new("/home/me/notes/a/b.md")));
+        assert!(!pattern.matches(Path::new("/home/me/other.md")));
+    }
+
+    #[test]
+    fn globs_watch_their_literal_prefix() {
+        let pattern = WatchPattern::parse("/home/me/notes/*.md", true).unwrap();
+        assert_eq!(pattern.root, Path::new("/home/me/notes"));
+        assert!(!pattern.recursive);
+        assert!(pattern.matches(Path::new("/home/me/notes/a.md")));
+        assert!(!pattern.matches(Path::new("/home/me/notes/sub/a.md")));
+        assert!(!pattern.matches(Path::new("/home/me/notes/a.txt")));
+
+        let pattern = WatchPattern::parse("/home/me/*/todo.md", false).unwrap();
+        assert_eq!(pattern.root, Path::new("/home/me"));
+        assert!(pattern.recursive);
+
+        let pattern = WatchPattern::parse("/home/me/notes/**/*.md", false).unwrap();
+        assert_eq!(pattern.root, Path::new("/home/me/notes"));
+        assert!(pattern.recursive);
+        assert!(pattern.matches(Path::new("/home/me/notes/a/b/c.md")));
+    }
+
+    #[test]
+    fn renames_map_to_delete_and_create() {
+        assert_eq!(
+            event_kind(&EventKind::Modify(ModifyKind::Name(RenameMode::From))),
+            Some(FileEventKind::Delete)
+        );
+        assert_eq!(
+            event_kind(&EventKind::Modify(ModifyKind::Name(RenameMode::To))),
+            Some(FileEventKind::Create)
+        );
+        assert_eq!(
+            event_kind(&EventKind::Modify(ModifyKind::Name(RenameMode::Both))),
+            Some(FileEventKind::Modify)
+        );
+        assert_eq!(
+            event_kind(&EventKind::Modify(ModifyKind::Data(DataChange::Content))),
+            Some(FileEventKind::Modify)
+        );
+        assert_eq!(
+            event_kind(&EventKind::Create(CreateKind::File)),
+            Some(FileEventKind::Create)
+        );
+        assert_eq!(
+            event_kind(&EventKind::Remove(RemoveKind::File)),
+            Some(FileEventKind::Delete)
+        );
+        assert_eq!(event_kind(&EventKind::Access(AccessKind::Read)), None);
+    }
+
+    fn notes_filter(events: Vec<FileEventKind>) -> WatchFilter {
+        WatchFilter {
+            patterns: vec![WatchPattern::parse("/home/me/notes/*.md", false).unwrap()],
+            events,
+            debounce: Duration::from_millis(300),
+        }
+    }
+
+    fn change(kind: FileEventKind, name: &str) -> WatchEvent {
+        WatchEvent {
+            trigger_id: "notes".to_string(),
+            kind,
+            paths: vec![Path::new("/home/me/notes").join(name)],
+        }
+    }
+
+    fn paths(firing: &PendingFiring) -> Vec<&str> {
+        firing
+            .paths
+            .iter()
+            .map(|path| path.to_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn burst_of_changes_fires_once() {
+        let filter = notes_filter(Vec::new());
+        let mut debouncer = Debouncer::default();
+        let start = Instant::now();
+
+        for name in ["a.md", "b.md", "a.md", "ignored.txt"] {
+            debouncer.collect(&filter, change(FileEventKind::Modify, name));
+            tokio::time::advance(Duration::from_millis(50)).await;
+        }
+        // The last matching change restarted the window
+        assert_eq!(
+            debouncer.next_deadline(),
+            Some(start + Duration::from_millis(100 + 300))
+        );
+        assert!(debouncer.take_due().is_empty());
+
+        tokio::time::sleep_until(debouncer.next_deadline().unwrap()).await;
+        let due = debouncer.take_due();
+        assert_eq!(due.len(), 1);
+        let (id, firing) = &due[0];
+        assert_eq!(id, "notes");
+        assert_eq!(
+            paths(firing),
+            ["/home/me/notes/a.md", "/home/me/notes/b.md"]
+        );
+        assert_eq!(
+            firing.kinds.iter().collect::<Vec<_>>(),
+            [&FileEventKind::Modify]
+        );
+        assert_eq!(debouncer.next_deadline(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn endless_changes_fire_at_the_cutoff() {
+        let filter = notes_filter(Vec::new());
+        let mut debouncer = Debouncer::default();
+        let start = Instant::now();
+
+        let mut fired_at = None;
+        while fired_at.is_none() {
+            debouncer.collect(&filter, change(FileEventKind::Modify, "a.md"));
+            tokio::time::advance(Duration::from_millis(100)).await;
+            if !debouncer.take_due().is_empty() {
+                fired_at = Some(Instant::now());
+            }
+        }
+        assert_eq!(
+            fired_at,
+            Some(start + Duration::from_millis(300) * MAX_DEBOUNCE_WINDOWS)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unwatched_event_kinds_are_ignored() {
+        let filter = notes_filter(vec![FileEventKind::Create]);
+        let mut debouncer = Debouncer::default();
+
+        debouncer.collect(&filter, change(FileEventKind::Modify, "a.md"));
+        debouncer.collect(&filter, change(FileEventKind::Delete, "b.md"));
+        assert_eq!(debouncer.next_deadline(), None);
+
+        debouncer.collect(&filter, change(FileEventKind::Create, "c.md"));
+        tokio::time::advance(Duration::from_millis(300)).await;
+        let due = debouncer.take_due();
+        assert_eq!(due.len(), 1);
+        assert_eq!(paths(&due[0].1), ["/home/me/notes/c.md"]);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::types::errors::AppError;
+use crate::types::shortcut::{Accelerator, ShortcutAction, ShortcutSettings, ShortcutStatus};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+/// Registered accelerator and the last registration failure
+struct Registration {
+    settings: ShortcutSettings,
+    registered: Option<Accelerator>,
+    error: Option<String>,
+}
+
+/// Registers the global shortcut that summons the main window
+pub struct GlobalShortcutService {
+    app: AppHandle,
+    registration: Mutex<Registration>,
+    /// Read by the shortcut handler on every press
+    action: Arc<RwLock<ShortcutAction>>,
+}
+
+impl GlobalShortcutService {
+    /// Create the service; nothing is registered until `register`
+    pub fn new(app: AppHandle, settings: ShortcutSettings) -> Self {
+        Self {
+            app,
+            action: Arc::new(RwLock::new(settings.action)),
+            registration: Mutex::new(Registration {
+                settings,
+                registered: None,
+                error: None,
+            }),
+        }
+    }
+
+    /// Register the configured accelerator
+    ///
+    /// A conflict is recorded in the status rather than failing startup.
+    pub fn register(&self) {
+        let mut registration = self
+            .registration
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !registration.settings.enabled {
+            return;
+        }
+
+        match registration
+            .settings
+            .accelerator
+            .parse::<Accelerator>()
+            .and_then(|accelerator| self.register_accelerator(&accelerator).map(|_| accelerator))
+        {
+            Ok(accelerator) => {
+                registration.registered = Some(accelerator);
+                registration.error = None;
+            }
+            Err(e) => {
+                tracing::warn!("Global shortcut not registered: {}", e);
+                registration.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Current preferences and registration state
+    pub fn status(&self) -> ShortcutStatus {
+        let registration = self
+            .registration
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        status_of(&registration)
+    }
+
+    /// Apply new preferences, normalizing the accelerator
+    ///
+    /// The new accelerator is registered before the old one is released, so a
+    /// conflict leaves the previous shortcut working. Persisting is up to the
+    /// caller once this succeeds.
+    pub fn apply(&self, mut settings: ShortcutSettings) -> Result<ShortcutStatus, AppError> {
+        let accelerator: Accelerator = settings.accelerator.parse()?;
+        settings.accelerator = accelerator.to_string();
+
+        let mut registration = self
+            .registration
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let unchanged = registration.registered.as_ref() == Some(&accelerator);
+
+        if settings.enabled && !unchanged {
+            self.register_accelerator(&accelerator)?;
+        }
+        if !settings.enabled || !unchanged {
+            if let Some(previous) = registration.registered.take() {
+                self.unregister_accelerator(&previous);
+            }
+        }
+        if settings.enabled {
+            registration.registered = Some(accelerator);
+        }
+
+        *self.action.write().unwrap_or_else(PoisonError::into_inner) = settings.action;
+        registration.settings = settings;
+        registration.error = None;
+
+        Ok(status_of(&registration))
+    }
+
+    fn register_accelerator(&self, accelerator: &Accelerator) -> Result<(), AppError> {
+        let shortcut = accelerator.to_string();
+        let global_shortcut = self.app.global_shortcut();
+
+        if global_shortcut.is_registered(shortcut.as_str()) {
+            return Err(AppError::InvalidState(format!(
+                "Shortcut '{}' is already registered by this application",
+                shortcut
+            )));
+        }
+
+        let action = self.action.clone();
+        global_shortcut
+            .on_shortcut(shortcut.as_str(), move |app, _shortcut, event| {
+                if event.state == ShortcutState::Pressed {
+                    apply_to_main_window(
+                        app,
+                        *action.read().unwrap_or_else(PoisonError::into_inner),
+                    );
+                }
+            })
+            .map_err(|e| {
+                AppError::InvalidState(format!(
+                    "Shortcut '{}' conflicts with another application: {}",
+                    shortcut, e
+                ))
+            })
+    }
+
+    fn unregister_accelerator(&self, accelerator: &Accelerator) {
+        let shortcut = accelerator.to_string();
+        if let Err(e) = self.app.global_shortcut().unregister(shortcut.as_str()) {
+            tracing::warn!("Failed to unregister shortcut '{}': {}", shortcut, e);
+        }
+    }
+}
+
+fn status_of(registration: &Registration) -> ShortcutStatus {
+    ShortcutStatus {
+        settings: registration.settings.clone(),
+        registered: registration.registered.as_ref().map(|a| a.to_string()),
+        error: registration.error.clone(),
+    }
+}
+
+/// Show, focus or hide the main window according to the shortcut action
+pub fn apply_to_main_window(app: &AppHandle, action: ShortcutAction) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    match action {
+        ShortcutAction::Toggle => {
+            let visible = window.is_visible().unwrap_or(false);
+            if visible && window.is_focused().unwrap_or(false) {
+                let _ = window.hide();
+            } else {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        ShortcutAction::Show => {
+            let _ = window.show();
+        }
+        ShortcutAction::Focus => {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+}
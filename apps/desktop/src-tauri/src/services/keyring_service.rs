@@ -2,12 +2,27 @@ use crate::types::errors::AppError;
 use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 /// Service for managing secrets in the system keyring
+///
+/// Shared behind an `Arc`; only the known key list and the redaction cache
+/// are locked, never across a keyring call, so an OS unlock prompt does not
+/// block other callers.
 pub struct KeyringService {
     service_name: String,
     keys_file_path: PathBuf,
-    known_keys: HashSet<String>,
+    known_keys: Mutex<HashSet<String>>,
+    redaction: Mutex<RedactionCache>,
+}
+
+/// Secret values kept in memory for redaction, so listings and log reads
+/// don't go to the keyring every time
+#[derive(Default)]
+struct RedactionCache {
+    /// Bumped by every change, so a read that raced one is not cached
+    generation: u64,
+    values: Option<Vec<String>>,
 }
 
 impl KeyringService {
@@ -29,57 +44,168 @@ impl KeyringService {
         Ok(Self {
             service_name: service_name.to_string(),
             keys_file_path,
-            known_keys,
+            known_keys: Mutex::new(known_keys),
+            redaction: Mutex::new(RedactionCache::default()),
         })
     }
 
+    /// Run keyring operations on the blocking thread pool
+    ///
+    /// Keyring backends can block for as long as an OS unlock prompt is open,
+    /// which must not stall the async runtime.
+    pub async fn run_blocking<T, F>(self: &Arc<Self>, op: F) -> Result<T, AppError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&KeyringService) -> Result<T, AppError> + Send + 'static,
+    {
+        let keyring = self.clone();
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || span.in_scope(|| op(&keyring)))
+            .await
+            .map_err(|e| AppError::Keyring(format!("Keyring task failed: {}", e)))?
+    }
+
     /// Set a secret in the keyring
-    pub fn set_secret(&mut self, key: &str, value: &str) -> Result<(), AppError> {
+    #[tracing::instrument(skip_all, fields(service = %self.service_name, key = %key), err(level = "warn"))]
+    pub fn set_secret(&self, key: &str, value: &str) -> Result<(), AppError> {
         let entry = keyring::Entry::new(&self.service_name, key)?;
         entry.set_password(value)?;
 
         // Add key to known keys and persist
-        self.known_keys.insert(key.to_string());
-        self.save_keys()?;
+        let mut known_keys = self.known_keys();
+        known_keys.insert(key.to_string());
+        self.save_keys(&known_keys)?;
+        drop(known_keys);
+        self.invalidate_redaction();
 
         Ok(())
     }
 
     /// Get a secret from the keyring
+    #[tracing::instrument(skip_all, fields(service = %self.service_name, key = %key), err(level = "warn"))]
     pub fn get_secret(&self, key: &str) -> Result<String, AppError> {
         let entry = keyring::Entry::new(&self.service_name, key)?;
-        Ok(entry.get_password()?)
+        entry.get_password().map_err(|e| with_key(e, key))
     }
 
     /// Delete a secret from the keyring
-    pub fn delete_secret(&mut self, key: &str) -> Result<(), AppError> {
+    #[tracing::instrument(skip_all, fields(service = %self.service_name, key = %key), err(level = "warn"))]
+    pub fn delete_secret(&self, key: &str) -> Result<(), AppError> {
         let entry = keyring::Entry::new(&self.service_name, key)?;
-        entry.delete_credential()?;
+        entry.delete_credential().map_err(|e| with_key(e, key))?;
 
         // Remove key from known keys and persist
-        self.known_keys.remove(key);
-        self.save_keys()?;
-
-        Ok(())
+        self.remove_key(key)
     }
 
     /// List all known secret keys
     pub fn list_keys(&self) -> Vec<String> {
-        self.known_keys.iter().cloned().collect()
+        self.known_keys().iter().cloned().collect()
+    }
+
+    /// Read every known secret, skipping any that cannot be read
+    pub fn read_available(&self) -> Vec<(String, String)> {
+        self.list_keys()
+            .into_iter()
+            .filter_map(|key| self.get_secret(&key).ok().map(|value| (key, value)))
+            .collect()
+    }
+
+    /// Values of the known secrets, to redact from text shown to the user
+    ///
+    /// Read from the keyring on first use, then from memory until a secret is
+    /// set or deleted through this service. Unreadable secrets are skipped,
+    /// and the values are only cached once every secret could be read, so a
+    /// keyring that was locked on first use is asked again next time.
+    pub fn redaction_values(&self) -> Vec<String> {
+        let generation = {
+            let cache = self.redaction();
+            if let Some(values) = &cache.values {
+                return values.clone();
+            }
+            cache.generation
+        };
+
+        let keys = self.list_keys();
+        let values: Vec<String> = keys
+            .iter()
+            .filter_map(|key| self.get_secret(key).ok())
+            .collect();
+        let mut cache = self.redaction();
+        if values.len() == keys.len() && cache.generation == generation {
+            cache.values = Some(values.clone());
+        }
+        values
     }
 
     /// Remove a key from the known keys list (used for cleanup of stale entries)
-    pub fn remove_key(&mut self, key: &str) -> Result<(), AppError> {
-        self.known_keys.remove(key);
-        self.save_keys()?;
+    pub fn remove_key(&self, key: &str) -> Result<(), AppError> {
+        let mut known_keys = self.known_keys();
+        known_keys.remove(key);
+        self.save_keys(&known_keys)?;
+        drop(known_keys);
+        self.invalidate_redaction();
         Ok(())
     }
 
-    /// Save the known keys list to disk
-    fn save_keys(&self) -> Result<(), AppError> {
-        let json = serde_json::to_string_pretty(&self.known_keys)
-            .map_err(|e| AppError::Io(e.to_string()))?;
+    fn redaction(&self) -> MutexGuard<'_, RedactionCache> {
+        self.redaction
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn invalidate_redaction(&self) {
+        let mut cache = self.redaction();
+        cache.generation += 1;
+        cache.values = None;
+    }
+
+    /// Lock the known keys; the set is always valid, so a panic elsewhere
+    /// while it was held does not poison it for later callers
+    fn known_keys(&self) -> MutexGuard<'_, HashSet<String>> {
+        self.known_keys
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Save the known keys list to disk; called with the list locked so
+    /// concurrent writers cannot reorder
+    fn save_keys(&self, known_keys: &HashSet<String>) -> Result<(), AppError> {
+        let json =
+            serde_json::to_string_pretty(known_keys).map_err(|e| AppError::Io(e.to_string()))?;
         fs::write(&self.keys_file_path, json)?;
         Ok(())
     }
 }
+
+/// Name the missing key instead of the backend's generic "no entry" message
+fn with_key(err: keyring::Error, key: &str) -> AppError {
+    match AppError::from(err) {
+        AppError::KeyringNoEntry(_) => AppError::KeyringNoEntry(key.to_string()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redaction_values_are_not_cached_while_a_secret_is_unreadable() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("keyring_keys.json"),
+            r#"["aios-keyring-test-missing"]"#,
+        )
+        .unwrap();
+        let keyring = KeyringService::new("aios-keyring-test", dir.path().to_path_buf()).unwrap();
+
+        assert!(keyring.redaction_values().is_empty());
+        assert!(keyring.redaction().values.is_none());
+
+        // With nothing left to read, the (empty) result is complete
+        keyring.remove_key("aios-keyring-test-missing").unwrap();
+        assert!(keyring.redaction_values().is_empty());
+        assert_eq!(keyring.redaction().values, Some(Vec::new()));
+    }
+}
@@ -0,0 +1,601 @@
+use crate::types::errors::AppError;
+use crate::types::logging::{LogEntry, LoggingSettings};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tracing::level_filters::LevelFilter;
+use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+/// Log files are named `aios.<date>.log`
+const LOG_FILE_PREFIX: &str = "aios";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Replacement for redacted values
+const REDACTED: &str = "[REDACTED]";
+/// Field names whose values are always redacted, matched exactly or as a
+/// `_`-separated suffix
+const SENSITIVE_FIELDS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+];
+/// Field names containing any of these are redacted wherever they appear,
+/// e.g. `secret_key` or `aws_secret_access_key`
+const SENSITIVE_FRAGMENTS: &[&str] = &["secret", "password", "private_key"];
+/// Shorter secret values are not scrubbed from free text, where they would
+/// match ordinary words
+const MIN_REDACTED_LEN: usize = 4;
+/// Bytes read at a time when scanning a log file backwards
+const READ_BLOCK_BYTES: u64 = 64 * 1024;
+
+/// Installed tracing subscriber with a reloadable level filter
+pub struct Logging {
+    log_dir: PathBuf,
+    filter: reload::Handle<EnvFilter, Registry>,
+    /// Flushes buffered lines when dropped
+    _guard: WorkerGuard,
+}
+
+impl Logging {
+    /// Install the global subscriber, writing JSON lines to a daily rolling
+    /// file in `log_dir` and human-readable lines to stderr
+    pub fn init(log_dir: PathBuf, settings: &LoggingSettings) -> Result<Self, AppError> {
+        fs::create_dir_all(&log_dir)?;
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix(LOG_FILE_SUFFIX)
+            .max_log_files(settings.max_files.max(1))
+            .build(&log_dir)
+            .map_err(|e| AppError::Io(format!("Failed to open log file: {}", e)))?;
+        let (writer, guard) = tracing_appender::non_blocking(appender);
+        let (filter, handle) = reload::Layer::new(env_filter(settings)?);
+
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(
+                fmt::layer()
+                    .json()
+                    .with_current_span(false)
+                    .with_span_list(true)
+                    .with_writer(writer),
+            )
+            .with(fmt::layer().with_writer(std::io::stderr))
+            .try_init()
+            .map_err(|e| AppError::InvalidState(format!("Logging already initialized: {}", e)))?;
+
+        Ok(Self {
+            log_dir,
+            filter: handle,
+            _guard: guard,
+        })
+    }
+
+    /// Apply new levels; a changed `max_files` applies after restart
+    pub fn set_levels(&self, settings: &LoggingSettings) -> Result<(), AppError> {
+        self.filter
+            .reload(env_filter(settings)?)
+            .map_err(|e| AppError::InvalidState(format!("Failed to change log levels: {}", e)))
+    }
+
+    /// Read the newest entries at or above `min_level`, newest first, with
+    /// sensitive fields and the given secret values redacted
+    pub fn recent(
+        &self,
+        limit: usize,
+        min_level: Option<Level>,
+        secrets: &[String],
+    ) -> Result<Vec<LogEntry>, AppError> {
+        read_recent(&self.log_dir, limit, min_level, secrets)
+    }
+}
+
+/// Check logging settings before they are applied
+pub fn validate_settings(settings: &LoggingSettings) -> Result<(), AppError> {
+    if settings.max_files == 0 {
+        return Err(AppError::Validation(
+            "Logging max_files must be at least 1".to_string(),
+        ));
+    }
+    env_filter(settings).map(|_| ())
+}
+
+/// Build the filter directives: the default level, then one per module
+fn env_filter(settings: &LoggingSettings) -> Result<EnvFilter, AppError> {
+    let mut directives = vec![parse_level(&settings.level)?.to_string()];
+    for (module, level) in &settings.modules {
+        let valid_module = !module.is_empty()
+            && module
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':' || c == '-');
+        if !valid_module {
+            return Err(AppError::Validation(format!(
+                "Invalid log module '{}'",
+                module
+            )));
+        }
+        directives.push(format!("{}={}", module, parse_level(level)?));
+    }
+
+    EnvFilter::builder()
+        .parse(directives.join(","))
+        .map_err(|e| AppError::Validation(format!("Invalid log levels: {}", e)))
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, AppError> {
+    level.parse().map_err(|_| {
+        AppError::Validation(format!(
+            "Invalid log level '{}', expected off, error, warn, info, debug or trace",
+            level
+        ))
+    })
+}
+
+fn read_recent(
+    log_dir: &Path,
+    limit: usize,
+    min_level: Option<Level>,
+    secrets: &[String],
+) -> Result<Vec<LogEntry>, AppError> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(log_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.starts_with(&format!("{}.", LOG_FILE_PREFIX))
+                            && name.ends_with(&format!(".{}", LOG_FILE_SUFFIX))
+                    })
+            })
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    // Dated names sort chronologically
+    files.sort();
+
+    let mut entries = Vec::new();
+    // Newest first, reading only as much of each file as the limit needs
+    for file in files.iter().rev() {
+        for line in ReverseLines::open(file)? {
+            let Some(entry) = parse_entry(&line?, secrets) else {
+                continue;
+            };
+            if let Some(min_level) = min_level {
+                // More severe levels compare as smaller
+                if entry.level.parse::<Level>().map_or(true, |l| l > min_level) {
+                    continue;
+                }
+            }
+            entries.push(entry);
+            if entries.len() >= limit {
+                return Ok(entries);
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Lines of a file from last to first, read a block at a time from the end
+struct ReverseLines {
+    file: fs::File,
+    block_bytes: u64,
+    /// Offset up to which the file has not been read yet
+    unread: u64,
+    /// Start of the earliest line read so far, which may continue further back
+    partial: Vec<u8>,
+    /// Complete lines from the last block read, in file order
+    lines: Vec<String>,
+}
+
+impl ReverseLines {
+    fn open(path: &Path) -> std::io::Result<Self> {
+        let file = fs::File::open(path)?;
+        let unread = file.metadata()?.len();
+        Ok(Self {
+            file,
+            block_bytes: READ_BLOCK_BYTES,
+            unread,
+            partial: Vec::new(),
+            lines: Vec::new(),
+        })
+    }
+
+    fn read_block(&mut self) -> std::io::Result<()> {
+        let start = self.unread.saturating_sub(self.block_bytes);
+        let mut block = vec![0; (self.unread - start) as usize];
+        self.file.seek(SeekFrom::Start(start))?;
+        self.file.read_exact(&mut block)?;
+        block.append(&mut self.partial);
+        self.unread = start;
+
+        let mut lines = block.split(|b| *b == b'\n');
+        // Unless the file starts here, the first line began in an earlier block
+        self.partial = lines.next().unwrap_or_default().to_vec();
+        self.lines = lines
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+            .collect();
+        Ok(())
+    }
+}
+
+impl Iterator for ReverseLines {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.lines.pop() {
+                return Some(Ok(line));
+            }
+            if self.unread == 0 {
+                if self.partial.is_empty() {
+                    return None;
+                }
+                let line = std::mem::take(&mut self.partial);
+                return Some(Ok(String::from_utf8_lossy(&line).into_owned()));
+            }
+            if let Err(e) = self.read_block() {
+                self.unread = 0;
+                self.partial.clear();
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Parse one JSON log line, redacting as it goes
+fn parse_entry(line: &str, secrets: &[String]) -> Option<LogEntry> {
+    let mut record: Map<String, Value> = serde_json::from_str(line).ok()?;
+    let text = |record: &mut Map<String, Value>, key: &str| match record.remove(key) {
+        Some(Value::String(s)) => s,
+        _ => String::new(),
+    };
+
+    let timestamp = text(&mut record, "timestamp");
+    let level = text(&mut record, "level");
+    let target = text(&mut record, "target");
+    let mut fields = match record.remove("fields") {
+        Some(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    let mut spans = match record.remove("spans") {
+        Some(Value::Array(spans)) => spans,
+        _ => Vec::new(),
+    };
+
+    let mut message = match fields.remove("message") {
+        Some(Value::String(s)) => s,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    message = redact_text(&message, secrets);
+    for (key, value) in fields.iter_mut() {
+        redact_field(key, value, secrets);
+    }
+    for span in spans.iter_mut() {
+        redact(span, secrets);
+    }
+
+    Some(LogEntry {
+        timestamp,
+        level,
+        target,
+        message,
+        fields,
+        spans,
+    })
+}
+
+/// Redact sensitive fields and secret values anywhere in a JSON value
+pub fn redact(value: &mut Value, secrets: &[String]) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                redact_field(key, value, secrets);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact(item, secrets);
+            }
+        }
+        Value::String(s) => *s = redact_text(s, secrets),
+        _ => {}
+    }
+}
+
+fn redact_field(key: &str, value: &mut Value, secrets: &[String]) {
+    if is_sensitive(key) && !value.is_null() {
+        *value = Value::String(REDACTED.to_string());
+    } else {
+        redact(value, secrets);
+    }
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_FRAGMENTS
+        .iter()
+        .any(|fragment| key.contains(fragment))
+        || SENSITIVE_FIELDS
+            .iter()
+            .any(|field| key == *field || key.ends_with(&format!("_{}", field)))
+}
+
+/// Replace known secret values and bearer tokens in free text
+pub fn redact_text(text: &str, secrets: &[String]) -> String {
+    let mut text = text.to_string();
+    for secret in secrets {
+        if secret.len() >= MIN_REDACTED_LEN && text.contains(secret.as_str()) {
+            text = text.replace(secret.as_str(), REDACTED);
+        }
+    }
+
+    // Authorization headers that reached a log line
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = find_ignore_case(rest, "bearer ") {
+        let token_start = start + "bearer ".len();
+        redacted.push_str(&rest[..token_start]);
+        let token_len = rest[token_start..]
+            .find(|c: char| c.is_whitespace() || c == '"' || c == '\'')
+            .unwrap_or(rest.len() - token_start);
+        if token_len > 0 {
+            redacted.push_str(REDACTED);
+        }
+        rest = &rest[token_start + token_len..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// Render a command line for display, redacting secret values, bearer
+/// tokens and the values of sensitive flags such as `--api-key`
+pub fn redact_command_line(command: &str, args: &[String], secrets: &[String]) -> String {
+    let mut parts = vec![quote_arg(&redact_text(command, secrets))];
+    let mut redact_next = false;
+    for arg in args {
+        let shown = if redact_next {
+            REDACTED.to_string()
+        } else {
+            match arg.split_once('=') {
+                Some((flag, _)) if is_sensitive_flag(flag) => format!("{}={}", flag, REDACTED),
+                _ => redact_text(arg, secrets),
+            }
+        };
+        // `--token value` carries the secret in the next argument
+        redact_next = !arg.contains('=') && is_sensitive_flag(arg);
+        parts.push(quote_arg(&shown));
+    }
+    parts.join(" ")
+}
+
+fn is_sensitive_flag(arg: &str) -> bool {
+    let name = arg.trim_start_matches('-');
+    name.len() < arg.len() && is_sensitive(&name.replace('-', "_"))
+}
+
+fn quote_arg(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(char::is_whitespace) {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    } else {
+        arg.to_string()
+    }
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Get the default directory for log files
+pub fn default_log_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("logs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn secrets(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn sensitive_field_names_are_matched() {
+        for key in [
+            "password",
+            "Authorization",
+            "api_key",
+            "APIKEY",
+            "github_token",
+            "client_secret",
+            "OPENAI_API_KEY",
+            "secret_key",
+            "aws_secret_access_key",
+            "private_key",
+            "ssh_private_key_path",
+            "db_password_hash",
+        ] {
+            assert!(is_sensitive(key), "{}", key);
+        }
+        for key in ["tokens", "token_count", "tokenizer", "api_key_id", "name"] {
+            assert!(!is_sensitive(key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn secret_values_and_bearer_tokens_are_redacted_from_text() {
+        let secrets = secrets(&["sk-live-1234", "abc"]);
+
+        assert_eq!(
+            redact_text("calling with sk-live-1234 twice: sk-live-1234", &secrets),
+            "calling with [REDACTED] twice: [REDACTED]"
+        );
+        // Values shorter than MIN_REDACTED_LEN would match ordinary words
+        assert_eq!(redact_text("abc and abcd", &secrets), "abc and abcd");
+        assert_eq!(
+            redact_text(
+                r#"headers: {"Authorization": "Bearer eyJ.x.y"} BEARER t0k done"#,
+                &[]
+            ),
+            r#"headers: {"Authorization": "Bearer [REDACTED]"} BEARER [REDACTED] done"#
+        );
+        assert_eq!(redact_text("trailing bearer ", &[]), "trailing bearer ");
+    }
+
+    #[test]
+    fn sensitive_flags_are_redacted_from_command_lines() {
+        let args = secrets(&[
+            "--token",
+            "ghp_value",
+            "--api-key=sk-1",
+            "--port",
+            "8080",
+            "-password",
+            "hunter2",
+            "--name=my server",
+            "--header=x-sk-live-1234",
+        ]);
+
+        assert_eq!(
+            redact_command_line("npx", &args, &secrets(&["sk-live-1234"])),
+            "npx --token [REDACTED] --api-key=[REDACTED] --port 8080 -password [REDACTED] \
+             '--name=my server' --header=x-[REDACTED]"
+        );
+        // A flag with its value attached does not hide the next argument
+        assert_eq!(
+            redact_command_line("server", &secrets(&["--token=a", "visible"]), &[]),
+            "server --token=[REDACTED] visible"
+        );
+        // Only flags are matched by name
+        assert_eq!(
+            redact_command_line("server", &secrets(&["token", "visible"]), &[]),
+            "server token visible"
+        );
+    }
+
+    #[test]
+    fn entries_are_redacted_in_fields_and_nested_spans() {
+        let line = json!({
+            "timestamp": "2026-01-01T00:00:00Z",
+            "level": "WARN",
+            "target": "aios_desktop::services::mcp_manager",
+            "fields": {
+                "message": "failed with sk-live-1234",
+                "authorization": "Bearer abc",
+                "api_key": null,
+                "detail": { "client_secret": "x", "items": ["sk-live-1234"] },
+            },
+            "spans": [
+                { "name": "start_server", "server": "github" },
+                { "name": "request", "env": { "GITHUB_TOKEN": "ghp" }, "url": "https://x?sk-live-1234" },
+            ],
+        })
+        .to_string();
+
+        let entry = parse_entry(&line, &secrets(&["sk-live-1234"])).unwrap();
+        assert_eq!(entry.level, "WARN");
+        assert_eq!(entry.target, "aios_desktop::services::mcp_manager");
+        assert_eq!(entry.message, "failed with [REDACTED]");
+        assert_eq!(
+            Value::Object(entry.fields),
+            json!({
+                "authorization": "[REDACTED]",
+                "api_key": null,
+                "detail": { "client_secret": "[REDACTED]", "items": ["[REDACTED]"] },
+            })
+        );
+        assert_eq!(
+            entry.spans,
+            [
+                json!({ "name": "start_server", "server": "github" }),
+                json!({
+                    "name": "request",
+                    "env": { "GITHUB_TOKEN": "[REDACTED]" },
+                    "url": "https://x?[REDACTED]",
+                }),
+            ]
+        );
+
+        assert!(parse_entry("not json", &[]).is_none());
+    }
+
+    #[test]
+    fn recent_entries_are_newest_first_and_filtered_by_level() {
+        let dir = tempfile::tempdir().unwrap();
+        let line = |level: &str, message: &str| {
+            json!({ "level": level, "fields": { "message": message } }).to_string()
+        };
+        fs::write(
+            dir.path().join("aios.2026-01-01.log"),
+            [line("INFO", "one"), line("ERROR", "two")].join("\n"),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("aios.2026-01-02.log"),
+            [line("DEBUG", "three"), line("WARN", "four")].join("\n"),
+        )
+        .unwrap();
+        fs::write(dir.path().join("other.log"), line("ERROR", "ignored")).unwrap();
+
+        let messages = |entries: Vec<LogEntry>| -> Vec<String> {
+            entries.into_iter().map(|entry| entry.message).collect()
+        };
+        assert_eq!(
+            messages(read_recent(dir.path(), 10, None, &[]).unwrap()),
+            ["four", "three", "two", "one"]
+        );
+        assert_eq!(
+            messages(read_recent(dir.path(), 10, Some(Level::WARN), &[]).unwrap()),
+            ["four", "two"]
+        );
+        assert_eq!(
+            messages(read_recent(dir.path(), 1, Some(Level::INFO), &[]).unwrap()),
+            ["four"]
+        );
+        assert!(read_recent(&dir.path().join("missing"), 10, None, &[])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn lines_are_read_backwards_across_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("aios.2026-01-01.log");
+        let lines: Vec<String> = (0..50)
+            .map(|i| format!("line {} {}", i, "x".repeat(i % 13)))
+            .collect();
+
+        for trailer in ["", "\n"] {
+            fs::write(&path, lines.join("\n") + trailer).unwrap();
+            for block_bytes in [1, 7, 64, READ_BLOCK_BYTES] {
+                let mut reader = ReverseLines::open(&path).unwrap();
+                reader.block_bytes = block_bytes;
+                let read: Vec<String> = reader
+                    .map(Result::unwrap)
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                let expected: Vec<String> = lines.iter().rev().cloned().collect();
+                assert_eq!(read, expected, "block of {} bytes", block_bytes);
+            }
+        }
+
+        fs::write(&path, "").unwrap();
+        assert_eq!(ReverseLines::open(&path).unwrap().count(), 0);
+    }
+}
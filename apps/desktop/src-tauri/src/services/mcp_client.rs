@@ -0,0 +1,145 @@
+use crate::types::config::InitializeResult;
+use crate::types::errors::AppError;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{ChildStdin, ChildStdout};
+
+/// MCP protocol revision requested during the handshake
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// JSON-RPC connection to an MCP server over its stdio pipes
+///
+/// Messages are newline-delimited JSON. Requests are answered in order, so a
+/// single caller at a time is expected.
+pub struct McpConnection {
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    next_id: u64,
+}
+
+impl McpConnection {
+    pub fn new(stdin: ChildStdin, stdout: ChildStdout) -> Self {
+        Self {
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            next_id: 0,
+        }
+    }
+
+    /// Perform the `initialize` handshake and confirm it with the
+    /// `initialized` notification
+    pub async fn initialize(&mut self) -> Result<InitializeResult, AppError> {
+        let result = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "aios",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+        let result: InitializeResult = serde_json::from_value(result)
+            .map_err(|e| AppError::Protocol(format!("Invalid initialize response: {}", e)))?;
+
+        self.notify("notifications/initialized", json!({})).await?;
+        Ok(result)
+    }
+
+    /// List the server's tools, following pagination cursors
+    pub async fn list_tools(&mut self) -> Result<Vec<Value>, AppError> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let mut result = self.request("tools/list", params).await?;
+            match result.get_mut("tools").map(Value::take) {
+                Some(Value::Array(page)) => tools.extend(page),
+                _ => {
+                    return Err(AppError::Protocol(
+                        "Invalid tools/list response: missing tools".to_string(),
+                    ))
+                }
+            }
+            cursor = match result.get("nextCursor").and_then(Value::as_str) {
+                Some(next) => Some(next.to_string()),
+                None => return Ok(tools),
+            };
+        }
+    }
+
+    /// Send a `ping` and wait for the empty response
+    pub async fn ping(&mut self) -> Result<(), AppError> {
+        self.request("ping", json!({})).await.map(|_| ())
+    }
+
+    /// Send a request and wait for its response
+    ///
+    /// Notifications, requests from the server and non-JSON output lines
+    /// received in the meantime are skipped.
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value, AppError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))
+        .await?;
+
+        loop {
+            let line = self.stdout.next_line().await?.ok_or_else(|| {
+                AppError::Protocol(format!(
+                    "Server closed its output before answering '{}'",
+                    method
+                ))
+            })?;
+            let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                tracing::debug!("Skipping non-JSON server output: {}", line);
+                continue;
+            };
+            // Responses carry our id and no method
+            if message.get("method").is_some() || message.get("id") != Some(&json!(id)) {
+                continue;
+            }
+
+            if let Some(error) = message.get("error") {
+                let reason = error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string());
+                return Err(AppError::Protocol(format!(
+                    "'{}' failed: {}",
+                    method, reason
+                )));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    /// Send a notification, which has no response
+    pub async fn notify(&mut self, method: &str, params: Value) -> Result<(), AppError> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }))
+        .await
+    }
+
+    async fn send(&mut self, message: &Value) -> Result<(), AppError> {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.stdin.write_all(line.as_bytes()).await?;
+        self.stdin.flush().await?;
+        Ok(())
+    }
+}
@@ -1,24 +1,320 @@
-use crate::types::config::{McpConfig, McpServerConfig, McpServerStatus};
+use crate::services::logging;
+use crate::services::mcp_client::McpConnection;
+use crate::services::mcp_startup;
+use crate::services::process_metrics::{self, ProcessTable, TreeUsage};
+use crate::services::sandbox;
+use crate::types::config::{
+    HealthCheckConfig, InitializeResult, McpConfig, McpServerConfig, McpServerEntry,
+    McpServerStatus, Readiness, RestartPolicy, SandboxReport,
+};
 use crate::types::errors::AppError;
+use crate::types::metrics::{PingSample, ProcessSample, ServerHealth, ServerMetrics};
 use backoff::backoff::Backoff;
 use backoff::ExponentialBackoff;
-use std::collections::HashMap;
-use std::process::Stdio;
-use std::time::Duration;
-use tokio::process::{Child, Command};
-use tokio::sync::RwLock;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::{Child, ChildStderr, Command};
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::MissedTickBehavior;
+
+/// Time given to a server whose handshake failed to finish exiting
+const EXIT_SETTLE_TIME: Duration = Duration::from_millis(200);
+/// Lines of stderr kept for failure reasons
+const STDERR_TAIL_LINES: usize = 20;
+/// Time to wait for the stderr reader to drain after the process exits
+const STDERR_DRAIN_TIME: Duration = Duration::from_millis(500);
+/// Interval between startup probes
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+/// Interval between resource usage samples
+const METRICS_INTERVAL: Duration = Duration::from_secs(5);
+/// Samples kept per server, five minutes at the sampling interval
+const METRICS_HISTORY: usize = 60;
+/// Interval between checks for idle on-demand servers
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Granularity of per-server health check intervals
+const HEALTH_CHECK_TICK: Duration = Duration::from_secs(1);
+/// Pings kept per server
+const PING_HISTORY: usize = 60;
+/// Delay before an automatic restart, doubled for each earlier one within
+/// `RESTART_WINDOW`
+const RESTART_BACKOFF: Duration = Duration::from_secs(1);
+/// Automatic restarts allowed within `RESTART_WINDOW` before giving up
+const MAX_RESTARTS: usize = 5;
+const RESTART_WINDOW: Duration = Duration::from_secs(600);
 
 /// Process handle with metadata
 struct ManagedProcess {
     child: Child,
     retry_count: u32,
+    /// Open stdio connection when readiness was a handshake; locked for the
+    /// duration of each request
+    connection: Option<Arc<tokio::sync::Mutex<McpConnection>>>,
+    /// What the server reported during the handshake
+    info: Option<InitializeResult>,
+    /// Tools offered after the handshake, `None` without one or when listing
+    /// them failed
+    tool_count: Option<usize>,
+    stderr: StderrTail,
+    /// Protections in effect for the process
+    sandbox: SandboxReport,
+    started_at: DateTime<Utc>,
+    /// Start of the last tool call, for idle shutdown
+    last_used: Instant,
+    metrics: MetricsHistory,
+    health: HealthState,
+    /// Environment overrides the server was started with, reused when it is
+    /// restarted
+    env_overrides: HashMap<String, String>,
+}
+
+/// Health check results of a running server
+struct HealthState {
+    next_ping: Instant,
+    consecutive_failures: u32,
+    /// Set once `failure_threshold` pings in a row have failed, until one
+    /// succeeds
+    unhealthy: Option<String>,
+    samples: VecDeque<PingSample>,
+}
+
+impl HealthState {
+    fn new(first_ping: Instant) -> Self {
+        Self {
+            next_ping: first_ping,
+            consecutive_failures: 0,
+            unhealthy: None,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record a ping result, returning whether the server became healthy or
+    /// unhealthy
+    fn record(
+        &mut self,
+        sent: DateTime<Utc>,
+        result: Result<Duration, String>,
+        threshold: u32,
+    ) -> bool {
+        let was_unhealthy = self.unhealthy.is_some();
+        let sample = match result {
+            Ok(latency) => {
+                self.consecutive_failures = 0;
+                self.unhealthy = None;
+                PingSample {
+                    timestamp: sent.to_rfc3339(),
+                    latency_ms: Some(latency.as_secs_f64() * 1000.0),
+                    error: None,
+                }
+            }
+            Err(error) => {
+                self.consecutive_failures += 1;
+                if self.consecutive_failures >= threshold.max(1) && !was_unhealthy {
+                    self.unhealthy = Some(format!(
+                        "{} consecutive failed health checks, last: {}",
+                        self.consecutive_failures, error
+                    ));
+                }
+                PingSample {
+                    timestamp: sent.to_rfc3339(),
+                    latency_ms: None,
+                    error: Some(error),
+                }
+            }
+        };
+
+        if self.samples.len() == PING_HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        was_unhealthy != self.unhealthy.is_some()
+    }
+}
+
+/// Recent resource usage of a server's process tree
+struct MetricsHistory {
+    samples: VecDeque<ProcessSample>,
+    /// When CPU time was last read and its total then, for CPU percentages
+    last_cpu: (Instant, u64),
+}
+
+impl MetricsHistory {
+    /// Start the history at spawn time, so the first sample covers the
+    /// server's whole life
+    fn new(spawned: Instant) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            last_cpu: (spawned, 0),
+        }
+    }
+
+    fn record(&mut self, usage: TreeUsage, ticks_per_second: u64) {
+        let now = Instant::now();
+        let (last_at, last_ticks) = self.last_cpu;
+        let elapsed = now.duration_since(last_at).as_secs_f64();
+        // Ticks drop when a child exits, which is not negative CPU use
+        let ticks = usage.cpu_ticks.saturating_sub(last_ticks);
+        let cpu_percent = if elapsed > 0.0 {
+            ticks as f64 / ticks_per_second as f64 / elapsed * 100.0
+        } else {
+            0.0
+        };
+        self.last_cpu = (now, usage.cpu_ticks);
+
+        if self.samples.len() == METRICS_HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ProcessSample {
+            timestamp: Utc::now().to_rfc3339(),
+            cpu_percent,
+            rss_bytes: usage.rss_bytes,
+            threads: usage.threads,
+            open_fds: usage.open_fds,
+            processes: usage.processes,
+        });
+    }
+}
+
+/// What happened to a server's earlier processes; kept while it is stopped
+#[derive(Default)]
+struct ServerHistory {
+    spawns: u32,
+    last_exit_code: Option<i32>,
+    last_error: Option<String>,
+    /// Times of automatic restarts within `RESTART_WINDOW`
+    restarts: VecDeque<Instant>,
+    /// Environment to restart with after an unexpected exit, until the
+    /// health check loop schedules the restart
+    exit_restart: Option<HashMap<String, String>>,
+}
+
+/// Keeps the last lines a server wrote to stderr
+struct StderrTail {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl StderrTail {
+    /// Drain `stderr` in the background, keeping the most recent lines
+    fn capture(stderr: Option<ChildStderr>) -> Self {
+        let lines = Arc::new(Mutex::new(VecDeque::new()));
+        let reader = stderr.map(|stderr| {
+            let lines = lines.clone();
+            tokio::spawn(async move {
+                let mut reader = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = reader.next_line().await {
+                    tracing::debug!(target: "mcp_server_stderr", "{}", line);
+                    let mut lines = lines.lock().unwrap_or_else(PoisonError::into_inner);
+                    if lines.len() == STDERR_TAIL_LINES {
+                        lines.pop_front();
+                    }
+                    lines.push_back(line);
+                }
+            })
+        });
+        Self { lines, reader }
+    }
+
+    /// Captured lines once the process has exited and its output is drained
+    async fn drained(&mut self) -> String {
+        if let Some(reader) = self.reader.take() {
+            let _ = tokio::time::timeout(STDERR_DRAIN_TIME, reader).await;
+        }
+        self.text()
+    }
+
+    fn text(&self) -> String {
+        let lines = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        lines
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// How a start attempt ended, as seen by callers that joined it
+#[derive(Debug, Clone)]
+enum StartOutcome {
+    Running,
+    Failed(String),
+    Cancelled,
+}
+
+/// A start in progress; later callers wait on `outcome` instead of spawning
+struct PendingStart {
+    /// Distinguishes this start from a later one for the same server
+    id: u64,
+    /// Set once the start finishes
+    outcome: watch::Receiver<Option<StartOutcome>>,
+    /// Set by `stop_server` to abandon the start
+    cancel: watch::Sender<bool>,
+}
+
+/// Lifecycle of one server
+///
+/// Every transition happens under the `servers` lock, so a server is never
+/// spawned twice and a stop always sees the start it has to cancel.
+enum ServerState {
+    Starting(PendingStart),
+    Running(Box<ManagedProcess>),
+    Failed(String),
+}
+
+/// What a caller of `start_server` has to do
+enum StartClaim {
+    Running,
+    /// Another caller is starting the server
+    Join(watch::Receiver<Option<StartOutcome>>),
+    /// This caller starts the server and reports the outcome
+    Lead {
+        id: u64,
+        outcome: watch::Sender<Option<StartOutcome>>,
+        cancel: watch::Receiver<bool>,
+    },
 }
 
 /// Manager for MCP server processes
 pub struct McpManager {
     config: McpConfig,
-    processes: RwLock<HashMap<String, ManagedProcess>>,
-    statuses: RwLock<HashMap<String, McpServerStatus>>,
+    /// Servers that are not stopped
+    servers: Mutex<HashMap<String, ServerState>>,
+    /// Locked after `servers` when both are needed
+    history: Mutex<HashMap<String, ServerHistory>>,
+    next_start_id: AtomicU64,
+    /// Bumped whenever a server status changes
+    status_changes: watch::Sender<u64>,
+}
+
+/// Resets a start that was abandoned because its caller was dropped
+struct StartGuard<'a> {
+    manager: &'a McpManager,
+    name: &'a str,
+    id: u64,
+    finished: bool,
+}
+
+impl Drop for StartGuard<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let mut servers = self.manager.servers();
+        if matches!(servers.get(self.name), Some(ServerState::Starting(pending)) if pending.id == self.id)
+        {
+            servers.remove(self.name);
+            drop(servers);
+            self.manager.notify();
+        }
+    }
 }
 
 impl McpManager {
@@ -26,25 +322,88 @@ impl McpManager {
     pub fn new(config: McpConfig) -> Self {
         Self {
             config,
-            processes: RwLock::new(HashMap::new()),
-            statuses: RwLock::new(HashMap::new()),
+            servers: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
+            next_start_id: AtomicU64::new(0),
+            status_changes: watch::channel(0).0,
+        }
+    }
+
+    /// Subscribe to server status changes
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.status_changes.subscribe()
+    }
+
+    /// Lock the server table; each transition leaves it consistent, so a
+    /// poisoned lock is still safe to use
+    fn servers(&self) -> MutexGuard<'_, HashMap<String, ServerState>> {
+        self.servers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn history(&self) -> MutexGuard<'_, HashMap<String, ServerHistory>> {
+        self.history.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record how a server's process exited
+    fn record_exit(&self, name: &str, status: ExitStatus) {
+        self.history()
+            .entry(name.to_string())
+            .or_default()
+            .last_exit_code = status.code();
+    }
+
+    fn record_error(&self, name: &str, error: String) {
+        self.history()
+            .entry(name.to_string())
+            .or_default()
+            .last_error = Some(error);
+    }
+
+    /// Notify subscribers that a server status changed
+    fn notify(&self) {
+        self.status_changes.send_modify(|revision| *revision += 1);
+    }
+
+    /// Decide whether this caller starts the server or joins a start already
+    /// in progress, registering a new start in the former case
+    fn claim_start(&self, name: &str) -> StartClaim {
+        let mut servers = self.servers();
+        match servers.get(name) {
+            Some(ServerState::Running(_)) => return StartClaim::Running,
+            Some(ServerState::Starting(pending)) => {
+                return StartClaim::Join(pending.outcome.clone())
+            }
+            Some(ServerState::Failed(_)) | None => {}
+        }
+
+        let id = self.next_start_id.fetch_add(1, Ordering::Relaxed);
+        let (outcome_tx, outcome_rx) = watch::channel(None);
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        servers.insert(
+            name.to_string(),
+            ServerState::Starting(PendingStart {
+                id,
+                outcome: outcome_rx,
+                cancel: cancel_tx,
+            }),
+        );
+        StartClaim::Lead {
+            id,
+            outcome: outcome_tx,
+            cancel: cancel_rx,
         }
     }
 
     /// Start an MCP server with the given name and optional environment overrides
+    ///
+    /// Concurrent calls for the same server share one start and all receive
+    /// its outcome; a server that is already running is left alone.
+    #[tracing::instrument(skip(self, env_overrides), err(level = "warn"))]
     pub async fn start_server(
         &self,
         name: &str,
         env_overrides: HashMap<String, String>,
     ) -> Result<(), AppError> {
-        // Check if server is already running
-        {
-            let statuses = self.statuses.read().await;
-            if let Some(McpServerStatus::Running) = statuses.get(name) {
-                return Ok(());
-            }
-        }
-
         // Get server config
         let server_config = self
             .config
@@ -53,118 +412,275 @@ impl McpManager {
             .ok_or_else(|| AppError::NotFound(format!("MCP server '{}' not found", name)))?
             .clone();
 
-        // Update status to Starting
-        {
-            let mut statuses = self.statuses.write().await;
-            statuses.insert(name.to_string(), McpServerStatus::Starting);
-        }
+        let (id, outcome_tx, mut cancel_rx) = match self.claim_start(name) {
+            StartClaim::Running => return Ok(()),
+            StartClaim::Join(outcome) => return join_start(name, outcome).await,
+            StartClaim::Lead {
+                id,
+                outcome,
+                cancel,
+            } => (id, outcome, cancel),
+        };
+        self.notify();
 
-        // Attempt to start with retry logic
-        match self
-            .start_with_retry(name, &server_config, env_overrides)
-            .await
-        {
-            Ok(child) => {
-                let mut processes = self.processes.write().await;
-                processes.insert(
-                    name.to_string(),
-                    ManagedProcess {
-                        child,
-                        retry_count: 0,
-                    },
-                );
+        let mut guard = StartGuard {
+            manager: self,
+            name,
+            id,
+            finished: false,
+        };
 
-                let mut statuses = self.statuses.write().await;
-                statuses.insert(name.to_string(), McpServerStatus::Running);
+        // Dropping the start future on cancellation kills any spawned child
+        let result = tokio::select! {
+            result = self.start_with_retry(name, &server_config, env_overrides) => Some(result),
+            _ = cancel_rx.wait_for(|cancelled| *cancelled) => None,
+        };
 
-                Ok(())
-            }
-            Err(e) => {
-                let mut statuses = self.statuses.write().await;
-                statuses.insert(name.to_string(), McpServerStatus::Failed(e.to_string()));
-                Err(e)
+        let outcome = {
+            let mut servers = self.servers();
+            let current = matches!(servers.get(name), Some(ServerState::Starting(pending)) if pending.id == id);
+            match result {
+                // A stop arrived after the process was spawned but before
+                // the result was recorded; the child is killed on drop
+                _ if !current => StartOutcome::Cancelled,
+                None => StartOutcome::Cancelled,
+                Some(Ok(managed)) => {
+                    servers.insert(name.to_string(), ServerState::Running(Box::new(managed)));
+                    StartOutcome::Running
+                }
+                Some(Err(e)) => {
+                    servers.insert(name.to_string(), ServerState::Failed(e.to_string()));
+                    self.record_error(name, e.to_string());
+                    StartOutcome::Failed(e.to_string())
+                }
             }
-        }
+        };
+        guard.finished = true;
+        self.notify();
+        outcome_tx.send_replace(Some(outcome.clone()));
+
+        outcome_result(name, outcome)
     }
 
-    /// Start a server with exponential backoff retry (max 3 attempts)
+    /// Launch a server, retrying failed attempts with exponential backoff as
+    /// configured for the server
+    #[tracing::instrument(skip(self, config, env_overrides))]
     async fn start_with_retry(
         &self,
         name: &str,
         config: &McpServerConfig,
         env_overrides: HashMap<String, String>,
-    ) -> Result<Child, AppError> {
+    ) -> Result<ManagedProcess, AppError> {
+        let startup = &config.startup;
         let mut backoff = ExponentialBackoff {
-            max_elapsed_time: Some(Duration::from_secs(30)),
-            max_interval: Duration::from_secs(10),
+            initial_interval: Duration::from_millis(startup.initial_backoff_ms),
+            max_interval: Duration::from_millis(startup.max_backoff_ms),
+            // Attempts are capped by count instead
+            max_elapsed_time: None,
             ..Default::default()
         };
+        let max_attempts = startup.max_attempts.max(1);
 
         let mut attempts = 0;
-        const MAX_RETRIES: u32 = 3;
-
         loop {
             attempts += 1;
 
-            match self.spawn_process(config, &env_overrides).await {
-                Ok(child) => {
+            match self.launch(name, config, &env_overrides).await {
+                Ok(mut managed) => {
                     if attempts > 1 {
-                        eprintln!(
+                        tracing::info!(
+                            attempts,
                             "MCP server '{}' started successfully after {} attempts",
-                            name, attempts
+                            name,
+                            attempts
                         );
                     }
-                    return Ok(child);
+                    managed.retry_count = attempts - 1;
+                    return Ok(managed);
                 }
                 Err(e) => {
-                    if attempts >= MAX_RETRIES {
+                    if attempts >= max_attempts {
                         return Err(AppError::Process(format!(
                             "Failed to start MCP server '{}' after {} attempts: {}",
                             name, attempts, e
                         )));
                     }
 
-                    eprintln!(
+                    tracing::warn!(
+                        attempt = attempts,
                         "Failed to start MCP server '{}' (attempt {}/{}): {}",
-                        name, attempts, MAX_RETRIES, e
+                        name,
+                        attempts,
+                        max_attempts,
+                        e
                     );
 
-                    // Calculate backoff delay
                     if let Some(delay) = backoff.next_backoff() {
                         tokio::time::sleep(delay).await;
-                    } else {
-                        return Err(AppError::Process(format!(
-                            "Backoff timeout starting MCP server '{}'",
-                            name
-                        )));
                     }
                 }
             }
         }
     }
 
-    /// Spawn an MCP server process with the given configuration
-    async fn spawn_process(
+    /// Spawn a server and wait until it is ready and has outlived the grace
+    /// period; an exit before then fails the attempt with the server's stderr
+    async fn launch(
         &self,
+        name: &str,
         config: &McpServerConfig,
         env_overrides: &HashMap<String, String>,
-    ) -> Result<Child, AppError> {
-        let mut cmd = Command::new(&config.command);
-        cmd.args(&config.args);
+    ) -> Result<ManagedProcess, AppError> {
+        let startup = &config.startup;
+        let secrets = secret_values(env_overrides);
+        let (mut child, sandbox) = self.spawn_process(config, env_overrides).await?;
+        self.history().entry(name.to_string()).or_default().spawns += 1;
+        let started_at = Utc::now();
+        let metrics = MetricsHistory::new(Instant::now());
+        let mut stderr = StderrTail::capture(child.stderr.take());
 
-        // Set environment variables from config
-        for (key, value) in &config.env {
-            cmd.env(key, value);
-        }
+        let mut connection = match startup.readiness {
+            Readiness::Handshake => match (child.stdin.take(), child.stdout.take()) {
+                (Some(stdin), Some(stdout)) => Some(McpConnection::new(stdin, stdout)),
+                _ => {
+                    return Err(AppError::Process(
+                        "MCP server stdio is not available".to_string(),
+                    ))
+                }
+            },
+            _ => None,
+        };
+
+        let ready_timeout = Duration::from_millis(startup.ready_timeout_ms);
+        let ready = tokio::select! {
+            status = child.wait() => {
+                return Err(self.early_exit(name, status?, "before becoming ready", &mut stderr, &secrets).await);
+            }
+            ready = tokio::time::timeout(
+                ready_timeout,
+                wait_ready(&startup.readiness, connection.as_mut()),
+            ) => ready,
+        };
+        let info = match ready {
+            Ok(Ok(info)) => info,
+            Ok(Err(e)) => {
+                // A handshake usually fails because the server is exiting
+                if let Ok(status) = tokio::time::timeout(EXIT_SETTLE_TIME, child.wait()).await {
+                    return Err(self
+                        .early_exit(
+                            name,
+                            status?,
+                            "before becoming ready",
+                            &mut stderr,
+                            &secrets,
+                        )
+                        .await);
+                }
+                return Err(AppError::Process(with_stderr(
+                    e.to_string(),
+                    &stderr.text(),
+                    &secrets,
+                )));
+            }
+            Err(_) => {
+                return Err(AppError::Timeout(with_stderr(
+                    format!("Not ready after {} ms", startup.ready_timeout_ms),
+                    &stderr.text(),
+                    &secrets,
+                )))
+            }
+        };
 
-        // Apply environment overrides (e.g., from keyring)
-        for (key, value) in env_overrides {
-            cmd.env(key, value);
+        let grace_period = Duration::from_millis(startup.grace_period_ms);
+        if let Ok(status) = tokio::time::timeout(grace_period, child.wait()).await {
+            return Err(self
+                .early_exit(
+                    name,
+                    status?,
+                    "during the grace period",
+                    &mut stderr,
+                    &secrets,
+                )
+                .await);
         }
 
-        // Configure stdio
+        // The tool count is informational, so failing to list tools does not
+        // fail the start
+        let tool_count = match (connection.as_mut(), &info) {
+            (Some(connection), Some(info)) if info.capabilities.get("tools").is_some() => {
+                match tokio::time::timeout(ready_timeout, connection.list_tools()).await {
+                    Ok(Ok(tools)) => Some(tools.len()),
+                    Ok(Err(e)) => {
+                        tracing::warn!("Failed to list tools of MCP server '{}': {}", name, e);
+                        None
+                    }
+                    Err(_) => {
+                        tracing::warn!("Listing tools of MCP server '{}' timed out", name);
+                        None
+                    }
+                }
+            }
+            (Some(_), Some(_)) => Some(0),
+            _ => None,
+        };
+
+        Ok(ManagedProcess {
+            child,
+            retry_count: 0,
+            connection: connection.map(|connection| Arc::new(tokio::sync::Mutex::new(connection))),
+            info,
+            tool_count,
+            stderr,
+            sandbox,
+            started_at,
+            last_used: Instant::now(),
+            metrics,
+            health: HealthState::new(
+                Instant::now() + Duration::from_millis(config.health.interval_ms),
+            ),
+            env_overrides: env_overrides.clone(),
+        })
+    }
+
+    /// Failure for a server that exited during startup
+    async fn early_exit(
+        &self,
+        name: &str,
+        status: ExitStatus,
+        when: &str,
+        stderr: &mut StderrTail,
+        secrets: &[String],
+    ) -> AppError {
+        self.record_exit(name, status);
+        let output = stderr.drained().await;
+        AppError::Process(with_stderr(
+            format!("Server exited with {} {}", status, when),
+            &output,
+            secrets,
+        ))
+    }
+
+    /// Spawn an MCP server process with the given configuration and its
+    /// sandbox, returning the protections that were applied
+    ///
+    /// Only the command is recorded; arguments and environment may hold secrets.
+    #[tracing::instrument(skip_all, fields(command = %config.command), err(level = "debug"))]
+    async fn spawn_process(
+        &self,
+        config: &McpServerConfig,
+        env_overrides: &HashMap<String, String>,
+    ) -> Result<(Child, SandboxReport), AppError> {
+        let (mut cmd, report) = sandbox::command(config, env_overrides)?;
+
+        // Configure stdio; stdout is only read by the MCP connection, which is
+        // kept for handshake readiness, and a pipe nobody reads would block the
+        // server once full
+        let stdout = match config.startup.readiness {
+            Readiness::Handshake => Stdio::piped(),
+            _ => Stdio::null(),
+        };
         cmd.stdin(Stdio::piped())
-            .stdout(Stdio::piped())
+            .stdout(stdout)
             .stderr(Stdio::piped());
 
         // Set kill_on_drop to ensure cleanup
@@ -174,71 +690,1295 @@ impl McpManager {
         let child = cmd
             .spawn()
             .map_err(|e| AppError::Process(format!("Failed to spawn process: {}", e)))?;
+        tracing::debug!(
+            pid = child.id(),
+            limits = ?report.limits,
+            clean_env = report.clean_env,
+            bubblewrap = report.bubblewrap,
+            network_isolated = report.network_isolated,
+            "Spawned MCP server process"
+        );
+        if !report.unavailable.is_empty() {
+            tracing::warn!(
+                "MCP server protections not available on this system: {}",
+                report.unavailable.join(", ")
+            );
+        }
 
-        Ok(child)
+        Ok((child, report))
     }
 
     /// Stop an MCP server
+    ///
+    /// A start in progress is cancelled; this returns once its process, if
+    /// any, has been killed. A failed server is cleared, cancelling any
+    /// automatic restart waiting to start it again.
+    #[tracing::instrument(skip(self), err(level = "warn"))]
     pub async fn stop_server(&self, name: &str) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
+        let state = self.servers().remove(name);
+        if let Some(past) = self.history().get_mut(name) {
+            past.exit_restart = None;
+        }
 
-        if let Some(mut managed) = processes.remove(name) {
-            // Kill the process
-            managed
-                .child
-                .kill()
+        match state {
+            Some(ServerState::Running(managed)) => {
+                self.notify();
+                self.kill(name, managed).await
+            }
+            Some(ServerState::Starting(pending)) => {
+                self.notify();
+                pending.cancel.send_replace(true);
+                // The start may already have finished on its own
+                let _ = join_start(name, pending.outcome).await;
+                Ok(())
+            }
+            Some(ServerState::Failed(_)) => {
+                self.notify();
+                Ok(())
+            }
+            None => Err(AppError::NotFound(format!(
+                "MCP server '{}' is not running",
+                name
+            ))),
+        }
+    }
+
+    /// Kill a server that was removed from the server table
+    async fn kill(&self, name: &str, mut managed: Box<ManagedProcess>) -> Result<(), AppError> {
+        managed
+            .child
+            .kill()
+            .await
+            .map_err(|e| AppError::Process(format!("Failed to kill process: {}", e)))?;
+        if let Ok(Some(status)) = managed.child.try_wait() {
+            self.record_exit(name, status);
+        }
+        Ok(())
+    }
+
+    /// Start a server after the servers it depends on
+    ///
+    /// A dependency that fails to start fails this start too.
+    pub async fn start_with_dependencies(
+        &self,
+        name: &str,
+        env_overrides: HashMap<String, String>,
+    ) -> Result<(), AppError> {
+        let order = mcp_startup::start_order(&self.config.mcp_servers, [name])?;
+        for server in order {
+            let result = self.start_server(&server, env_overrides.clone()).await;
+            match result {
+                Err(e) if server != name => {
+                    return Err(AppError::Process(format!(
+                        "Dependency '{}' of MCP server '{}' failed to start: {}",
+                        server, name, e
+                    )))
+                }
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Start the servers marked `autostart` that are not `on_demand`,
+    /// dependencies first, each after its `start_delay_ms`
+    ///
+    /// Servers whose dependencies fail are skipped; nothing is started when
+    /// the dependencies are misconfigured. `env_overrides` is only called
+    /// when there is a server to start.
+    pub async fn autostart<F, Fut>(&self, env_overrides: F)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<HashMap<String, String>, AppError>>,
+    {
+        let roots = self
+            .config
+            .mcp_servers
+            .iter()
+            .filter(|(_, config)| config.autostart && !config.on_demand)
+            .map(|(name, _)| name.as_str());
+        let order = match mcp_startup::start_order(&self.config.mcp_servers, roots) {
+            Ok(order) => order,
+            Err(e) => {
+                tracing::error!("Not starting MCP servers: {}", e);
+                return;
+            }
+        };
+        if order.is_empty() {
+            return;
+        }
+        let env_overrides = env_overrides().await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to read secrets for MCP servers: {}", e);
+            HashMap::new()
+        });
+
+        let mut failed: HashSet<String> = HashSet::new();
+        for name in order {
+            let config = &self.config.mcp_servers[&name];
+            if let Some(dependency) = config.depends_on.iter().find(|d| failed.contains(*d)) {
+                tracing::warn!(
+                    "Not starting MCP server '{}' because its dependency '{}' failed",
+                    name,
+                    dependency
+                );
+                failed.insert(name);
+                continue;
+            }
+
+            if config.start_delay_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(config.start_delay_ms)).await;
+            }
+            // Failures are logged by start_server
+            if self
+                .start_server(&name, env_overrides.clone())
+                .await
+                .is_err()
+            {
+                failed.insert(name);
+            }
+        }
+    }
+
+    /// Call a tool on a server, starting an `on_demand` server first
+    ///
+    /// `env_overrides` is only called when the server has to be started. A
+    /// call unanswered after the server's `tool_timeout_ms` fails; its late
+    /// response is skipped by the next request.
+    #[tracing::instrument(skip(self, arguments, env_overrides), err(level = "warn"))]
+    pub async fn call_tool<F, Fut>(
+        &self,
+        name: &str,
+        tool: &str,
+        arguments: Value,
+        env_overrides: F,
+    ) -> Result<Value, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<HashMap<String, String>, AppError>>,
+    {
+        let config = self
+            .config
+            .mcp_servers
+            .get(name)
+            .ok_or_else(|| AppError::NotFound(format!("MCP server '{}' not found", name)))?;
+        if config.on_demand && !matches!(self.servers().get(name), Some(ServerState::Running(_))) {
+            self.start_with_dependencies(name, env_overrides().await?)
+                .await?;
+        }
+
+        let connection = {
+            let mut servers = self.servers();
+            let Some(ServerState::Running(managed)) = servers.get_mut(name) else {
+                return Err(AppError::NotFound(format!(
+                    "MCP server '{}' is not running",
+                    name
+                )));
+            };
+            // Marked used under the lock, so an idle check cannot stop the
+            // server before the call has the connection
+            managed.last_used = Instant::now();
+            managed.connection.clone().ok_or_else(|| {
+                AppError::InvalidState(format!(
+                    "MCP server '{}' has no stdio connection to call tools over",
+                    name
+                ))
+            })?
+        };
+
+        let timeout = Duration::from_millis(config.tool_timeout_ms);
+        let call = async {
+            let mut connection = connection.lock().await;
+            connection
+                .request(
+                    "tools/call",
+                    json!({ "name": tool, "arguments": arguments }),
+                )
                 .await
-                .map_err(|e| AppError::Process(format!("Failed to kill process: {}", e)))?;
+        };
+        tokio::time::timeout(timeout, call).await.map_err(|_| {
+            AppError::Timeout(format!(
+                "Tool '{}' of MCP server '{}' did not answer within {} ms",
+                tool, name, config.tool_timeout_ms
+            ))
+        })?
+    }
 
-            // Update status
-            let mut statuses = self.statuses.write().await;
-            statuses.insert(name.to_string(), McpServerStatus::Stopped);
+    /// Stop `on_demand` servers that have been idle for their
+    /// `idle_timeout_ms`, until the app exits
+    pub async fn run_idle_shutdown(&self) {
+        let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            for (name, managed) in self.take_idle() {
+                tracing::info!("Stopping idle MCP server '{}'", name);
+                if let Err(e) = self.kill(&name, managed).await {
+                    tracing::warn!("Failed to stop idle MCP server '{}': {}", name, e);
+                }
+            }
+        }
+    }
 
-            Ok(())
-        } else {
-            Err(AppError::NotFound(format!(
-                "MCP server '{}' is not running",
-                name
-            )))
+    /// Remove idle `on_demand` servers from the server table
+    ///
+    /// Servers with a call in progress, or that a running server depends on,
+    /// are kept.
+    fn take_idle(&self) -> Vec<(String, Box<ManagedProcess>)> {
+        let mut servers = self.servers();
+        let needed: HashSet<&String> = servers
+            .iter()
+            .filter(|(_, state)| matches!(state, ServerState::Running(_)))
+            .filter_map(|(name, _)| self.config.mcp_servers.get(name))
+            .flat_map(|config| &config.depends_on)
+            .collect();
+
+        let idle: Vec<String> = servers
+            .iter()
+            .filter(|(name, state)| {
+                let Some(config) = self.config.mcp_servers.get(*name) else {
+                    return false;
+                };
+                let ServerState::Running(managed) = state else {
+                    return false;
+                };
+                let busy = managed
+                    .connection
+                    .as_ref()
+                    .is_some_and(|connection| connection.try_lock().is_err());
+                config.on_demand
+                    && !needed.contains(name)
+                    && !busy
+                    && managed.last_used.elapsed() >= Duration::from_millis(config.idle_timeout_ms)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        let taken: Vec<(String, Box<ManagedProcess>)> = idle
+            .into_iter()
+            .filter_map(|name| match servers.remove(&name) {
+                Some(ServerState::Running(managed)) => Some((name, managed)),
+                _ => None,
+            })
+            .collect();
+        drop(servers);
+        if !taken.is_empty() {
+            self.notify();
+        }
+        taken
+    }
+
+    /// Ping running servers on their health check intervals until the app
+    /// exits, restarting unhealthy and exited ones as their restart policy
+    /// says
+    ///
+    /// Restarts run in the background, so a slow start does not hold up the
+    /// checks of other servers.
+    pub async fn run_health_checks(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(HEALTH_CHECK_TICK);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.reap_exited();
+            self.restart_exited();
+            self.check_health().await;
+        }
+    }
+
+    /// Ping every server whose check is due and record the results
+    async fn check_health(self: &Arc<Self>) {
+        let now = Instant::now();
+        let due: Vec<_> = self
+            .servers()
+            .iter_mut()
+            .filter_map(|(name, state)| {
+                let ServerState::Running(managed) = state else {
+                    return None;
+                };
+                let health = &self.config.mcp_servers.get(name)?.health;
+                if !health.enabled || now < managed.health.next_ping {
+                    return None;
+                }
+                managed.health.next_ping = now + Duration::from_millis(health.interval_ms);
+                Some((
+                    name.clone(),
+                    managed.child.id()?,
+                    managed.connection.clone()?,
+                    Duration::from_millis(health.timeout_ms),
+                ))
+            })
+            .collect();
+        if due.is_empty() {
+            return;
+        }
+
+        let mut pings = JoinSet::new();
+        for (name, pid, connection, timeout) in due {
+            pings.spawn(async move {
+                let sent = Utc::now();
+                let result = ping(&connection, timeout).await;
+                (name, pid, sent, result)
+            });
+        }
+
+        let mut changed = false;
+        let mut restarts = Vec::new();
+        while let Some(joined) = pings.join_next().await {
+            let Ok((name, pid, sent, Some(result))) = joined else {
+                continue;
+            };
+            let mut servers = self.servers();
+            // Skip servers restarted while the ping was in flight
+            let Some(ServerState::Running(managed)) = servers.get_mut(&name) else {
+                continue;
+            };
+            if managed.child.id() != Some(pid) {
+                continue;
+            }
+            let health = &self.config.mcp_servers[&name].health;
+            if !managed
+                .health
+                .record(sent, result, health.failure_threshold)
+            {
+                continue;
+            }
+
+            changed = true;
+            match &managed.health.unhealthy {
+                Some(reason) => {
+                    tracing::warn!("MCP server '{}' is unhealthy: {}", name, reason);
+                    let reason = reason.clone();
+                    drop(servers);
+                    self.record_error(&name, reason);
+                    if matches!(health.restart, RestartPolicy::OnFailure) {
+                        restarts.push(name);
+                    }
+                }
+                None => tracing::info!("MCP server '{}' is healthy again", name),
+            }
+        }
+        if changed {
+            self.notify();
+        }
+
+        for name in restarts {
+            self.restart_unhealthy(&name);
+        }
+    }
+
+    /// Mark an unhealthy server as failed and restart it in the background
+    ///
+    /// A server restarted too often recently is left running, unhealthy.
+    fn restart_unhealthy(self: &Arc<Self>, name: &str) {
+        let mut servers = self.servers();
+        let reason = match servers.get(name) {
+            Some(ServerState::Running(managed)) => match &managed.health.unhealthy {
+                Some(reason) => reason.clone(),
+                None => return,
+            },
+            // Stopped or restarted in the meantime
+            _ => return,
+        };
+        let Some(delay) = self.restart_delay(name) else {
+            return;
+        };
+        let Some(ServerState::Running(managed)) =
+            servers.insert(name.to_string(), ServerState::Failed(reason))
+        else {
+            return;
+        };
+        drop(servers);
+        self.notify();
+
+        let env_overrides = managed.env_overrides.clone();
+        self.schedule_restart(name.to_string(), env_overrides, Some(managed), delay);
+    }
+
+    /// Restart servers whose process exited unexpectedly, as found by
+    /// `reap_exited`
+    fn restart_exited(self: &Arc<Self>) {
+        let exited: Vec<(String, HashMap<String, String>)> = self
+            .history()
+            .iter_mut()
+            .filter_map(|(name, past)| Some((name.clone(), past.exit_restart.take()?)))
+            .collect();
+        for (name, env_overrides) in exited {
+            if let Some(delay) = self.restart_delay(&name) {
+                self.schedule_restart(name, env_overrides, None, delay);
+            }
+        }
+    }
+
+    /// Count an automatic restart, returning how long to wait before it, or
+    /// `None` when the server has been restarted too often recently
+    fn restart_delay(&self, name: &str) -> Option<Duration> {
+        let now = Instant::now();
+        let mut history = self.history();
+        let restarts = &mut history.entry(name.to_string()).or_default().restarts;
+        while restarts
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= RESTART_WINDOW)
+        {
+            restarts.pop_front();
+        }
+        if restarts.len() >= MAX_RESTARTS {
+            tracing::error!(
+                "Not restarting MCP server '{}' again after {} restarts within {} minutes",
+                name,
+                restarts.len(),
+                RESTART_WINDOW.as_secs() / 60
+            );
+            return None;
+        }
+
+        let delay = RESTART_BACKOFF * 2u32.pow(restarts.len() as u32);
+        restarts.push_back(now);
+        Some(delay)
+    }
+
+    /// Kill `process`, if any, then start the server again after `delay`
+    /// unless it was stopped or started in the meantime
+    ///
+    /// Stopping a failed server removes it, so the restart finds it gone.
+    fn schedule_restart(
+        self: &Arc<Self>,
+        name: String,
+        env_overrides: HashMap<String, String>,
+        process: Option<Box<ManagedProcess>>,
+        delay: Duration,
+    ) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            if let Some(process) = process {
+                if let Err(e) = manager.kill(&name, process).await {
+                    tracing::warn!("Failed to kill unhealthy MCP server '{}': {}", name, e);
+                }
+            }
+            tokio::time::sleep(delay).await;
+            if !matches!(manager.servers().get(&name), Some(ServerState::Failed(_))) {
+                return;
+            }
+
+            tracing::warn!(
+                "Restarting MCP server '{}' after {} s",
+                name,
+                delay.as_secs()
+            );
+            // Failures are logged and recorded by start_server
+            let _ = manager.start_server(&name, env_overrides).await;
+        });
+    }
+
+    /// Health check results of a running server
+    pub fn health(&self, name: &str) -> Option<ServerHealth> {
+        match self.servers().get(name) {
+            Some(ServerState::Running(managed)) => Some(server_health(
+                managed,
+                &self.config.mcp_servers.get(name)?.health,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Mark running servers whose process has exited as failed, leaving
+    /// those whose restart policy says so for `restart_exited`
+    fn reap_exited(&self) {
+        let mut exited = false;
+        let mut servers = self.servers();
+        for (name, state) in servers.iter_mut() {
+            let ServerState::Running(managed) = state else {
+                continue;
+            };
+            let Ok(Some(status)) = managed.child.try_wait() else {
+                continue;
+            };
+            let reason = with_stderr(
+                format!("Server exited with {}", status),
+                &managed.stderr.text(),
+                &secret_values(&managed.env_overrides),
+            );
+            tracing::warn!("MCP server '{}' exited unexpectedly with {}", name, status);
+            self.record_exit(name, status);
+            self.record_error(name, reason.clone());
+            if self
+                .config
+                .mcp_servers
+                .get(name)
+                .is_some_and(|config| matches!(config.health.restart, RestartPolicy::OnFailure))
+            {
+                self.history().entry(name.clone()).or_default().exit_restart =
+                    Some(managed.env_overrides.clone());
+            }
+            *state = ServerState::Failed(reason);
+            exited = true;
+        }
+        drop(servers);
+        if exited {
+            self.notify();
         }
     }
 
     /// List all MCP servers with their current status
     pub async fn list_servers(&self) -> HashMap<String, McpServerStatus> {
-        let statuses = self.statuses.read().await;
-        let mut result = HashMap::new();
+        self.reap_exited();
+        let servers = self.servers();
 
         // Include all configured servers
-        for name in self.config.mcp_servers.keys() {
-            let status = statuses
-                .get(name)
-                .cloned()
-                .unwrap_or(McpServerStatus::Stopped);
-            result.insert(name.clone(), status);
+        self.config
+            .mcp_servers
+            .keys()
+            .map(|name| (name.clone(), status_of(servers.get(name))))
+            .collect()
+    }
+
+    /// Describe every configured server, sorted by name
+    ///
+    /// `secrets` are redacted from the listed command lines.
+    pub async fn describe_servers(&self, secrets: &[String]) -> Vec<McpServerEntry> {
+        self.reap_exited();
+        let servers = self.servers();
+        let history = self.history();
+
+        let mut entries: Vec<McpServerEntry> = self
+            .config
+            .mcp_servers
+            .iter()
+            .map(|(name, config)| {
+                let state = servers.get(name);
+                let managed = match state {
+                    Some(ServerState::Running(managed)) => Some(managed),
+                    _ => None,
+                };
+                let info = managed.and_then(|managed| managed.info.as_ref());
+                let past = history.get(name);
+
+                McpServerEntry {
+                    name: name.clone(),
+                    status: status_of(state),
+                    transport: config.transport(),
+                    command_line: logging::redact_command_line(
+                        &config.command,
+                        &config.args,
+                        secrets,
+                    ),
+                    pid: managed.and_then(|managed| managed.child.id()),
+                    started_at: managed.map(|managed| managed.started_at.to_rfc3339()),
+                    restart_count: past.map_or(0, |past| past.spawns.saturating_sub(1)),
+                    last_exit_code: past.and_then(|past| past.last_exit_code),
+                    last_error: past.and_then(|past| past.last_error.clone()),
+                    server_info: info.map(|info| info.server_info.clone()),
+                    protocol_version: info.map(|info| info.protocol_version.clone()),
+                    capabilities: info.map(|info| info.capabilities.clone()),
+                    tool_count: managed.and_then(|managed| managed.tool_count),
+                    metrics: managed.map(|managed| server_metrics(managed)),
+                    health: managed.map(|managed| server_health(managed, &config.health)),
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+    }
+
+    /// Protections in effect for a running server
+    pub fn sandbox_report(&self, name: &str) -> Option<SandboxReport> {
+        match self.servers().get(name) {
+            Some(ServerState::Running(managed)) => Some(managed.sandbox.clone()),
+            _ => None,
+        }
+    }
+
+    /// Resource usage of a running server's process tree
+    pub fn metrics(&self, name: &str) -> Option<ServerMetrics> {
+        match self.servers().get(name) {
+            Some(ServerState::Running(managed)) => Some(server_metrics(managed)),
+            _ => None,
         }
+    }
 
-        result
+    /// Sample the resource usage of running servers until the app exits
+    pub async fn run_metrics(&self) {
+        let ticks_per_second = process_metrics::clock_ticks_per_second();
+        let mut interval = tokio::time::interval(METRICS_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.sample_metrics(ticks_per_second).await;
+        }
+    }
+
+    /// Read `/proc` for every running server and record a sample each
+    async fn sample_metrics(&self, ticks_per_second: u64) {
+        let pids: Vec<(String, u32)> = self
+            .servers()
+            .iter()
+            .filter_map(|(name, state)| match state {
+                ServerState::Running(managed) => managed.child.id().map(|pid| (name.clone(), pid)),
+                _ => None,
+            })
+            .collect();
+        if pids.is_empty() {
+            return;
+        }
+
+        let usage = tokio::task::spawn_blocking(move || {
+            let table = ProcessTable::read()?;
+            Some(
+                pids.into_iter()
+                    .filter_map(|(name, pid)| table.tree_usage(pid).map(|usage| (name, pid, usage)))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .await;
+        let usage = match usage {
+            Ok(Some(usage)) => usage,
+            // No /proc on this platform
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Process metrics reader failed: {}", e);
+                return;
+            }
+        };
+
+        let mut servers = self.servers();
+        for (name, pid, usage) in usage {
+            // Skip servers restarted while /proc was being read
+            if let Some(ServerState::Running(managed)) = servers.get_mut(&name) {
+                if managed.child.id() == Some(pid) {
+                    managed.metrics.record(usage, ticks_per_second);
+                }
+            }
+        }
     }
 
-    /// Stop all running MCP servers
+    /// Stop all running MCP servers, cancelling any starts in progress
     pub async fn stop_all(&self) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
-        let mut statuses = self.statuses.write().await;
+        let states: Vec<(String, ServerState)> = self.servers().drain().collect();
+        self.notify();
 
-        for (name, mut managed) in processes.drain() {
-            if let Err(e) = managed.child.kill().await {
-                eprintln!("Failed to kill MCP server '{}': {}", name, e);
+        for (name, state) in states {
+            match state {
+                ServerState::Running(mut managed) => {
+                    if let Err(e) = managed.child.kill().await {
+                        tracing::warn!("Failed to kill MCP server '{}': {}", name, e);
+                    }
+                    if let Ok(Some(status)) = managed.child.try_wait() {
+                        self.record_exit(&name, status);
+                    }
+                }
+                ServerState::Starting(pending) => {
+                    pending.cancel.send_replace(true);
+                    let _ = join_start(&name, pending.outcome).await;
+                }
+                ServerState::Failed(_) => {}
             }
-            statuses.insert(name, McpServerStatus::Stopped);
         }
 
         Ok(())
     }
 }
 
+fn status_of(state: Option<&ServerState>) -> McpServerStatus {
+    match state {
+        None => McpServerStatus::Stopped,
+        Some(ServerState::Starting(_)) => McpServerStatus::Starting,
+        Some(ServerState::Running(managed)) => match &managed.health.unhealthy {
+            Some(reason) => McpServerStatus::Unhealthy(reason.clone()),
+            None => McpServerStatus::Running,
+        },
+        Some(ServerState::Failed(reason)) => McpServerStatus::Failed(reason.clone()),
+    }
+}
+
+fn server_health(managed: &ManagedProcess, config: &HealthCheckConfig) -> ServerHealth {
+    let inactive_reason = if !config.enabled {
+        Some("Health checks are disabled".to_string())
+    } else if managed.connection.is_none() {
+        Some("Only servers ready by handshake have a connection to ping".to_string())
+    } else {
+        None
+    };
+    ServerHealth {
+        active: inactive_reason.is_none(),
+        inactive_reason,
+        consecutive_failures: managed.health.consecutive_failures,
+        last_ping: managed.health.samples.back().cloned(),
+        history: managed.health.samples.iter().cloned().collect(),
+    }
+}
+
+/// Ping a server, or `None` when a request such as a tool call is already
+/// using its connection, which is no sign of ill health
+async fn ping(
+    connection: &tokio::sync::Mutex<McpConnection>,
+    timeout: Duration,
+) -> Option<Result<Duration, String>> {
+    let mut connection = connection.try_lock().ok()?;
+    let started = Instant::now();
+    Some(
+        match tokio::time::timeout(timeout, connection.ping()).await {
+            Ok(Ok(())) => Ok(started.elapsed()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!("No response within {} ms", timeout.as_millis())),
+        },
+    )
+}
+
+fn server_metrics(managed: &ManagedProcess) -> ServerMetrics {
+    ServerMetrics {
+        pid: managed.child.id(),
+        uptime_secs: (Utc::now() - managed.started_at).num_seconds().max(0) as u64,
+        current: managed.metrics.samples.back().cloned(),
+        history: managed.metrics.samples.iter().cloned().collect(),
+    }
+}
+
+/// Wait until the server passes its readiness check
+async fn wait_ready(
+    readiness: &Readiness,
+    connection: Option<&mut McpConnection>,
+) -> Result<Option<InitializeResult>, AppError> {
+    match readiness {
+        Readiness::Handshake => {
+            let connection = connection.ok_or_else(|| {
+                AppError::InvalidState("Handshake readiness needs a stdio connection".to_string())
+            })?;
+            connection.initialize().await.map(Some)
+        }
+        Readiness::Tcp { address } => loop {
+            if TcpStream::connect(address.as_str()).await.is_ok() {
+                return Ok(None);
+            }
+            tokio::time::sleep(PROBE_INTERVAL).await;
+        },
+        Readiness::Command { command, args } => loop {
+            let status = Command::new(command)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .status()
+                .await
+                .map_err(|e| {
+                    AppError::Process(format!("Failed to run startup probe '{}': {}", command, e))
+                })?;
+            if status.success() {
+                return Ok(None);
+            }
+            tokio::time::sleep(PROBE_INTERVAL).await;
+        },
+        Readiness::None => Ok(None),
+    }
+}
+
+/// Append a server's recent stderr to a failure reason, redacting the
+/// secrets the server was given, which it may have echoed
+fn with_stderr(reason: String, output: &str, secrets: &[String]) -> String {
+    let reason = if output.is_empty() {
+        reason
+    } else {
+        format!("{}; stderr:\n{}", reason, output)
+    };
+    logging::redact_text(&reason, secrets)
+}
+
+/// Secret values passed to a server through its environment overrides
+fn secret_values(env_overrides: &HashMap<String, String>) -> Vec<String> {
+    env_overrides.values().cloned().collect()
+}
+
+/// Wait for a start begun by another caller and return its outcome
+async fn join_start(
+    name: &str,
+    mut outcome: watch::Receiver<Option<StartOutcome>>,
+) -> Result<(), AppError> {
+    let outcome = match outcome.wait_for(Option::is_some).await {
+        Ok(outcome) => outcome.clone().unwrap_or(StartOutcome::Cancelled),
+        // The starting caller went away without finishing
+        Err(_) => StartOutcome::Cancelled,
+    };
+    outcome_result(name, outcome)
+}
+
+fn outcome_result(name: &str, outcome: StartOutcome) -> Result<(), AppError> {
+    match outcome {
+        StartOutcome::Running => Ok(()),
+        StartOutcome::Failed(reason) => Err(AppError::Process(reason)),
+        StartOutcome::Cancelled => Err(AppError::InvalidState(format!(
+            "Start of MCP server '{}' was cancelled",
+            name
+        ))),
+    }
+}
+
 impl Drop for McpManager {
     fn drop(&mut self) {
         // Processes will be killed automatically due to kill_on_drop(true)
         // This ensures no zombie processes on app exit
     }
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    /// Starts a `stub` server running `script`, which gets the path of a file
+    /// to record its pid in as `PIDFILE`
+    fn stub_manager(dir: &Path, script: &str) -> Arc<McpManager> {
+        stub_manager_with(
+            dir,
+            script,
+            json!({
+                "startup": {
+                    "readiness": { "type": "none" },
+                    "gracePeriodMs": 1000,
+                    "maxAttempts": 1,
+                },
+            }),
+        )
+    }
+
+    /// Like `stub_manager`, with `settings` added to the server configuration
+    fn stub_manager_with(dir: &Path, script: &str, settings: Value) -> Arc<McpManager> {
+        let mut server = json!({
+            "command": "sh",
+            "args": ["-c", script],
+            "env": { "PIDFILE": pid_file(dir) },
+        });
+        for (key, value) in settings.as_object().unwrap() {
+            server[key] = value.clone();
+        }
+        let config: McpConfig =
+            serde_json::from_value(json!({ "mcpServers": { "stub": server } })).unwrap();
+        Arc::new(McpManager::new(config))
+    }
+
+    const LONG_RUNNING: &str = "echo $$ >> \"$PIDFILE\"; exec sleep 30";
+
+    /// Completes the handshake, then never answers another request
+    const HANDSHAKE_ONLY: &str = r#"echo $$ >> "$PIDFILE"
+read -r request
+echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2025-06-18","capabilities":{},"serverInfo":{"name":"stub"}}}'
+exec sleep 30"#;
+
+    fn pid_file(dir: &Path) -> PathBuf {
+        dir.join("pids")
+    }
+
+    fn spawned_pids(dir: &Path) -> Vec<u32> {
+        std::fs::read_to_string(pid_file(dir))
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect()
+    }
+
+    /// Wait until the stub has been spawned and return its pid
+    async fn wait_for_spawn(dir: &Path) -> u32 {
+        for _ in 0..500 {
+            if let Some(pid) = spawned_pids(dir).first() {
+                return *pid;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("stub server was never spawned");
+    }
+
+    /// Whether a process exists and has not exited; a killed child may
+    /// linger briefly as a zombie until it is reaped
+    fn is_alive(pid: u32) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            .and_then(|stat| {
+                let state = stat
+                    .get(stat.rfind(')')? + 1..)?
+                    .trim_start()
+                    .chars()
+                    .next()?;
+                Some(state != 'Z' && state != 'X')
+            })
+            .unwrap_or(false)
+    }
+
+    /// Wait briefly for a killed process to go away; SIGKILL is sent
+    /// synchronously but delivered asynchronously
+    async fn exits_soon(pid: u32) -> bool {
+        for _ in 0..100 {
+            if !is_alive(pid) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    fn is_cancelled(result: &Result<(), AppError>) -> bool {
+        matches!(result, Err(AppError::InvalidState(reason)) if reason.contains("cancelled"))
+    }
+
+    #[tokio::test]
+    async fn concurrent_starts_spawn_one_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager(dir.path(), LONG_RUNNING);
+
+        let (first, second) = tokio::join!(
+            manager.start_server("stub", HashMap::new()),
+            manager.start_server("stub", HashMap::new()),
+        );
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+        assert_eq!(spawned_pids(dir.path()).len(), 1);
+        assert_eq!(manager.history()["stub"].spawns, 1);
+        assert!(matches!(
+            manager.list_servers().await["stub"],
+            McpServerStatus::Running
+        ));
+
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn servers_without_a_connection_can_write_to_stdout() {
+        let dir = tempfile::tempdir().unwrap();
+        // More output than a pipe holds, which would block if nothing read it
+        let manager = stub_manager(
+            dir.path(),
+            "head -c 1000000 /dev/zero; echo $$ >> \"$PIDFILE\"; exec sleep 30",
+        );
+
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+        let pid = wait_for_spawn(dir.path()).await;
+        assert!(is_alive(pid));
+
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn concurrent_starts_share_a_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager(dir.path(), "echo $$ >> \"$PIDFILE\"; exit 3");
+
+        let (first, second) = tokio::join!(
+            manager.start_server("stub", HashMap::new()),
+            manager.start_server("stub", HashMap::new()),
+        );
+        let (Err(AppError::Process(first)), Err(AppError::Process(second))) = (first, second)
+        else {
+            panic!("both starts should fail");
+        };
+        assert_eq!(first, second);
+        assert_eq!(spawned_pids(dir.path()).len(), 1);
+        assert!(matches!(
+            manager.list_servers().await["stub"],
+            McpServerStatus::Failed(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn failure_reasons_redact_secrets_from_stderr() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager(
+            dir.path(),
+            "echo \"auth failed for $API_TOKEN\" >&2; exit 1",
+        );
+        let secret = "sk-live-0123456789";
+
+        let error = manager
+            .start_server(
+                "stub",
+                HashMap::from([("API_TOKEN".to_string(), secret.to_string())]),
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("auth failed for [REDACTED]"), "{}", error);
+        assert!(!error.contains(secret));
+
+        let entry = manager.describe_servers(&[]).await.remove(0);
+        let McpServerStatus::Failed(reason) = entry.status else {
+            panic!("stub should have failed");
+        };
+        assert!(!reason.contains(secret));
+        assert!(!entry.last_error.unwrap().contains(secret));
+    }
+
+    #[tokio::test]
+    async fn tool_calls_time_out_and_read_secrets_only_to_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager_with(
+            dir.path(),
+            HANDSHAKE_ONLY,
+            json!({
+                "startup": { "gracePeriodMs": 100, "maxAttempts": 1 },
+                "onDemand": true,
+                "toolTimeoutMs": 200,
+            }),
+        );
+        let env_reads = AtomicU64::new(0);
+        let read_env = || async {
+            env_reads.fetch_add(1, Ordering::Relaxed);
+            Ok(HashMap::new())
+        };
+
+        let result = manager
+            .call_tool("stub", "search", json!({}), read_env)
+            .await;
+        assert!(matches!(result, Err(AppError::Timeout(_))));
+        assert_eq!(env_reads.load(Ordering::Relaxed), 1);
+
+        // Already running, so the environment is not needed
+        let result = manager
+            .call_tool("stub", "search", json!({}), || async {
+                Err(AppError::Keyring("Keyring is locked".to_string()))
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Timeout(_))));
+        assert_eq!(spawned_pids(dir.path()).len(), 1);
+
+        manager.stop_all().await.unwrap();
+    }
+
+    /// Wait until the stub has been spawned `count` times
+    async fn wait_for_spawns(dir: &Path, count: usize, within: Duration) -> bool {
+        let deadline = Instant::now() + within;
+        while Instant::now() < deadline {
+            if spawned_pids(dir).len() >= count {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        false
+    }
+
+    const EXITS_SOON: &str = "echo $$ >> \"$PIDFILE\"; sleep 0.3; exit 1";
+
+    #[test]
+    fn automatic_restarts_back_off_then_stop() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager(dir.path(), LONG_RUNNING);
+
+        let delays: Vec<u64> = std::iter::from_fn(|| manager.restart_delay("stub"))
+            .map(|delay| delay.as_secs())
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16]);
+        assert!(manager.restart_delay("stub").is_none());
+    }
+
+    #[tokio::test]
+    async fn exited_servers_are_restarted() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager_with(
+            dir.path(),
+            EXITS_SOON,
+            json!({ "startup": { "readiness": { "type": "none" }, "gracePeriodMs": 100 } }),
+        );
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+        let checks = tokio::spawn(manager.clone().run_health_checks());
+
+        assert!(wait_for_spawns(dir.path(), 2, Duration::from_secs(6)).await);
+        checks.abort();
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn stopping_a_failed_server_cancels_its_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager_with(
+            dir.path(),
+            EXITS_SOON,
+            json!({ "startup": { "readiness": { "type": "none" }, "gracePeriodMs": 100 } }),
+        );
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+        let checks = tokio::spawn(manager.clone().run_health_checks());
+
+        // Exited, with its restart scheduled and waiting out the delay
+        let scheduled = || {
+            manager
+                .history()
+                .get("stub")
+                .is_some_and(|past| past.restarts.len() == 1)
+        };
+        for _ in 0..100 {
+            if scheduled() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(scheduled());
+        manager.stop_server("stub").await.unwrap();
+        assert!(matches!(
+            manager.list_servers().await["stub"],
+            McpServerStatus::Stopped
+        ));
+
+        assert!(!wait_for_spawns(dir.path(), 2, Duration::from_secs(3)).await);
+        checks.abort();
+    }
+
+    #[tokio::test]
+    async fn exited_servers_stay_failed_without_a_restart_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager_with(
+            dir.path(),
+            EXITS_SOON,
+            json!({
+                "startup": { "readiness": { "type": "none" }, "gracePeriodMs": 100 },
+                "health": { "restart": "never" },
+            }),
+        );
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+        let checks = tokio::spawn(manager.clone().run_health_checks());
+
+        assert!(!wait_for_spawns(dir.path(), 2, Duration::from_secs(3)).await);
+        assert!(matches!(
+            manager.list_servers().await["stub"],
+            McpServerStatus::Failed(_)
+        ));
+        checks.abort();
+    }
+
+    #[tokio::test]
+    async fn unhealthy_servers_are_killed_and_restarted() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager_with(
+            dir.path(),
+            HANDSHAKE_ONLY,
+            json!({
+                "startup": { "gracePeriodMs": 100 },
+                "health": { "intervalMs": 1000, "timeoutMs": 100, "failureThreshold": 1 },
+            }),
+        );
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+        let first = spawned_pids(dir.path())[0];
+        let checks = tokio::spawn(manager.clone().run_health_checks());
+
+        assert!(wait_for_spawns(dir.path(), 2, Duration::from_secs(6)).await);
+        assert!(exits_soon(first).await);
+        checks.abort();
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn health_checks_without_a_connection_are_reported_inactive() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager(dir.path(), LONG_RUNNING);
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+
+        let health = manager.health("stub").unwrap();
+        assert!(!health.active);
+        assert!(health.inactive_reason.unwrap().contains("handshake"));
+        manager.stop_all().await.unwrap();
+
+        let manager = stub_manager_with(
+            dir.path(),
+            HANDSHAKE_ONLY,
+            json!({ "health": { "enabled": false } }),
+        );
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+        let health = manager.health("stub").unwrap();
+        assert!(!health.active);
+        assert_eq!(
+            health.inactive_reason.as_deref(),
+            Some("Health checks are disabled")
+        );
+        manager.stop_all().await.unwrap();
+
+        let manager = stub_manager_with(dir.path(), HANDSHAKE_ONLY, json!({}));
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+        let health = manager.health("stub").unwrap();
+        assert!(health.active);
+        assert_eq!(health.inactive_reason, None);
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn start_while_starting_joins() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager(dir.path(), LONG_RUNNING);
+
+        let leader = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.start_server("stub", HashMap::new()).await }
+        });
+        wait_for_spawn(dir.path()).await;
+        assert!(matches!(
+            manager.list_servers().await["stub"],
+            McpServerStatus::Starting
+        ));
+
+        manager.start_server("stub", HashMap::new()).await.unwrap();
+        assert!(leader.await.unwrap().is_ok());
+        assert_eq!(spawned_pids(dir.path()).len(), 1);
+
+        manager.stop_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn stop_while_starting_kills_the_child_and_cancels_joiners() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager(dir.path(), LONG_RUNNING);
+
+        let leader = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.start_server("stub", HashMap::new()).await }
+        });
+        let pid = wait_for_spawn(dir.path()).await;
+        let joiner = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.start_server("stub", HashMap::new()).await }
+        });
+        // Let the joiner register before the stop
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        manager.stop_server("stub").await.unwrap();
+        assert!(exits_soon(pid).await);
+        assert!(is_cancelled(&leader.await.unwrap()));
+        assert!(is_cancelled(&joiner.await.unwrap()));
+        assert!(matches!(
+            manager.list_servers().await["stub"],
+            McpServerStatus::Stopped
+        ));
+        assert_eq!(spawned_pids(dir.path()).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn dropping_the_leading_start_clears_starting() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = stub_manager(dir.path(), LONG_RUNNING);
+
+        let (pid, joiner) = {
+            let leader = manager.start_server("stub", HashMap::new());
+            tokio::pin!(leader);
+            let pid = tokio::select! {
+                _ = &mut leader => panic!("start should still be in its grace period"),
+                pid = wait_for_spawn(dir.path()) => pid,
+            };
+            let joiner = tokio::spawn({
+                let manager = manager.clone();
+                async move { manager.start_server("stub", HashMap::new()).await }
+            });
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            (pid, joiner)
+            // The leader is dropped here, mid-start
+        };
+        assert!(exits_soon(pid).await);
+
+        assert!(matches!(
+            manager.list_servers().await["stub"],
+            McpServerStatus::Stopped
+        ));
+        assert!(is_cancelled(&joiner.await.unwrap()));
+
+        // Nothing is left behind that would make a new start join a dead one
+        let restarted = tokio::time::timeout(
+            Duration::from_secs(5),
+            manager.start_server("stub", HashMap::new()),
+        )
+        .await
+        .unwrap();
+        assert!(restarted.is_ok());
+        assert_eq!(spawned_pids(dir.path()).len(), 2);
+
+        manager.stop_all().await.unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+use crate::types::config::McpServerConfig;
+use crate::types::errors::AppError;
+use std::collections::{HashMap, HashSet};
+
+/// Order `roots` and everything they depend on so that each server comes
+/// after its dependencies
+///
+/// Roots are visited by name and dependencies in the order they are listed,
+/// so the order is stable. Unknown dependencies and dependency cycles are
+/// configuration errors.
+pub fn start_order<'a>(
+    servers: &HashMap<String, McpServerConfig>,
+    roots: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<String>, AppError> {
+    let mut roots: Vec<&str> = roots.into_iter().collect();
+    roots.sort_unstable();
+
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    let mut path = Vec::new();
+    for root in roots {
+        visit(servers, root, &mut path, &mut done, &mut order)?;
+    }
+    Ok(order)
+}
+
+/// Depth-first visit; `path` holds the chain of dependents being visited, so
+/// meeting a server already on it means a cycle
+fn visit(
+    servers: &HashMap<String, McpServerConfig>,
+    name: &str,
+    path: &mut Vec<String>,
+    done: &mut HashSet<String>,
+    order: &mut Vec<String>,
+) -> Result<(), AppError> {
+    if done.contains(name) {
+        return Ok(());
+    }
+    if let Some(start) = path.iter().position(|visiting| visiting == name) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(name.to_string());
+        return Err(AppError::Config(format!(
+            "MCP server dependency cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+
+    let config = servers.get(name).ok_or_else(|| match path.last() {
+        Some(dependent) => AppError::Config(format!(
+            "MCP server '{}' depends on unknown server '{}'",
+            dependent, name
+        )),
+        None => AppError::NotFound(format!("MCP server '{}' not found", name)),
+    })?;
+
+    path.push(name.to_string());
+    for dependency in &config.depends_on {
+        visit(servers, dependency, path, done, order)?;
+    }
+    path.pop();
+
+    done.insert(name.to_string());
+    order.push(name.to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Servers named by the keys of `deps`, each depending on the listed ones
+    fn servers(deps: &[(&str, &[&str])]) -> HashMap<String, McpServerConfig> {
+        deps.iter()
+            .map(|(name, depends_on)| {
+                let config = json!({ "command": "true", "args": [], "dependsOn": depends_on });
+                (name.to_string(), serde_json::from_value(config).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chains_start_dependencies_first() {
+        let servers = servers(&[("app", &["cache"]), ("cache", &["db"]), ("db", &[])]);
+
+        assert_eq!(
+            start_order(&servers, ["app"]).unwrap(),
+            ["db", "cache", "app"]
+        );
+        // Only what the roots need is started
+        assert_eq!(start_order(&servers, ["cache"]).unwrap(), ["db", "cache"]);
+        assert!(start_order(&servers, []).unwrap().is_empty());
+    }
+
+    #[test]
+    fn shared_dependencies_start_once() {
+        let servers = servers(&[
+            ("app", &["left", "right"]),
+            ("left", &["base"]),
+            ("right", &["base"]),
+            ("base", &[]),
+        ]);
+
+        assert_eq!(
+            start_order(&servers, ["app"]).unwrap(),
+            ["base", "left", "right", "app"]
+        );
+        // Roots are visited by name, whatever order they are given in
+        assert_eq!(
+            start_order(&servers, ["right", "left", "app"]).unwrap(),
+            ["base", "left", "right", "app"]
+        );
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let servers = servers(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"]), ("d", &["d"])]);
+
+        let Err(AppError::Config(message)) = start_order(&servers, ["a"]) else {
+            panic!("cycle was accepted");
+        };
+        assert_eq!(message, "MCP server dependency cycle: a -> b -> c -> a");
+
+        let Err(AppError::Config(message)) = start_order(&servers, ["d"]) else {
+            panic!("self-dependency was accepted");
+        };
+        assert_eq!(message, "MCP server dependency cycle: d -> d");
+    }
+
+    #[test]
+    fn unknown_servers_are_rejected() {
+        let servers = servers(&[("app", &["db"]), ("db", &["missing"])]);
+
+        let Err(AppError::Config(message)) = start_order(&servers, ["app"]) else {
+            panic!("unknown dependency was accepted");
+        };
+        assert_eq!(
+            message,
+            "MCP server 'db' depends on unknown server 'missing'"
+        );
+        assert!(matches!(
+            start_order(&servers, ["nope"]),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,461 @@
+use crate::services::search::to_fts_query;
+use crate::types::errors::AppError;
+use crate::types::memory::{MemoryRecord, MemorySearchHit, MemorySearchQuery, MemoryUpsert};
+use serde_json::{Map, Value};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+use std::collections::HashMap;
+
+const DEFAULT_TOP_K: usize = 10;
+const MAX_TOP_K: usize = 200;
+const DEFAULT_VECTOR_WEIGHT: f32 = 0.7;
+
+/// Text matches considered per requested result when blending with vector scores
+const TEXT_CANDIDATES_PER_RESULT: usize = 4;
+
+const MEMORY_COLUMNS: &str = "m.id, m.namespace, m.key, m.run_id, m.content, m.metadata, \
+                              m.created_at, m.updated_at";
+
+/// Encode an embedding as little-endian f32 bytes for BLOB storage
+pub fn encode_embedding(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Decode an embedding stored by [`encode_embedding`]
+pub fn decode_embedding(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// Cosine similarity of two vectors, or `None` if their lengths differ or either is zero
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+    Some(dot / (norm_a.sqrt() * norm_b.sqrt()))
+}
+
+fn validate_embedding(embedding: &[f32]) -> Result<(), AppError> {
+    if embedding.is_empty() {
+        return Err(AppError::Validation("Embedding must not be empty".into()));
+    }
+    if embedding.iter().any(|v| !v.is_finite()) {
+        return Err(AppError::Validation(
+            "Embedding must only contain finite values".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Insert a memory, or update it in place when its `(namespace, key)` already exists
+#[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+pub async fn upsert(pool: &SqlitePool, memory: &MemoryUpsert) -> Result<MemoryRecord, AppError> {
+    validate_embedding(&memory.embedding)?;
+
+    let metadata = serde_json::to_string(&memory.metadata)
+        .map_err(|e| AppError::Validation(format!("Invalid memory metadata: {}", e)))?;
+
+    let row = sqlx::query(
+        "INSERT INTO memories (namespace, key, run_id, content, embedding, dimensions, metadata) \
+         VALUES (?, ?, ?, ?, ?, ?, ?) \
+         ON CONFLICT(namespace, key) DO UPDATE SET \
+             run_id = excluded.run_id, \
+             content = excluded.content, \
+             embedding = excluded.embedding, \
+             dimensions = excluded.dimensions, \
+             metadata = excluded.metadata, \
+             updated_at = datetime('now') \
+         RETURNING id, namespace, key, run_id, content, metadata, created_at, updated_at",
+    )
+    .bind(&memory.namespace)
+    .bind(&memory.key)
+    .bind(&memory.run_id)
+    .bind(&memory.content)
+    .bind(encode_embedding(&memory.embedding))
+    .bind(memory.embedding.len() as i64)
+    .bind(metadata)
+    .fetch_one(pool)
+    .await?;
+
+    record_from_row(&row)
+}
+
+/// Delete a memory by id
+#[tracing::instrument(level = "debug", skip(pool), err(level = "debug"))]
+pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), AppError> {
+    let result = sqlx::query("DELETE FROM memories WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Memory {} not found", id)));
+    }
+    Ok(())
+}
+
+/// Partial result collected for a single memory before ranking
+struct Candidate {
+    memory: MemoryRecord,
+    vector_score: Option<f32>,
+    text_score: Option<f32>,
+}
+
+/// Search memories by embedding similarity, text relevance, or both
+#[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+pub async fn search(
+    pool: &SqlitePool,
+    query: &MemorySearchQuery,
+) -> Result<Vec<MemorySearchHit>, AppError> {
+    let fts_query = query.text.as_deref().and_then(to_fts_query);
+    if query.embedding.is_none() && fts_query.is_none() {
+        return Err(AppError::Validation(
+            "Memory search needs an embedding or text".into(),
+        ));
+    }
+
+    let hybrid = query.embedding.is_some() && fts_query.is_some();
+    let top_k = query.top_k.unwrap_or(DEFAULT_TOP_K).clamp(1, MAX_TOP_K);
+    let vector_weight = query
+        .vector_weight
+        .unwrap_or(DEFAULT_VECTOR_WEIGHT)
+        .clamp(0.0, 1.0);
+
+    let mut candidates: HashMap<i64, Candidate> = HashMap::new();
+
+    if let Some(embedding) = &query.embedding {
+        validate_embedding(embedding)?;
+
+        // Only entries with matching dimensions can be compared
+        let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT ");
+        builder
+            .push(MEMORY_COLUMNS)
+            .push(", m.embedding FROM memories m WHERE m.namespace = ")
+            .push_bind(query.namespace.clone())
+            .push(" AND m.dimensions = ")
+            .push_bind(embedding.len() as i64);
+        push_metadata_filters(&mut builder, &query.filters);
+
+        for row in builder.build().fetch_all(pool).await? {
+            let stored: Vec<u8> = row.try_get("embedding")?;
+            let Some(similarity) = cosine_similarity(embedding, &decode_embedding(&stored)) else {
+                continue;
+            };
+            let memory = record_from_row(&row)?;
+            candidates.insert(
+                memory.id,
+                Candidate {
+                    memory,
+                    vector_score: Some(similarity),
+                    text_score: None,
+                },
+            );
+        }
+    }
+
+    if let Some(fts_query) = fts_query {
+        let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT ");
+        builder
+            .push(MEMORY_COLUMNS)
+            .push(
+                ", bm25(memories_fts) AS rank FROM memories_fts \
+                 JOIN memories m ON m.id = memories_fts.rowid \
+                 WHERE memories_fts MATCH ",
+            )
+            .push_bind(fts_query)
+            .push(" AND m.namespace = ")
+            .push_bind(query.namespace.clone());
+        push_metadata_filters(&mut builder, &query.filters);
+        builder
+            .push(" ORDER BY rank LIMIT ")
+            .push_bind((top_k * TEXT_CANDIDATES_PER_RESULT) as i64);
+
+        let rows = builder.build().fetch_all(pool).await?;
+
+        // BM25 is negative with lower meaning better; rescale to 0..1 within this result set
+        let ranks: Vec<f64> = rows
+            .iter()
+            .map(|row| row.try_get::<f64, _>("rank"))
+            .collect::<Result<_, _>>()?;
+        let best = ranks.iter().cloned().fold(f64::INFINITY, f64::min);
+        let worst = ranks.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        for (row, rank) in rows.iter().zip(ranks) {
+            let text_score = if worst > best {
+                ((worst - rank) / (worst - best)) as f32
+            } else {
+                1.0
+            };
+            let memory = record_from_row(row)?;
+            candidates
+                .entry(memory.id)
+                .or_insert(Candidate {
+                    memory,
+                    vector_score: None,
+                    text_score: None,
+                })
+                .text_score = Some(text_score);
+        }
+    }
+
+    let mut hits: Vec<MemorySearchHit> = candidates
+        .into_values()
+        .map(|candidate| {
+            let score = if hybrid {
+                vector_weight * candidate.vector_score.unwrap_or(0.0).max(0.0)
+                    + (1.0 - vector_weight) * candidate.text_score.unwrap_or(0.0)
+            } else {
+                candidate
+                    .vector_score
+                    .or(candidate.text_score)
+                    .unwrap_or(0.0)
+            };
+            MemorySearchHit {
+                memory: candidate.memory,
+                score,
+                vector_score: candidate.vector_score,
+                text_score: candidate.text_score,
+            }
+        })
+        .filter(|hit| query.min_score.is_none_or(|min| hit.score >= min))
+        .collect();
+
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.memory.id.cmp(&a.memory.id))
+    });
+    hits.truncate(top_k);
+
+    Ok(hits)
+}
+
+/// Append `AND` clauses requiring each top-level metadata field to equal the given value
+fn push_metadata_filters(builder: &mut QueryBuilder<'_, Sqlite>, filters: &Map<String, Value>) {
+    for (field, value) in filters {
+        let path = format!("$.\"{}\"", field.replace('"', ""));
+        builder
+            .push(" AND json_extract(m.metadata, ")
+            .push_bind(path);
+
+        // json_extract yields SQL values for scalars and JSON text for arrays/objects
+        match value {
+            Value::Null => {
+                builder.push(") IS NULL");
+            }
+            Value::Bool(b) => {
+                builder.push(") = ").push_bind(*b as i64);
+            }
+            Value::Number(n) => {
+                builder.push(") = ");
+                if let Some(i) = n.as_i64() {
+                    builder.push_bind(i);
+                } else {
+                    builder.push_bind(n.as_f64().unwrap_or(f64::NAN));
+                }
+            }
+            Value::String(s) => {
+                builder.push(") = ").push_bind(s.clone());
+            }
+            Value::Array(_) | Value::Object(_) => {
+                builder
+                    .push(") = json(")
+                    .push_bind(value.to_string())
+                    .push(")");
+            }
+        }
+    }
+}
+
+fn record_from_row(row: &SqliteRow) -> Result<MemoryRecord, AppError> {
+    let metadata: String = row.try_get("metadata")?;
+    Ok(MemoryRecord {
+        id: row.try_get("id")?,
+        namespace: row.try_get("namespace")?,
+        key: row.try_get("key")?,
+        run_id: row.try_get("run_id")?,
+        content: row.try_get("content")?,
+        metadata: serde_json::from_str(&metadata).unwrap_or_default(),
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::Database;
+    use serde_json::json;
+
+    fn memory(key: &str, content: &str, embedding: Vec<f32>, metadata: Value) -> MemoryUpsert {
+        MemoryUpsert {
+            namespace: "default".to_string(),
+            key: Some(key.to_string()),
+            run_id: None,
+            content: content.to_string(),
+            embedding,
+            metadata: metadata.as_object().cloned().unwrap_or_default(),
+        }
+    }
+
+    fn query(embedding: Option<Vec<f32>>, text: Option<&str>) -> MemorySearchQuery {
+        MemorySearchQuery {
+            namespace: "default".to_string(),
+            embedding,
+            text: text.map(str::to_string),
+            filters: Map::new(),
+            top_k: None,
+            vector_weight: None,
+            min_score: None,
+        }
+    }
+
+    async fn seeded() -> Database {
+        let db = Database::in_memory().await.unwrap();
+        let memories = [
+            memory(
+                "a",
+                "rust borrow checker",
+                vec![1.0, 0.0],
+                json!({"topic": "rust", "pinned": true}),
+            ),
+            memory(
+                "b",
+                "python garbage collector",
+                vec![0.0, 1.0],
+                json!({"topic": "python"}),
+            ),
+            memory(
+                "c",
+                "rust async runtime",
+                vec![0.6, 0.8],
+                json!({"topic": "rust", "level": 2}),
+            ),
+        ];
+        for memory in &memories {
+            upsert(db.pool(), memory).await.unwrap();
+        }
+        db
+    }
+
+    fn keys(hits: &[MemorySearchHit]) -> Vec<&str> {
+        hits.iter()
+            .map(|hit| hit.memory.key.as_deref().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn embedding_round_trips() {
+        let embedding = vec![0.0, -1.5, f32::MAX, 1e-7];
+        let bytes = encode_embedding(&embedding);
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(decode_embedding(&bytes), embedding);
+    }
+
+    #[test]
+    fn cosine_similarity_rejects_mismatched_and_zero_vectors() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0, 0.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[], &[]), None);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 0.0]), None);
+
+        let same = cosine_similarity(&[1.0, 2.0], &[2.0, 4.0]).unwrap();
+        assert!((same - 1.0).abs() < 1e-6);
+        let opposite = cosine_similarity(&[1.0, 0.0], &[-1.0, 0.0]).unwrap();
+        assert!((opposite + 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), Some(0.0));
+    }
+
+    #[tokio::test]
+    async fn vector_search_ranks_by_similarity() {
+        let db = seeded().await;
+        let hits = search(db.pool(), &query(Some(vec![1.0, 0.1]), None))
+            .await
+            .unwrap();
+        assert_eq!(keys(&hits), ["a", "c", "b"]);
+        assert!(hits.iter().all(|hit| hit.text_score.is_none()));
+
+        // Entries with other dimensions are not comparable
+        let hits = search(db.pool(), &query(Some(vec![1.0, 0.0, 0.0]), None))
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn hybrid_search_blends_vector_and_text_scores() {
+        let db = seeded().await;
+
+        // Text alone prefers the rust memories; the vector pulls toward "b"
+        let mut hybrid = query(Some(vec![0.0, 1.0]), Some("rust"));
+        hybrid.vector_weight = Some(0.2);
+        let hits = search(db.pool(), &hybrid).await.unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!(hits[..2].iter().all(|hit| hit.text_score.is_some()));
+        assert_eq!(keys(&hits)[2], "b");
+
+        hybrid.vector_weight = Some(1.0);
+        let hits = search(db.pool(), &hybrid).await.unwrap();
+        assert_eq!(keys(&hits)[0], "b");
+        for hit in &hits {
+            let expected = hit.vector_score.unwrap().max(0.0);
+            assert!((hit.score - expected).abs() < 1e-6);
+        }
+    }
+
+    #[tokio::test]
+    async fn metadata_filters_match_exactly() {
+        let db = seeded().await;
+        let mut filtered = query(Some(vec![1.0, 0.0]), None);
+
+        filtered.filters = json!({"topic": "rust"}).as_object().cloned().unwrap();
+        let mut found = keys(&search(db.pool(), &filtered).await.unwrap())
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(found, ["a", "c"]);
+
+        filtered.filters = json!({"pinned": true}).as_object().cloned().unwrap();
+        assert_eq!(keys(&search(db.pool(), &filtered).await.unwrap()), ["a"]);
+
+        filtered.filters = json!({"level": 2}).as_object().cloned().unwrap();
+        assert_eq!(keys(&search(db.pool(), &filtered).await.unwrap()), ["c"]);
+
+        filtered.filters = json!({"topic": "go"}).as_object().cloned().unwrap();
+        assert!(search(db.pool(), &filtered).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn upsert_updates_existing_key() {
+        let db = seeded().await;
+        let updated = upsert(
+            db.pool(),
+            &memory("a", "rust lifetimes", vec![1.0, 0.0], json!({})),
+        )
+        .await
+        .unwrap();
+        let hits = search(db.pool(), &query(None, Some("lifetimes")))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].memory.id, updated.id);
+        assert!(search(db.pool(), &query(None, Some("borrow")))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,458 @@
+use crate::types::errors::AppError;
+use crate::types::message::{Attachment, MessagePart, StoredMessage, MESSAGE_ROLES};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+
+/// Render message parts as plain text for `messages.content` and full-text search
+pub fn plain_text(parts: &[MessagePart]) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            MessagePart::Text { text } => text.clone(),
+            MessagePart::ToolUse {
+                server,
+                tool,
+                arguments,
+                ..
+            } => format!("[tool_use {}/{}] {}", server, tool, arguments),
+            MessagePart::ToolResult {
+                content, is_error, ..
+            } => {
+                if *is_error {
+                    format!("[tool_error] {}", content)
+                } else {
+                    format!("[tool_result] {}", content)
+                }
+            }
+            MessagePart::Image { attachment } | MessagePart::File { attachment } => format!(
+                "[attachment {}]",
+                attachment.name.as_deref().unwrap_or(&attachment.hash)
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Append a structured message to a run
+#[tracing::instrument(level = "debug", skip(pool, parts), err(level = "debug"))]
+pub async fn append(
+    pool: &SqlitePool,
+    run_id: &str,
+    role: &str,
+    parts: Vec<MessagePart>,
+) -> Result<StoredMessage, AppError> {
+    if !MESSAGE_ROLES.contains(&role) {
+        return Err(AppError::Validation(format!(
+            "Unknown message role '{}'",
+            role
+        )));
+    }
+    if parts.is_empty() {
+        return Err(AppError::Validation(
+            "Message must have at least one part".into(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    let (id, created_at) = insert_in_tx(&mut tx, run_id, role, &parts, None).await?;
+    tx.commit().await?;
+
+    Ok(StoredMessage {
+        id,
+        run_id: run_id.to_string(),
+        role: role.to_string(),
+        parts,
+        created_at,
+    })
+}
+
+/// Insert a message and its parts inside an existing transaction
+///
+/// `created_at` defaults to now; imports pass the original timestamp.
+/// Returns the new message id and its timestamp.
+#[tracing::instrument(level = "debug", skip_all, fields(run_id = %run_id, role = %role), err(level = "debug"))]
+pub async fn insert_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    run_id: &str,
+    role: &str,
+    parts: &[MessagePart],
+    created_at: Option<&str>,
+) -> Result<(i64, String), AppError> {
+    let row = sqlx::query(
+        "INSERT INTO messages (run_id, role, content, created_at) \
+         VALUES (?, ?, ?, COALESCE(?, datetime('now'))) RETURNING id, created_at",
+    )
+    .bind(run_id)
+    .bind(role)
+    .bind(plain_text(parts))
+    .bind(created_at)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(map_reference_error)?;
+    let id: i64 = row.try_get("id")?;
+    let created_at: String = row.try_get("created_at")?;
+
+    for (position, part) in parts.iter().enumerate() {
+        insert_part(tx, id, position as i64, part).await?;
+    }
+
+    Ok((id, created_at))
+}
+
+async fn insert_part(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    message_id: i64,
+    position: i64,
+    part: &MessagePart,
+) -> Result<(), AppError> {
+    let query = sqlx::query(
+        "INSERT INTO message_parts \
+         (message_id, position, kind, text, tool_use_id, server, tool, data, is_error, \
+          attachment_hash, name) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(message_id)
+    .bind(position)
+    .bind(part.kind());
+
+    let query = match part {
+        MessagePart::Text { text } => query
+            .bind(text)
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(None::<bool>)
+            .bind(None::<String>)
+            .bind(None::<String>),
+        MessagePart::ToolUse {
+            id,
+            server,
+            tool,
+            arguments,
+        } => query
+            .bind(None::<String>)
+            .bind(id)
+            .bind(server)
+            .bind(tool)
+            .bind(arguments.to_string())
+            .bind(None::<bool>)
+            .bind(None::<String>)
+            .bind(None::<String>),
+        MessagePart::ToolResult {
+            tool_use_id,
+            content,
+            data,
+            is_error,
+        } => query
+            .bind(content)
+            .bind(tool_use_id)
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(data.as_ref().map(|d| d.to_string()))
+            .bind(*is_error)
+            .bind(None::<String>)
+            .bind(None::<String>),
+        MessagePart::Image { attachment } | MessagePart::File { attachment } => query
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(None::<bool>)
+            .bind(&attachment.hash)
+            .bind(&attachment.name),
+    };
+
+    query
+        .execute(&mut **tx)
+        .await
+        .map_err(map_reference_error)?;
+    Ok(())
+}
+
+/// Report foreign key violations as invalid input rather than database failures
+fn map_reference_error(err: sqlx::Error) -> AppError {
+    match err {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            AppError::Validation("Message references an unknown run or attachment".to_string())
+        }
+        e => e.into(),
+    }
+}
+
+/// List the messages of a run in order, with their structured parts
+#[tracing::instrument(level = "debug", skip(pool), err(level = "debug"))]
+pub async fn list_for_run(pool: &SqlitePool, run_id: &str) -> Result<Vec<StoredMessage>, AppError> {
+    let message_rows = sqlx::query(
+        "SELECT id, run_id, role, content, created_at FROM messages WHERE run_id = ? ORDER BY id",
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await?;
+
+    let part_rows = sqlx::query(
+        "SELECT p.message_id, p.kind, p.text, p.tool_use_id, p.server, p.tool, p.data, \
+                p.is_error, p.attachment_hash, p.name, a.mime_type, a.size \
+         FROM message_parts p \
+         JOIN messages m ON m.id = p.message_id \
+         LEFT JOIN attachments a ON a.hash = p.attachment_hash \
+         WHERE m.run_id = ? \
+         ORDER BY p.message_id, p.position",
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut parts_by_message: HashMap<i64, Vec<MessagePart>> = HashMap::new();
+    for row in &part_rows {
+        let message_id: i64 = row.try_get("message_id")?;
+        parts_by_message
+            .entry(message_id)
+            .or_default()
+            .push(part_from_row(row)?);
+    }
+
+    message_rows
+        .iter()
+        .map(|row| {
+            let id: i64 = row.try_get("id")?;
+            // Messages written without parts (e.g. directly through the SQL plugin)
+            // are presented as a single text part
+            let parts = match parts_by_message.remove(&id) {
+                Some(parts) => parts,
+                None => vec![MessagePart::Text {
+                    text: row.try_get("content")?,
+                }],
+            };
+            Ok(StoredMessage {
+                id,
+                run_id: row.try_get("run_id")?,
+                role: row.try_get("role")?,
+                parts,
+                created_at: row.try_get("created_at")?,
+            })
+        })
+        .collect()
+}
+
+fn part_from_row(row: &SqliteRow) -> Result<MessagePart, AppError> {
+    let kind: String = row.try_get("kind")?;
+    let data: Option<String> = row.try_get("data")?;
+    let data = data.and_then(|d| serde_json::from_str(&d).ok());
+
+    let attachment = || -> Result<Attachment, AppError> {
+        Ok(Attachment {
+            hash: row.try_get("attachment_hash")?,
+            mime_type: row
+                .try_get::<Option<String>, _>("mime_type")?
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            size: row.try_get::<Option<i64>, _>("size")?.unwrap_or(0),
+            name: row.try_get("name")?,
+        })
+    };
+
+    let part = match kind.as_str() {
+        "text" => MessagePart::Text {
+            text: row
+                .try_get::<Option<String>, _>("text")?
+                .unwrap_or_default(),
+        },
+        "tool_use" => MessagePart::ToolUse {
+            id: row.try_get("tool_use_id")?,
+            server: row.try_get("server")?,
+            tool: row.try_get("tool")?,
+            arguments: data.unwrap_or_default(),
+        },
+        "tool_result" => MessagePart::ToolResult {
+            tool_use_id: row.try_get("tool_use_id")?,
+            content: row
+                .try_get::<Option<String>, _>("text")?
+                .unwrap_or_default(),
+            data,
+            is_error: row.try_get::<Option<bool>, _>("is_error")?.unwrap_or(false),
+        },
+        "image" => MessagePart::Image {
+            attachment: attachment()?,
+        },
+        "file" => MessagePart::File {
+            attachment: attachment()?,
+        },
+        other => {
+            return Err(AppError::Database(format!(
+                "Unknown message part kind '{}'",
+                other
+            )))
+        }
+    };
+    Ok(part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::Database;
+    use serde_json::json;
+
+    async fn insert_run(pool: &SqlitePool, id: &str) {
+        sqlx::query(
+            "INSERT INTO runs (id, task, status, started_at) VALUES (?, 'task', 'pending', datetime('now'))",
+        )
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn legacy_messages_become_text_parts() {
+        let database = Database::in_memory_at(3).await.unwrap();
+        insert_run(database.pool(), "run").await;
+        for (role, content) in [("user", "Summarize my inbox"), ("assistant", "")] {
+            sqlx::query("INSERT INTO messages (run_id, role, content) VALUES ('run', ?, ?)")
+                .bind(role)
+                .bind(content)
+                .execute(database.pool())
+                .await
+                .unwrap();
+        }
+
+        let database = database.migrate_to_latest().await.unwrap();
+        let parts: Vec<(i64, i64, String, Option<String>)> = sqlx::query_as(
+            "SELECT message_id, position, kind, text FROM message_parts ORDER BY message_id",
+        )
+        .fetch_all(database.pool())
+        .await
+        .unwrap();
+        assert_eq!(
+            parts,
+            [
+                (
+                    1,
+                    0,
+                    "text".to_string(),
+                    Some("Summarize my inbox".to_string())
+                ),
+                (2, 0, "text".to_string(), Some(String::new())),
+            ]
+        );
+
+        let messages = list_for_run(database.pool(), "run").await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "user");
+        assert_eq!(
+            serde_json::to_value(&messages[0].parts).unwrap(),
+            json!([{ "type": "text", "text": "Summarize my inbox" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn every_part_kind_round_trips() {
+        let database = Database::in_memory().await.unwrap();
+        insert_run(database.pool(), "run").await;
+        let hash = "ab".repeat(32);
+        sqlx::query("INSERT INTO attachments (hash, mime_type, size) VALUES (?, 'image/png', 42)")
+            .bind(&hash)
+            .execute(database.pool())
+            .await
+            .unwrap();
+        let attachment =
+            json!({ "hash": hash, "mime_type": "image/png", "size": 42, "name": null });
+        let parts = json!([
+            { "type": "text", "text": "Look at this" },
+            {
+                "type": "tool_use",
+                "id": "call-1",
+                "server": "files",
+                "tool": "read",
+                "arguments": { "path": "/tmp/a", "lines": [1, 2] },
+            },
+            {
+                "type": "tool_result",
+                "tool_use_id": "call-1",
+                "content": "contents",
+                "data": { "bytes": 8 },
+                "is_error": true,
+            },
+            { "type": "image", "attachment": attachment },
+            {
+                "type": "file",
+                "attachment": {
+                    "hash": hash,
+                    "mime_type": "image/png",
+                    "size": 42,
+                    "name": "screenshot.png",
+                },
+            },
+        ]);
+
+        let appended = append(
+            database.pool(),
+            "run",
+            "assistant",
+            serde_json::from_value(parts.clone()).unwrap(),
+        )
+        .await
+        .unwrap();
+        let listed = list_for_run(database.pool(), "run").await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, appended.id);
+        assert_eq!(listed[0].created_at, appended.created_at);
+        assert_eq!(serde_json::to_value(&listed[0].parts).unwrap(), parts);
+
+        let content: String = sqlx::query_scalar("SELECT content FROM messages")
+            .fetch_one(database.pool())
+            .await
+            .unwrap();
+        assert!(content.starts_with("Look at this\n\n[tool_use files/read] "));
+        assert!(content.contains("[tool_error] contents"));
+        assert!(content.ends_with(&format!(
+            "[attachment {}]\n\n[attachment screenshot.png]",
+            hash
+        )));
+    }
+
+    #[tokio::test]
+    async fn invalid_messages_are_rejected() {
+        let database = Database::in_memory().await.unwrap();
+        insert_run(database.pool(), "run").await;
+        let text = || {
+            vec![MessagePart::Text {
+                text: "hello".to_string(),
+            }]
+        };
+
+        for role in ["", "admin", "User"] {
+            let result = append(database.pool(), "run", role, text()).await;
+            assert!(matches!(result, Err(AppError::Validation(_))), "{:?}", role);
+        }
+        let empty = append(database.pool(), "run", "user", Vec::new()).await;
+        assert!(matches!(empty, Err(AppError::Validation(_))));
+
+        let unknown_run = append(database.pool(), "missing", "user", text()).await;
+        assert!(matches!(unknown_run, Err(AppError::Validation(_))));
+        let unknown_attachment = append(
+            database.pool(),
+            "run",
+            "user",
+            vec![MessagePart::File {
+                attachment: Attachment {
+                    hash: "cd".repeat(32),
+                    mime_type: "text/plain".to_string(),
+                    size: 1,
+                    name: None,
+                },
+            }],
+        )
+        .await;
+        assert!(matches!(unknown_attachment, Err(AppError::Validation(_))));
+
+        // Nothing was half-written
+        let messages: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM messages")
+            .fetch_one(database.pool())
+            .await
+            .unwrap();
+        assert_eq!(messages, 0);
+    }
+}
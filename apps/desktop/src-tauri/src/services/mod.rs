@@ -1,3 +1,25 @@
+pub mod attachment_store;
 pub mod config_loader;
+pub mod cron_schedule;
+pub mod database;
+pub mod file_watcher;
+pub mod global_shortcut;
 pub mod keyring_service;
+pub mod logging;
+pub mod mcp_client;
 pub mod mcp_manager;
+pub mod mcp_startup;
+pub mod memory;
+pub mod messages;
+pub mod notifications;
+pub mod process_metrics;
+pub mod run_archive;
+pub mod run_store;
+pub mod sandbox;
+pub mod scheduler;
+pub mod search;
+pub mod settings;
+pub mod sidecar_manager;
+pub mod task_queue;
+pub mod triggers;
+pub mod webhook_server;
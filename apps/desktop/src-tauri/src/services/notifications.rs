@@ -0,0 +1,698 @@
+use crate::services::database::Database;
+use crate::services::messages;
+use crate::services::run_store::RunStore;
+use crate::types::errors::AppError;
+use crate::types::message::MessagePart;
+use crate::types::notification::{
+    DoNotDisturb, Notification, NotificationAction, NotificationKind, NotificationSettings,
+    NotificationStatus,
+};
+use crate::types::run::{RunStatus, RunTransition};
+use chrono::NaiveTime;
+use serde::de::DeserializeOwned;
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Longest task excerpt shown in a notification body
+const MAX_BODY_TASK_CHARS: usize = 120;
+
+const NOTIFICATION_COLUMNS: &str =
+    "id, run_id, kind, title, body, actions, status, error, created_at, dismissed_at";
+
+/// Content of a notification before it is recorded
+struct Draft {
+    run_id: Option<String>,
+    kind: NotificationKind,
+    title: String,
+    body: String,
+    actions: Vec<NotificationAction>,
+}
+
+/// Where notifications are delivered
+///
+/// The app uses [`TauriNotificationSink`]; other implementations let the
+/// service run without a desktop session.
+pub trait NotificationSink: Send + Sync {
+    fn send(&self, notification: &Notification) -> Result<(), AppError>;
+}
+
+/// Shows OS notifications and mirrors them to the frontend
+///
+/// Desktop notification centers don't offer custom buttons, so actions are
+/// delivered with the `notification` event for the in-app notification list.
+pub struct TauriNotificationSink<R: Runtime> {
+    app: AppHandle<R>,
+}
+
+impl<R: Runtime> TauriNotificationSink<R> {
+    pub fn new(app: AppHandle<R>) -> Self {
+        Self { app }
+    }
+}
+
+impl<R: Runtime> NotificationSink for TauriNotificationSink<R> {
+    fn send(&self, notification: &Notification) -> Result<(), AppError> {
+        self.app
+            .notification()
+            .builder()
+            .title(&notification.title)
+            .body(&notification.body)
+            .show()
+            .map_err(|e| AppError::Io(format!("Failed to show notification: {}", e)))?;
+        let _ = self.app.emit("notification", notification);
+        Ok(())
+    }
+}
+
+/// Notifies the user when runs finish or need input
+///
+/// Transitions arriving within the coalescing window are combined into one
+/// summary notification. Every notification is recorded, including those
+/// suppressed by the do-not-disturb schedule.
+#[derive(Clone)]
+pub struct NotificationService {
+    database: Database,
+    run_store: RunStore,
+    sink: Arc<dyn NotificationSink>,
+    settings: Arc<RwLock<NotificationSettings>>,
+}
+
+impl NotificationService {
+    /// Create a new NotificationService delivering to `sink`
+    pub fn new(
+        database: Database,
+        run_store: RunStore,
+        sink: Arc<dyn NotificationSink>,
+        settings: NotificationSettings,
+    ) -> Self {
+        Self {
+            database,
+            run_store,
+            sink,
+            settings: Arc::new(RwLock::new(settings)),
+        }
+    }
+
+    /// Get the current notification preferences
+    pub fn settings(&self) -> NotificationSettings {
+        self.settings
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the notification preferences
+    pub fn set_settings(&self, settings: NotificationSettings) -> Result<(), AppError> {
+        validate_settings(&settings)?;
+        *self
+            .settings
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = settings;
+        Ok(())
+    }
+
+    /// Notify about run transitions until the run store is dropped
+    pub async fn run(&self) {
+        let mut transitions = self.run_store.subscribe();
+
+        loop {
+            let first = match transitions.recv().await {
+                Ok(transition) if is_notable(&transition) => transition,
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+
+            // Gather whatever else arrives within the coalescing window
+            let mut batch = vec![first];
+            let window = Duration::from_millis(self.settings().coalesce_window_ms);
+            let deadline = tokio::time::Instant::now() + window;
+            let mut closed = false;
+            loop {
+                match tokio::time::timeout_at(deadline, transitions.recv()).await {
+                    Ok(Ok(transition)) if is_notable(&transition) => batch.push(transition),
+                    Ok(Ok(_)) | Ok(Err(RecvError::Lagged(_))) => {}
+                    Ok(Err(RecvError::Closed)) => {
+                        closed = true;
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+
+            if let Err(e) = self.notify(&batch).await {
+                tracing::warn!("Failed to send notification: {}", e);
+            }
+            if closed {
+                break;
+            }
+        }
+    }
+
+    /// Build, record and send one notification for a batch of transitions
+    pub async fn notify(&self, batch: &[RunTransition]) -> Result<Option<Notification>, AppError> {
+        let settings = self.settings();
+        if !settings.enabled || batch.is_empty() {
+            return Ok(None);
+        }
+
+        let draft = match batch {
+            [transition] => self.describe(transition).await?,
+            _ => describe_summary(batch),
+        };
+        let suppressed = settings
+            .do_not_disturb
+            .as_ref()
+            .is_some_and(|dnd| in_do_not_disturb(dnd, chrono::Local::now().time()));
+        let status = if suppressed {
+            NotificationStatus::Suppressed
+        } else {
+            NotificationStatus::Sent
+        };
+
+        let row = sqlx::query(&format!(
+            "INSERT INTO notifications (run_id, kind, title, body, actions, status) \
+             VALUES (?, ?, ?, ?, ?, ?) RETURNING {}",
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(&draft.run_id)
+        .bind(draft.kind.as_str())
+        .bind(&draft.title)
+        .bind(&draft.body)
+        .bind(serde_json::to_string(&draft.actions).unwrap_or_else(|_| "[]".to_string()))
+        .bind(status.as_str())
+        .fetch_one(self.database.pool())
+        .await?;
+        let mut notification = notification_from_row(&row)?;
+
+        if !suppressed {
+            if let Err(e) = self.sink.send(&notification) {
+                notification.status = NotificationStatus::Failed;
+                notification.error = Some(e.to_string());
+                sqlx::query("UPDATE notifications SET status = ?, error = ? WHERE id = ?")
+                    .bind(notification.status.as_str())
+                    .bind(&notification.error)
+                    .bind(notification.id)
+                    .execute(self.database.pool())
+                    .await?;
+            }
+        }
+
+        Ok(Some(notification))
+    }
+
+    /// List recorded notifications, newest first
+    pub async fn list(&self, limit: i64) -> Result<Vec<Notification>, AppError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM notifications ORDER BY id DESC LIMIT ?",
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(self.database.pool())
+        .await?;
+
+        rows.iter().map(notification_from_row).collect()
+    }
+
+    /// Carry out a notification action and mark the notification as handled
+    ///
+    /// `reply` answers a run waiting for input: it is appended as a user message
+    /// and the run resumes, in one transaction that also dismisses the
+    /// notification, so a dismissed notification cannot be replied to twice.
+    /// Opening is left to the caller, which owns the window.
+    pub async fn handle_action(
+        &self,
+        id: i64,
+        action: NotificationAction,
+        reply: Option<String>,
+    ) -> Result<Notification, AppError> {
+        let notification = self.get(id).await?;
+        if !notification.actions.contains(&action) {
+            return Err(AppError::Validation(format!(
+                "Notification {} does not offer {:?}",
+                id, action
+            )));
+        }
+
+        if action == NotificationAction::Reply {
+            let run_id = notification.run_id.as_deref().ok_or_else(|| {
+                AppError::InvalidState("Notification is not about a single run".to_string())
+            })?;
+            let text = reply
+                .filter(|r| !r.trim().is_empty())
+                .ok_or_else(|| AppError::Validation("Reply must not be empty".to_string()))?;
+
+            let mut tx = self.database.pool().begin().await?;
+            let dismissed = sqlx::query(
+                "UPDATE notifications SET dismissed_at = datetime('now') \
+                 WHERE id = ? AND dismissed_at IS NULL",
+            )
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            if dismissed.rows_affected() == 0 {
+                return Err(AppError::InvalidState(format!(
+                    "Notification {} was already dismissed",
+                    id
+                )));
+            }
+
+            let status: Option<String> = sqlx::query_scalar("SELECT status FROM runs WHERE id = ?")
+                .bind(run_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let status: RunStatus = status
+                .ok_or_else(|| AppError::NotFound(format!("Run '{}' not found", run_id)))?
+                .parse()?;
+            if status != RunStatus::AwaitingInput {
+                return Err(AppError::InvalidState(format!(
+                    "Run '{}' is {}, not waiting for input",
+                    run_id, status
+                )));
+            }
+
+            messages::insert_in_tx(&mut tx, run_id, "user", &[MessagePart::Text { text }], None)
+                .await?;
+            let transition = RunStore::transition_in_tx(
+                &mut tx,
+                run_id,
+                RunStatus::Running,
+                Some("reply".to_string()),
+            )
+            .await?;
+            tx.commit().await?;
+
+            self.run_store.publish(transition);
+            return self.get(id).await;
+        }
+
+        sqlx::query(
+            "UPDATE notifications SET dismissed_at = COALESCE(dismissed_at, datetime('now')) \
+             WHERE id = ?",
+        )
+        .bind(id)
+        .execute(self.database.pool())
+        .await?;
+
+        self.get(id).await
+    }
+
+    async fn get(&self, id: i64) -> Result<Notification, AppError> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM notifications WHERE id = ?",
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(self.database.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Notification {} not found", id)))?;
+
+        notification_from_row(&row)
+    }
+
+    async fn describe(&self, transition: &RunTransition) -> Result<Draft, AppError> {
+        let run = self.run_store.get_run(&transition.run_id).await?;
+        let task = excerpt(&run.task);
+        let (kind, title, body, actions) = match transition.to {
+            RunStatus::Failed => (
+                NotificationKind::RunFailed,
+                "Run failed".to_string(),
+                match &transition.reason {
+                    Some(reason) => format!("{}\n{}", task, reason),
+                    None => task,
+                },
+                vec![NotificationAction::Open, NotificationAction::Dismiss],
+            ),
+            RunStatus::AwaitingInput => (
+                NotificationKind::InputRequested,
+                "Agent needs input".to_string(),
+                task,
+                vec![
+                    NotificationAction::Reply,
+                    NotificationAction::Open,
+                    NotificationAction::Dismiss,
+                ],
+            ),
+            _ => (
+                NotificationKind::RunCompleted,
+                "Run completed".to_string(),
+                task,
+                vec![NotificationAction::Open, NotificationAction::Dismiss],
+            ),
+        };
+
+        Ok(Draft {
+            run_id: Some(run.id),
+            kind,
+            title,
+            body,
+            actions,
+        })
+    }
+}
+
+/// Transitions worth telling the user about
+fn is_notable(transition: &RunTransition) -> bool {
+    matches!(
+        transition.to,
+        RunStatus::Completed | RunStatus::Failed | RunStatus::AwaitingInput
+    )
+}
+
+fn describe_summary(batch: &[RunTransition]) -> Draft {
+    let count = |status: RunStatus| batch.iter().filter(|t| t.to == status).count();
+    let parts: Vec<String> = [
+        (count(RunStatus::Completed), "completed"),
+        (count(RunStatus::Failed), "failed"),
+        (count(RunStatus::AwaitingInput), "waiting for input"),
+    ]
+    .into_iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, label)| format!("{} {}", n, label))
+    .collect();
+
+    Draft {
+        run_id: None,
+        kind: NotificationKind::Summary,
+        title: format!("{} run updates", batch.len()),
+        body: parts.join(", "),
+        actions: vec![NotificationAction::Open, NotificationAction::Dismiss],
+    }
+}
+
+/// Check notification preferences before they are applied
+pub fn validate_settings(settings: &NotificationSettings) -> Result<(), AppError> {
+    if let Some(dnd) = &settings.do_not_disturb {
+        parse_time(&dnd.start)?;
+        parse_time(&dnd.end)?;
+    }
+    Ok(())
+}
+
+/// Whether `now` falls inside the do-not-disturb window
+pub fn in_do_not_disturb(dnd: &DoNotDisturb, now: NaiveTime) -> bool {
+    let (Ok(start), Ok(end)) = (parse_time(&dnd.start), parse_time(&dnd.end)) else {
+        return false;
+    };
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, AppError> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| AppError::Validation(format!("Invalid time '{}', expected HH:MM", value)))
+}
+
+fn excerpt(task: &str) -> String {
+    let mut chars = task.chars();
+    let mut text: String = chars.by_ref().take(MAX_BODY_TASK_CHARS).collect();
+    if chars.next().is_some() {
+        text.push('…');
+    }
+    text
+}
+
+fn parse_stored<T: DeserializeOwned>(value: String) -> Result<T, AppError> {
+    serde_json::from_value(serde_json::Value::String(value))
+        .map_err(|e| AppError::Database(format!("Invalid stored notification: {}", e)))
+}
+
+fn notification_from_row(row: &SqliteRow) -> Result<Notification, AppError> {
+    let actions: String = row.try_get("actions")?;
+    Ok(Notification {
+        id: row.try_get("id")?,
+        run_id: row.try_get("run_id")?,
+        kind: parse_stored(row.try_get("kind")?)?,
+        title: row.try_get("title")?,
+        body: row.try_get("body")?,
+        actions: serde_json::from_str(&actions)
+            .map_err(|e| AppError::Database(format!("Invalid stored notification: {}", e)))?,
+        status: parse_stored(row.try_get("status")?)?,
+        error: row.try_get("error")?,
+        created_at: row.try_get("created_at")?,
+        dismissed_at: row.try_get("dismissed_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::run::NewRun;
+    use std::sync::Mutex;
+
+    /// Records what would have been shown, or fails every send
+    #[derive(Default)]
+    struct RecordingSink {
+        sent: Mutex<Vec<Notification>>,
+        fail: bool,
+    }
+
+    impl NotificationSink for RecordingSink {
+        fn send(&self, notification: &Notification) -> Result<(), AppError> {
+            if self.fail {
+                return Err(AppError::Io("No notification center".to_string()));
+            }
+            self.sent.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    impl RecordingSink {
+        fn sent(&self) -> Vec<Notification> {
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    async fn service(
+        sink: Arc<RecordingSink>,
+        settings: NotificationSettings,
+    ) -> NotificationService {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        NotificationService::new(database, run_store, sink, settings)
+    }
+
+    /// Create a run and walk it to `to` through the state machine
+    async fn run_reaching(run_store: &RunStore, to: RunStatus) -> RunTransition {
+        let run = run_store
+            .create_run(&NewRun {
+                task: "Write the report".to_string(),
+                agent: "default".to_string(),
+                priority: 0,
+            })
+            .await
+            .unwrap();
+        let mut last = None;
+        for status in [RunStatus::Queued, RunStatus::Running, to] {
+            last = Some(run_store.transition(&run.id, status, None).await.unwrap());
+        }
+        last.unwrap()
+    }
+
+    /// A do-not-disturb window around the current local time
+    fn dnd_now() -> DoNotDisturb {
+        let now = chrono::Local::now().time();
+        let hour = chrono::Duration::hours(1);
+        DoNotDisturb {
+            start: (now - hour).format("%H:%M").to_string(),
+            end: (now + hour).format("%H:%M").to_string(),
+        }
+    }
+
+    fn time(value: &str) -> NaiveTime {
+        parse_time(value).unwrap()
+    }
+
+    #[test]
+    fn do_not_disturb_within_a_day() {
+        let dnd = DoNotDisturb {
+            start: "09:00".to_string(),
+            end: "17:30".to_string(),
+        };
+        assert!(!in_do_not_disturb(&dnd, time("08:59")));
+        assert!(in_do_not_disturb(&dnd, time("09:00")));
+        assert!(in_do_not_disturb(&dnd, time("17:29")));
+        assert!(!in_do_not_disturb(&dnd, time("17:30")));
+    }
+
+    #[test]
+    fn do_not_disturb_spanning_midnight() {
+        let dnd = DoNotDisturb {
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+        };
+        assert!(in_do_not_disturb(&dnd, time("22:00")));
+        assert!(in_do_not_disturb(&dnd, time("23:59")));
+        assert!(in_do_not_disturb(&dnd, time("00:00")));
+        assert!(in_do_not_disturb(&dnd, time("06:59")));
+        assert!(!in_do_not_disturb(&dnd, time("07:00")));
+        assert!(!in_do_not_disturb(&dnd, time("12:00")));
+        assert!(!in_do_not_disturb(&dnd, time("21:59")));
+    }
+
+    #[tokio::test]
+    async fn transitions_within_the_window_are_coalesced() {
+        let sink = Arc::new(RecordingSink::default());
+        let service = service(
+            sink.clone(),
+            NotificationSettings {
+                coalesce_window_ms: 300,
+                ..Default::default()
+            },
+        )
+        .await;
+        let run_store = service.run_store.clone();
+        let worker = service.clone();
+        let task = tokio::spawn(async move { worker.run().await });
+
+        run_reaching(&run_store, RunStatus::Completed).await;
+        run_reaching(&run_store, RunStatus::Failed).await;
+        run_reaching(&run_store, RunStatus::AwaitingInput).await;
+        tokio::time::sleep(Duration::from_millis(600)).await;
+
+        run_reaching(&run_store, RunStatus::Completed).await;
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        task.abort();
+
+        let sent = sink.sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].kind, NotificationKind::Summary);
+        assert_eq!(sent[0].title, "3 run updates");
+        assert_eq!(sent[0].body, "1 completed, 1 failed, 1 waiting for input");
+        assert_eq!(sent[1].kind, NotificationKind::RunCompleted);
+        assert!(sent[1].run_id.is_some());
+    }
+
+    #[tokio::test]
+    async fn rows_record_sent_suppressed_and_failed() {
+        let sink = Arc::new(RecordingSink::default());
+        let service = service(sink.clone(), NotificationSettings::default()).await;
+        let completed = run_reaching(&service.run_store, RunStatus::Completed).await;
+
+        let sent = service
+            .notify(std::slice::from_ref(&completed))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sent.status, NotificationStatus::Sent);
+        assert_eq!(sink.sent().len(), 1);
+
+        service
+            .set_settings(NotificationSettings {
+                do_not_disturb: Some(dnd_now()),
+                ..Default::default()
+            })
+            .unwrap();
+        let suppressed = service
+            .notify(std::slice::from_ref(&completed))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(suppressed.status, NotificationStatus::Suppressed);
+        assert_eq!(sink.sent().len(), 1);
+
+        let failing = NotificationService {
+            sink: Arc::new(RecordingSink {
+                fail: true,
+                ..Default::default()
+            }),
+            settings: Arc::new(RwLock::new(NotificationSettings::default())),
+            ..service.clone()
+        };
+        let failed = failing.notify(&[completed]).await.unwrap().unwrap();
+        assert_eq!(failed.status, NotificationStatus::Failed);
+
+        let rows = service.list(10).await.unwrap();
+        let statuses: Vec<NotificationStatus> = rows.iter().map(|n| n.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                NotificationStatus::Failed,
+                NotificationStatus::Suppressed,
+                NotificationStatus::Sent
+            ]
+        );
+        assert_eq!(
+            rows[0].error.as_deref(),
+            Some("IO error: No notification center")
+        );
+    }
+
+    #[tokio::test]
+    async fn reply_resumes_the_run_once() {
+        let sink = Arc::new(RecordingSink::default());
+        let service = service(sink, NotificationSettings::default()).await;
+        let waiting = run_reaching(&service.run_store, RunStatus::AwaitingInput).await;
+        let notification = service
+            .notify(std::slice::from_ref(&waiting))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let handled = service
+            .handle_action(
+                notification.id,
+                NotificationAction::Reply,
+                Some("Use the Q3 numbers".to_string()),
+            )
+            .await
+            .unwrap();
+        assert!(handled.dismissed_at.is_some());
+        let run = service.run_store.get_run(&waiting.run_id).await.unwrap();
+        assert_eq!(run.status, RunStatus::Running);
+
+        let again = service
+            .handle_action(
+                notification.id,
+                NotificationAction::Reply,
+                Some("And Q4".to_string()),
+            )
+            .await;
+        assert!(matches!(again, Err(AppError::InvalidState(_))));
+
+        let replies = messages::list_for_run(service.database.pool(), &waiting.run_id)
+            .await
+            .unwrap();
+        assert_eq!(replies.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn reply_to_a_run_no_longer_waiting_changes_nothing() {
+        let sink = Arc::new(RecordingSink::default());
+        let service = service(sink, NotificationSettings::default()).await;
+        let waiting = run_reaching(&service.run_store, RunStatus::AwaitingInput).await;
+        let notification = service
+            .notify(std::slice::from_ref(&waiting))
+            .await
+            .unwrap()
+            .unwrap();
+        service
+            .run_store
+            .transition(&waiting.run_id, RunStatus::Cancelled, None)
+            .await
+            .unwrap();
+
+        let result = service
+            .handle_action(
+                notification.id,
+                NotificationAction::Reply,
+                Some("Too late".to_string()),
+            )
+            .await;
+        assert!(matches!(result, Err(AppError::InvalidState(_))));
+
+        let replies = messages::list_for_run(service.database.pool(), &waiting.run_id)
+            .await
+            .unwrap();
+        assert!(replies.is_empty());
+        let notification = service.get(notification.id).await.unwrap();
+        assert!(notification.dismissed_at.is_none());
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+/// Resource usage summed over a process and its descendants
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TreeUsage {
+    /// User plus system CPU time in clock ticks
+    pub cpu_ticks: u64,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    pub open_fds: u64,
+    pub processes: u64,
+}
+
+/// Fields of `/proc/<pid>/stat` used for metrics
+#[derive(Debug, Clone, Copy)]
+struct ProcStat {
+    ppid: u32,
+    cpu_ticks: u64,
+    threads: u64,
+    rss_pages: u64,
+}
+
+/// Snapshot of every process, read from `/proc`
+pub struct ProcessTable {
+    stats: HashMap<u32, ProcStat>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTable {
+    /// Read all processes; `None` where `/proc` is unavailable
+    pub fn read() -> Option<Self> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+
+        let mut stats = HashMap::new();
+        for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+                continue;
+            };
+            // Processes can exit between listing and reading
+            if let Some(stat) = read_stat(pid) {
+                stats.insert(pid, stat);
+            }
+        }
+        Some(Self::from_stats(stats))
+    }
+
+    fn from_stats(stats: HashMap<u32, ProcStat>) -> Self {
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (pid, stat) in &stats {
+            children.entry(stat.ppid).or_default().push(*pid);
+        }
+        Self { stats, children }
+    }
+
+    /// Usage of `root` and all its descendants, or `None` if it has exited
+    pub fn tree_usage(&self, root: u32) -> Option<TreeUsage> {
+        self.stats.get(&root)?;
+
+        let page_size = page_size();
+        let mut usage = TreeUsage::default();
+        let mut pending = vec![root];
+        while let Some(pid) = pending.pop() {
+            let Some(stat) = self.stats.get(&pid) else {
+                continue;
+            };
+            usage.cpu_ticks += stat.cpu_ticks;
+            usage.rss_bytes += stat.rss_pages * page_size;
+            usage.threads += stat.threads;
+            usage.open_fds += count_fds(pid);
+            usage.processes += 1;
+            if let Some(children) = self.children.get(&pid) {
+                pending.extend(children);
+            }
+        }
+        Some(usage)
+    }
+}
+
+fn read_stat(pid: u32) -> Option<ProcStat> {
+    parse_stat(&std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+/// Parse the contents of `/proc/<pid>/stat`; the command name is
+/// parenthesized and may hold spaces, so fields are counted from the closing
+/// parenthesis
+fn parse_stat(contents: &str) -> Option<ProcStat> {
+    let fields: Vec<&str> = contents
+        .get(contents.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    // Field n of proc(5) is at index n - 3
+    let field = |n: usize| fields.get(n - 3).and_then(|f| f.parse::<u64>().ok());
+
+    Some(ProcStat {
+        ppid: u32::try_from(field(4)?).ok()?,
+        cpu_ticks: field(14)? + field(15)?,
+        threads: field(20)?,
+        rss_pages: field(24)?,
+    })
+}
+
+fn count_fds(pid: u32) -> u64 {
+    std::fs::read_dir(format!("/proc/{}/fd", pid))
+        .map(|entries| entries.count() as u64)
+        .unwrap_or(0)
+}
+
+/// Clock ticks per second used for CPU times in `/proc`
+pub fn clock_ticks_per_second() -> u64 {
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: sysconf has no preconditions
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 {
+            return ticks as u64;
+        }
+    }
+    100
+}
+
+fn page_size() -> u64 {
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: sysconf has no preconditions
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if size > 0 {
+            return size as u64;
+        }
+    }
+    4096
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stat line with `name` as the command and fields 3 to 24 filled in
+    fn stat_line(pid: u32, name: &str, ppid: u32, utime: u64, stime: u64, threads: u64) -> String {
+        format!(
+            "{pid} ({name}) S {ppid} {pid} {pid} 0 -1 4194560 1000 0 0 0 {utime} {stime} 7 8 20 0 \
+             {threads} 0 123456 10485760 300 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 3 0 0 0 0 0"
+        )
+    }
+
+    #[test]
+    fn stat_fields_are_counted_after_the_command_name() {
+        let stat = parse_stat(&stat_line(42, "node", 1, 150, 50, 11)).unwrap();
+        assert_eq!(stat.ppid, 1);
+        // Only the process's own time, not that of waited-for children
+        assert_eq!(stat.cpu_ticks, 200);
+        assert_eq!(stat.threads, 11);
+        assert_eq!(stat.rss_pages, 300);
+
+        for name in ["Web Content", "a) S 9 9 9", "(sd-pam)", ") )"] {
+            let stat = parse_stat(&stat_line(42, name, 7, 1, 2, 3)).unwrap();
+            assert_eq!(stat.ppid, 7, "{}", name);
+            assert_eq!(stat.cpu_ticks, 3, "{}", name);
+            assert_eq!(stat.threads, 3, "{}", name);
+        }
+    }
+
+    #[test]
+    fn malformed_stat_lines_are_skipped() {
+        assert!(parse_stat("").is_none());
+        assert!(parse_stat("42 (node S 1 42").is_none());
+        assert!(parse_stat("42 (node) S 1 42 42 0 -1").is_none());
+        let line = stat_line(42, "node", 1, 1, 1, 1).replace(" 300 ", " lots ");
+        assert!(parse_stat(&line).is_none());
+    }
+
+    #[test]
+    fn tree_usage_sums_a_process_and_its_descendants() {
+        // Above the kernel's pid_max, so there are no fds to count
+        let pid = |n: u32| 4_000_000_000 + n;
+        let stats: HashMap<u32, ProcStat> = [
+            (pid(1), pid(0), 10, 1, 100),
+            (pid(2), pid(1), 20, 2, 200),
+            (pid(3), pid(2), 30, 3, 300),
+            (pid(4), pid(1), 40, 4, 400),
+            // Unrelated
+            (pid(5), pid(0), 1000, 50, 5000),
+        ]
+        .into_iter()
+        .map(|(pid, ppid, cpu_ticks, threads, rss_pages)| {
+            let stat = ProcStat {
+                ppid,
+                cpu_ticks,
+                threads,
+                rss_pages,
+            };
+            (pid, stat)
+        })
+        .collect();
+        let table = ProcessTable::from_stats(stats);
+
+        let usage = table.tree_usage(pid(1)).unwrap();
+        assert_eq!(usage.processes, 4);
+        assert_eq!(usage.cpu_ticks, 100);
+        assert_eq!(usage.threads, 10);
+        assert_eq!(usage.rss_bytes, 1000 * page_size());
+        assert_eq!(usage.open_fds, 0);
+
+        let usage = table.tree_usage(pid(2)).unwrap();
+        assert_eq!(usage.processes, 2);
+        assert_eq!(usage.cpu_ticks, 50);
+
+        assert!(table.tree_usage(pid(9)).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn the_test_process_is_sampled_with_its_children() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+
+        let table = ProcessTable::read().unwrap();
+        let own = table.tree_usage(std::process::id()).unwrap();
+        let child_usage = table.tree_usage(child.id()).unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert!(own.processes >= 2);
+        assert!(own.threads >= 2);
+        assert!(own.rss_bytes > child_usage.rss_bytes);
+        assert!(own.open_fds > 0);
+        assert_eq!(child_usage.processes, 1);
+        assert_eq!(child_usage.threads, 1);
+        assert!(child_usage.open_fds > 0);
+        assert!(clock_ticks_per_second() > 0);
+    }
+}
@@ -0,0 +1,802 @@
+use crate::services::attachment_store::AttachmentStore;
+use crate::services::database::Database;
+use crate::services::messages;
+use crate::services::run_store::RunStore;
+use crate::types::archive::{
+    ArchiveFormat, ArchivedAttachment, ArchivedToolCall, RunArchive, RUN_ARCHIVE_SCHEMA_VERSION,
+};
+use crate::types::errors::AppError;
+use crate::types::message::{MessagePart, StoredMessage, MESSAGE_ROLES};
+use crate::types::run::{Run, RunStatus};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the archive document inside zip bundles
+const ZIP_RUN_ENTRY: &str = "run.json";
+/// Directory holding attachment files inside zip bundles
+const ZIP_ATTACHMENTS_DIR: &str = "attachments/";
+
+/// Caps on the bytes an archive may expand to when read
+#[derive(Debug, Clone, Copy)]
+struct ReadLimits {
+    /// Largest single zip entry, uncompressed
+    entry: u64,
+    /// Largest archive file, and largest sum of its uncompressed entries
+    total: u64,
+}
+
+const READ_LIMITS: ReadLimits = ReadLimits {
+    entry: 256 * 1024 * 1024,
+    total: 1024 * 1024 * 1024,
+};
+
+/// Export a run with its events, messages and attachments to `path`
+pub async fn export_run(
+    database: &Database,
+    run_store: &RunStore,
+    attachment_store: &AttachmentStore,
+    run_id: &str,
+    path: &Path,
+    format: ArchiveFormat,
+) -> Result<(), AppError> {
+    let run = run_store.get_run(run_id).await?;
+    let events = run_store.list_events(run_id).await?;
+    let messages = messages::list_for_run(database.pool(), run_id).await?;
+    let tool_calls = collect_tool_calls(&messages);
+    let exported_at: String = sqlx::query_scalar("SELECT strftime('%Y-%m-%dT%H:%M:%SZ', 'now')")
+        .fetch_one(database.pool())
+        .await?;
+
+    // Unique attachments in order of first reference
+    let mut attachment_files: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    let mut attachments = Vec::new();
+    for part in messages.iter().flat_map(|m| &m.parts) {
+        if let MessagePart::Image { attachment } | MessagePart::File { attachment } = part {
+            if attachment_files.contains_key(&attachment.hash) {
+                continue;
+            }
+            let bytes = attachment_store.read(&attachment.hash).await?;
+            attachments.push(ArchivedAttachment {
+                hash: attachment.hash.clone(),
+                mime_type: attachment.mime_type.clone(),
+                size: bytes.len() as i64,
+                data_base64: match format {
+                    ArchiveFormat::Json => {
+                        Some(base64::engine::general_purpose::STANDARD.encode(&bytes))
+                    }
+                    ArchiveFormat::Zip => None,
+                },
+            });
+            attachment_files.insert(attachment.hash.clone(), bytes);
+        }
+    }
+
+    let archive = RunArchive {
+        schema_version: RUN_ARCHIVE_SCHEMA_VERSION,
+        exported_at,
+        run,
+        events,
+        messages,
+        tool_calls,
+        attachments,
+    };
+    let document = serde_json::to_vec_pretty(&archive)
+        .map_err(|e| AppError::Io(format!("Failed to serialize run archive: {}", e)))?;
+
+    let path = path.to_path_buf();
+    match format {
+        ArchiveFormat::Json => tokio::fs::write(&path, document).await?,
+        ArchiveFormat::Zip => {
+            tokio::task::spawn_blocking(move || write_zip(&path, &document, &attachment_files))
+                .await
+                .map_err(|e| AppError::Io(format!("Archive writer failed: {}", e)))??
+        }
+    }
+
+    Ok(())
+}
+
+/// Import a run archive written by [`export_run`], assigning a fresh run id
+pub async fn import_run(
+    database: &Database,
+    run_store: &RunStore,
+    attachment_store: &AttachmentStore,
+    path: &Path,
+) -> Result<Run, AppError> {
+    let path: PathBuf = path.to_path_buf();
+    let (mut archive, mut attachment_files) =
+        tokio::task::spawn_blocking(move || read_archive(&path, READ_LIMITS))
+            .await
+            .map_err(|e| AppError::Io(format!("Archive reader failed: {}", e)))??;
+
+    if archive.schema_version == 0 || archive.schema_version > RUN_ARCHIVE_SCHEMA_VERSION {
+        return Err(AppError::Validation(format!(
+            "Unsupported run archive schema version {} (supported: up to {})",
+            archive.schema_version, RUN_ARCHIVE_SCHEMA_VERSION
+        )));
+    }
+    // The same check messages::append makes; the schema does not constrain roles
+    if let Some(message) = archive
+        .messages
+        .iter()
+        .find(|m| !MESSAGE_ROLES.contains(&m.role.as_str()))
+    {
+        return Err(AppError::Validation(format!(
+            "Unknown message role '{}'",
+            message.role
+        )));
+    }
+
+    // Stored hashes are lower-case hex, so references must be too
+    for part in archive.messages.iter_mut().flat_map(|m| &mut m.parts) {
+        if let MessagePart::Image { attachment } | MessagePart::File { attachment } = part {
+            attachment.hash.make_ascii_lowercase();
+        }
+    }
+
+    // Check every attachment against its hash before storing any
+    let mut verified = Vec::with_capacity(archive.attachments.len());
+    for attachment in &archive.attachments {
+        let bytes = match &attachment.data_base64 {
+            Some(data) => base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| AppError::Validation(format!("Invalid attachment data: {}", e)))?,
+            None => attachment_files.remove(&attachment.hash).ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Archive is missing attachment '{}'",
+                    attachment.hash
+                ))
+            })?,
+        };
+        if hex::encode(Sha256::digest(&bytes)) != attachment.hash.to_ascii_lowercase() {
+            return Err(AppError::Validation(format!(
+                "Attachment '{}' does not match its contents",
+                attachment.hash
+            )));
+        }
+        verified.push((bytes, attachment.mime_type.as_str()));
+    }
+
+    // Attachment rows are part of the transaction; files written for it are
+    // removed again if it fails, so a bad archive leaves nothing behind
+    let mut new_files = Vec::new();
+    let tx = database.pool().begin().await?;
+    let imported = insert_archive(tx, attachment_store, &archive, verified, &mut new_files).await;
+    if imported.is_err() {
+        for path in new_files {
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                tracing::warn!(
+                    "Failed to remove attachment '{}' of a failed import: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    run_store.get_run(&imported?).await
+}
+
+/// Insert an archive's attachments, run, events and messages under a fresh
+/// run id, committing `tx` on success
+///
+/// Paths of attachment files created along the way are added to `new_files`.
+async fn insert_archive(
+    mut tx: sqlx::Transaction<'_, sqlx::Sqlite>,
+    attachment_store: &AttachmentStore,
+    archive: &RunArchive,
+    attachments: Vec<(Vec<u8>, &str)>,
+    new_files: &mut Vec<PathBuf>,
+) -> Result<String, AppError> {
+    // Their content hash is their identity, so no remapping is needed
+    for (bytes, mime_type) in attachments {
+        let (_, created) = attachment_store
+            .store_bytes_in_tx(&mut tx, &bytes, mime_type, None)
+            .await?;
+        new_files.extend(created);
+    }
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let run = &archive.run;
+    // An imported run is a record, not work: one exported before it finished
+    // is cancelled so the task queue never dispatches or resumes it
+    let status = if run.status.is_terminal() {
+        run.status
+    } else {
+        RunStatus::Cancelled
+    };
+
+    sqlx::query(
+        "INSERT INTO runs \
+         (id, task, status, started_at, completed_at, error, agent, priority, queued_at) \
+         VALUES (?, ?, ?, ?, COALESCE(?, CASE WHEN ? THEN datetime('now') END), ?, ?, ?, ?)",
+    )
+    .bind(&run_id)
+    .bind(&run.task)
+    .bind(status.as_str())
+    .bind(&run.started_at)
+    .bind(&run.completed_at)
+    .bind(status != run.status)
+    .bind(&run.error)
+    .bind(&run.agent)
+    .bind(run.priority)
+    .bind(&run.queued_at)
+    .execute(&mut *tx)
+    .await?;
+
+    for event in &archive.events {
+        sqlx::query(
+            "INSERT INTO run_events (run_id, from_status, to_status, reason, created_at) \
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&run_id)
+        .bind(event.from.map(|s| s.as_str()))
+        .bind(event.to.as_str())
+        .bind(&event.reason)
+        .bind(&event.at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if status != run.status {
+        sqlx::query(
+            "INSERT INTO run_events (run_id, from_status, to_status, reason) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&run_id)
+        .bind(run.status.as_str())
+        .bind(status.as_str())
+        .bind(format!("Imported while {}", run.status))
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for message in &archive.messages {
+        messages::insert_in_tx(
+            &mut tx,
+            &run_id,
+            &message.role,
+            &message.parts,
+            Some(&message.created_at),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(run_id)
+}
+
+/// Pair each tool use with its result
+fn collect_tool_calls(messages: &[StoredMessage]) -> Vec<ArchivedToolCall> {
+    let mut calls: Vec<ArchivedToolCall> = Vec::new();
+    let mut index_by_id: HashMap<String, usize> = HashMap::new();
+
+    for message in messages {
+        for part in &message.parts {
+            match part {
+                MessagePart::ToolUse {
+                    id,
+                    server,
+                    tool,
+                    arguments,
+                } => {
+                    index_by_id.insert(id.clone(), calls.len());
+                    calls.push(ArchivedToolCall {
+                        message_id: message.id,
+                        tool_use_id: id.clone(),
+                        server: server.clone(),
+                        tool: tool.clone(),
+                        arguments: arguments.clone(),
+                        result: None,
+                        is_error: None,
+                    });
+                }
+                MessagePart::ToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                    ..
+                } => {
+                    if let Some(&index) = index_by_id.get(tool_use_id) {
+                        calls[index].result = Some(content.clone());
+                        calls[index].is_error = Some(*is_error);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    calls
+}
+
+fn write_zip(
+    path: &Path,
+    document: &[u8],
+    attachment_files: &BTreeMap<String, Vec<u8>>,
+) -> Result<(), AppError> {
+    let zip_error = |e: zip::result::ZipError| AppError::Io(format!("Failed to write zip: {}", e));
+
+    let file = std::fs::File::create(path)?;
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    writer
+        .start_file(ZIP_RUN_ENTRY, options)
+        .map_err(zip_error)?;
+    writer.write_all(document)?;
+
+    for (hash, bytes) in attachment_files {
+        writer
+            .start_file(format!("{}{}", ZIP_ATTACHMENTS_DIR, hash), options)
+            .map_err(zip_error)?;
+        writer.write_all(bytes)?;
+    }
+
+    writer.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+/// Read a JSON or zip archive, detecting the format from its contents
+fn read_archive(
+    path: &Path,
+    limits: ReadLimits,
+) -> Result<(RunArchive, HashMap<String, Vec<u8>>), AppError> {
+    let size = std::fs::metadata(path)?.len();
+    if size > limits.total {
+        return Err(AppError::Validation(format!(
+            "Run archive is {} bytes, larger than the {} byte limit",
+            size, limits.total
+        )));
+    }
+    let bytes = std::fs::read(path)?;
+    let parse = |document: &[u8]| {
+        serde_json::from_slice::<RunArchive>(document)
+            .map_err(|e| AppError::Validation(format!("Invalid run archive: {}", e)))
+    };
+
+    // Zip local file header signature
+    if !bytes.starts_with(b"PK\x03\x04") {
+        return Ok((parse(&bytes)?, HashMap::new()));
+    }
+
+    let zip_error = |e: zip::result::ZipError| AppError::Validation(format!("Invalid zip: {}", e));
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(bytes)).map_err(zip_error)?;
+
+    // Entry headers can understate their size, so every read is bounded by
+    // what is left of both limits rather than by what the entry claims
+    let mut remaining = limits.total;
+    let mut read_entry = |entry: zip::read::ZipFile<'_>| -> Result<Vec<u8>, AppError> {
+        let name = entry.name().to_string();
+        let cap = limits.entry.min(remaining);
+        let mut data = Vec::new();
+        entry.take(cap + 1).read_to_end(&mut data)?;
+        if data.len() as u64 > limits.entry {
+            return Err(AppError::Validation(format!(
+                "Archive entry '{}' expands past the {} byte entry limit",
+                name, limits.entry
+            )));
+        }
+        if data.len() as u64 > cap {
+            return Err(AppError::Validation(format!(
+                "Archive expands past the {} byte limit at entry '{}'",
+                limits.total, name
+            )));
+        }
+        remaining -= data.len() as u64;
+        Ok(data)
+    };
+
+    let document = read_entry(zip.by_name(ZIP_RUN_ENTRY).map_err(zip_error)?)?;
+    let archive = parse(&document)?;
+
+    let mut attachment_files = HashMap::new();
+    for attachment in &archive.attachments {
+        let name = format!("{}{}", ZIP_ATTACHMENTS_DIR, attachment.hash);
+        if let Ok(entry) = zip.by_name(&name) {
+            attachment_files.insert(attachment.hash.clone(), read_entry(entry)?);
+        }
+    }
+
+    Ok((archive, attachment_files))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::run::NewRun;
+
+    struct Fixture {
+        dir: tempfile::TempDir,
+        database: Database,
+        run_store: RunStore,
+        attachment_store: AttachmentStore,
+    }
+
+    async fn fixture() -> Fixture {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::in_memory().await.unwrap();
+        Fixture {
+            run_store: RunStore::new(database.clone()),
+            attachment_store: AttachmentStore::new(dir.path()).unwrap(),
+            database,
+            dir,
+        }
+    }
+
+    #[tokio::test]
+    async fn unfinished_runs_are_imported_cancelled() {
+        let f = fixture().await;
+        let run = f
+            .run_store
+            .create_run(&NewRun {
+                task: "task".to_string(),
+                agent: "default".to_string(),
+                priority: 0,
+            })
+            .await
+            .unwrap();
+        f.run_store
+            .transition(&run.id, RunStatus::Queued, None)
+            .await
+            .unwrap();
+        let path = f.dir.path().join("run.json");
+        export_run(
+            &f.database,
+            &f.run_store,
+            &f.attachment_store,
+            &run.id,
+            &path,
+            ArchiveFormat::Json,
+        )
+        .await
+        .unwrap();
+
+        let imported = import_run(&f.database, &f.run_store, &f.attachment_store, &path)
+            .await
+            .unwrap();
+        assert_eq!(imported.status, RunStatus::Cancelled);
+        assert!(imported.completed_at.is_some());
+        let last = f
+            .run_store
+            .list_events(&imported.id)
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        assert_eq!(last.from, Some(RunStatus::Queued));
+        assert_eq!(last.to, RunStatus::Cancelled);
+        // Only the original is left for the queue to dispatch
+        let queued = f.run_store.list_runs(&[RunStatus::Queued]).await.unwrap();
+        assert!(queued.iter().all(|queued| queued.id == run.id));
+    }
+
+    #[tokio::test]
+    async fn mismatched_attachment_stores_nothing() {
+        let f = fixture().await;
+        let run = f
+            .run_store
+            .create_run(&NewRun {
+                task: "task".to_string(),
+                agent: "default".to_string(),
+                priority: 0,
+            })
+            .await
+            .unwrap();
+        let path = f.dir.path().join("run.json");
+        export_run(
+            &f.database,
+            &f.run_store,
+            &f.attachment_store,
+            &run.id,
+            &path,
+            ArchiveFormat::Json,
+        )
+        .await
+        .unwrap();
+
+        let mut archive: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        archive["attachments"] = serde_json::json!([{
+            "hash": "0".repeat(64),
+            "mime_type": "text/plain",
+            "size": 5,
+            "data_base64": "aGVsbG8=",
+        }]);
+        std::fs::write(&path, archive.to_string()).unwrap();
+
+        let result = import_run(&f.database, &f.run_store, &f.attachment_store, &path).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM attachments")
+            .fetch_one(f.database.pool())
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+        let files = std::fs::read_dir(f.dir.path().join("attachments"))
+            .unwrap()
+            .count();
+        assert_eq!(files, 0);
+    }
+
+    #[tokio::test]
+    async fn failed_imports_leave_no_attachments() {
+        let f = fixture().await;
+        let run = f
+            .run_store
+            .create_run(&NewRun {
+                task: "task".to_string(),
+                agent: "default".to_string(),
+                priority: 0,
+            })
+            .await
+            .unwrap();
+        let path = f.dir.path().join("run.json");
+        export_run(
+            &f.database,
+            &f.run_store,
+            &f.attachment_store,
+            &run.id,
+            &path,
+            ArchiveFormat::Json,
+        )
+        .await
+        .unwrap();
+
+        // A valid attachment, and a message referencing one that is missing
+        let mut archive: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        archive["attachments"] = serde_json::json!([{
+            "hash": hex::encode(Sha256::digest(b"hello")),
+            "mime_type": "text/plain",
+            "size": 5,
+            "data_base64": "aGVsbG8=",
+        }]);
+        archive["messages"] = serde_json::json!([{
+            "id": 1,
+            "run_id": run.id,
+            "role": "user",
+            "parts": [{
+                "type": "file",
+                "attachment": { "hash": "ab".repeat(32), "mime_type": "text/plain", "size": 1 },
+            }],
+            "created_at": "2024-01-01 00:00:00",
+        }]);
+        std::fs::write(&path, archive.to_string()).unwrap();
+
+        let result = import_run(&f.database, &f.run_store, &f.attachment_store, &path).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM attachments")
+            .fetch_one(f.database.pool())
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+        let path = f
+            .attachment_store
+            .path_for(&hex::encode(Sha256::digest(b"hello")))
+            .unwrap();
+        assert!(!path.exists());
+        let runs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM runs")
+            .fetch_one(f.database.pool())
+            .await
+            .unwrap();
+        assert_eq!(runs, 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_message_roles_are_rejected() {
+        let f = fixture().await;
+        let run = f
+            .run_store
+            .create_run(&NewRun {
+                task: "task".to_string(),
+                agent: "default".to_string(),
+                priority: 0,
+            })
+            .await
+            .unwrap();
+        messages::append(
+            f.database.pool(),
+            &run.id,
+            "user",
+            vec![MessagePart::Text {
+                text: "hello".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+        let path = f.dir.path().join("run.json");
+        export_run(
+            &f.database,
+            &f.run_store,
+            &f.attachment_store,
+            &run.id,
+            &path,
+            ArchiveFormat::Json,
+        )
+        .await
+        .unwrap();
+
+        let mut archive: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        archive["messages"][0]["role"] = serde_json::json!("root");
+        std::fs::write(&path, archive.to_string()).unwrap();
+
+        let Err(AppError::Validation(message)) =
+            import_run(&f.database, &f.run_store, &f.attachment_store, &path).await
+        else {
+            panic!("expected the role to be rejected");
+        };
+        assert!(message.contains("'root'"), "{}", message);
+        let messages: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM messages")
+            .fetch_one(f.database.pool())
+            .await
+            .unwrap();
+        assert_eq!(messages, 1);
+    }
+
+    #[tokio::test]
+    async fn upper_case_attachment_hashes_are_imported() {
+        let f = fixture().await;
+        let run = f
+            .run_store
+            .create_run(&NewRun {
+                task: "task".to_string(),
+                agent: "default".to_string(),
+                priority: 0,
+            })
+            .await
+            .unwrap();
+        let attachment = f
+            .attachment_store
+            .store_bytes(f.database.pool(), b"hello", "text/plain", None)
+            .await
+            .unwrap();
+        messages::append(
+            f.database.pool(),
+            &run.id,
+            "user",
+            vec![MessagePart::File { attachment }],
+        )
+        .await
+        .unwrap();
+        let path = f.dir.path().join("run.zip");
+        export_run(
+            &f.database,
+            &f.run_store,
+            &f.attachment_store,
+            &run.id,
+            &path,
+            ArchiveFormat::Zip,
+        )
+        .await
+        .unwrap();
+
+        // Re-bundle with every hash upper-cased, as another tool might
+        let (archive, files) = read_archive(&path, READ_LIMITS).unwrap();
+        let mut document = serde_json::to_value(&archive).unwrap();
+        let upper = |value: &mut serde_json::Value| {
+            let hash = value.as_str().unwrap().to_ascii_uppercase();
+            *value = serde_json::Value::String(hash);
+        };
+        upper(&mut document["attachments"][0]["hash"]);
+        upper(&mut document["messages"][0]["parts"][0]["attachment"]["hash"]);
+        let files = files
+            .into_iter()
+            .map(|(hash, bytes)| (hash.to_ascii_uppercase(), bytes))
+            .collect();
+        write_zip(&path, document.to_string().as_bytes(), &files).unwrap();
+
+        let imported = import_run(&f.database, &f.run_store, &f.attachment_store, &path)
+            .await
+            .unwrap();
+        let messages = messages::list_for_run(f.database.pool(), &imported.id)
+            .await
+            .unwrap();
+        let MessagePart::File { attachment } = &messages[0].parts[0] else {
+            panic!("expected a file part, got {:?}", messages[0].parts[0]);
+        };
+        assert_eq!(attachment.hash, hex::encode(Sha256::digest(b"hello")));
+        assert_eq!(attachment.size, 5);
+    }
+
+    fn bundle(dir: &Path, attachments: &[&[u8]]) -> PathBuf {
+        let mut files = BTreeMap::new();
+        let attachments: Vec<_> = attachments
+            .iter()
+            .map(|bytes| {
+                let hash = hex::encode(Sha256::digest(bytes));
+                files.insert(hash.clone(), bytes.to_vec());
+                serde_json::json!({
+                    "hash": hash,
+                    "mime_type": "application/octet-stream",
+                    "size": bytes.len(),
+                })
+            })
+            .collect();
+        let document = serde_json::json!({
+            "schema_version": RUN_ARCHIVE_SCHEMA_VERSION,
+            "exported_at": "2024-01-01T00:00:00Z",
+            "run": {
+                "id": "run",
+                "task": "task",
+                "status": "completed",
+                "started_at": "2024-01-01 00:00:00",
+                "agent": "default",
+                "priority": 0,
+            },
+            "events": [],
+            "messages": [],
+            "tool_calls": [],
+            "attachments": attachments,
+        });
+        let path = dir.join("run.zip");
+        write_zip(&path, document.to_string().as_bytes(), &files).unwrap();
+        path
+    }
+
+    #[test]
+    fn zip_entries_within_the_limits_are_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = bundle(dir.path(), &[&[0; 1000], &[1; 1000]]);
+
+        let limits = ReadLimits {
+            entry: 1000,
+            total: 4000,
+        };
+        let (archive, files) = read_archive(&path, limits).unwrap();
+        assert_eq!(archive.attachments.len(), 2);
+        assert!(files.values().all(|bytes| bytes.len() == 1000));
+    }
+
+    #[test]
+    fn oversized_zip_entries_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        // Highly compressible, so the zip itself stays small
+        let path = bundle(dir.path(), &[&[0; 100_000]]);
+        assert!(std::fs::metadata(&path).unwrap().len() < 10_000);
+
+        let limits = ReadLimits {
+            entry: 50_000,
+            total: 1_000_000,
+        };
+        let Err(AppError::Validation(message)) = read_archive(&path, limits) else {
+            panic!("expected the entry limit to be enforced");
+        };
+        assert!(message.contains("entry limit"), "{}", message);
+    }
+
+    #[test]
+    fn zips_expanding_past_the_total_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = bundle(dir.path(), &[&[0; 40_000], &[1; 40_000], &[2; 40_000]]);
+
+        let limits = ReadLimits {
+            entry: 50_000,
+            total: 100_000,
+        };
+        let Err(AppError::Validation(message)) = read_archive(&path, limits) else {
+            panic!("expected the total limit to be enforced");
+        };
+        assert!(message.contains("100000 byte limit"), "{}", message);
+    }
+
+    #[test]
+    fn oversized_archive_files_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.json");
+        std::fs::write(&path, vec![b' '; 2000]).unwrap();
+
+        let limits = ReadLimits {
+            entry: 1000,
+            total: 1000,
+        };
+        assert!(matches!(
+            read_archive(&path, limits),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+use crate::services::database::Database;
+use crate::types::errors::AppError;
+use crate::types::run::{NewRun, Run, RunStatus, RunTransition};
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+use tokio::sync::broadcast;
+
+/// Columns selected for [`Run`] rows
+pub const RUN_COLUMNS: &str =
+    "id, task, status, started_at, completed_at, error, agent, priority, queued_at";
+
+/// Capacity of the transition broadcast channel; slow subscribers skip older events
+const TRANSITION_CHANNEL_CAPACITY: usize = 256;
+
+/// Store for runs that enforces the [`RunStatus`] state machine
+///
+/// Every status change is recorded in `run_events` and published to
+/// subscribers (the Tauri event bridge, notifications, the tray).
+#[derive(Clone)]
+pub struct RunStore {
+    database: Database,
+    transitions: broadcast::Sender<RunTransition>,
+}
+
+impl RunStore {
+    /// Create a new RunStore on top of the app database
+    pub fn new(database: Database) -> Self {
+        let (transitions, _) = broadcast::channel(TRANSITION_CHANNEL_CAPACITY);
+        Self {
+            database,
+            transitions,
+        }
+    }
+
+    /// Subscribe to run status transitions
+    pub fn subscribe(&self) -> broadcast::Receiver<RunTransition> {
+        self.transitions.subscribe()
+    }
+
+    /// Create a new run in the `pending` status
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn create_run(&self, new_run: &NewRun) -> Result<Run, AppError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut tx = self.database.pool().begin().await?;
+
+        let row = sqlx::query(&format!(
+            "INSERT INTO runs (id, task, status, started_at, agent, priority) \
+             VALUES (?, ?, ?, datetime('now'), ?, ?) RETURNING {}",
+            RUN_COLUMNS
+        ))
+        .bind(&id)
+        .bind(&new_run.task)
+        .bind(RunStatus::Pending.as_str())
+        .bind(&new_run.agent)
+        .bind(new_run.priority)
+        .fetch_one(&mut *tx)
+        .await?;
+        let run = run_from_row(&row)?;
+
+        let at: String = sqlx::query_scalar(
+            "INSERT INTO run_events (run_id, from_status, to_status) VALUES (?, NULL, ?) \
+             RETURNING created_at",
+        )
+        .bind(&id)
+        .bind(RunStatus::Pending.as_str())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.publish(RunTransition {
+            run_id: id,
+            from: None,
+            to: RunStatus::Pending,
+            reason: None,
+            at,
+        });
+        Ok(run)
+    }
+
+    /// Get a run by id
+    #[tracing::instrument(level = "debug", skip(self), err(level = "debug"))]
+    pub async fn get_run(&self, id: &str) -> Result<Run, AppError> {
+        let row = sqlx::query(&format!("SELECT {} FROM runs WHERE id = ?", RUN_COLUMNS))
+            .bind(id)
+            .fetch_optional(self.database.pool())
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Run '{}' not found", id)))?;
+
+        run_from_row(&row)
+    }
+
+    /// List runs, most recent first, optionally restricted to some statuses
+    #[tracing::instrument(level = "debug", skip(self), err(level = "debug"))]
+    pub async fn list_runs(&self, statuses: &[RunStatus]) -> Result<Vec<Run>, AppError> {
+        let mut builder = sqlx::QueryBuilder::new(format!("SELECT {} FROM runs", RUN_COLUMNS));
+        if !statuses.is_empty() {
+            builder.push(" WHERE status IN (");
+            let mut separated = builder.separated(", ");
+            for status in statuses {
+                separated.push_bind(status.as_str());
+            }
+            separated.push_unseparated(")");
+        }
+        builder.push(" ORDER BY datetime(started_at) DESC, rowid DESC");
+
+        let rows = builder.build().fetch_all(self.database.pool()).await?;
+        rows.iter().map(run_from_row).collect()
+    }
+
+    /// Move a run to a new status, rejecting transitions the state machine forbids
+    ///
+    /// `reason` is recorded on the event; for `failed` it is also stored as the run error.
+    #[tracing::instrument(level = "debug", skip(self, reason), err(level = "debug"))]
+    pub async fn transition(
+        &self,
+        id: &str,
+        to: RunStatus,
+        reason: Option<String>,
+    ) -> Result<RunTransition, AppError> {
+        let mut tx = self.database.pool().begin().await?;
+        let transition = Self::transition_in_tx(&mut tx, id, to, reason).await?;
+        tx.commit().await?;
+
+        self.publish(transition.clone());
+        Ok(transition)
+    }
+
+    /// Move a run to a new status inside an existing transaction
+    ///
+    /// Nothing is published; the caller passes the returned transition to
+    /// [`RunStore::publish`] once the transaction commits.
+    pub async fn transition_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        id: &str,
+        to: RunStatus,
+        reason: Option<String>,
+    ) -> Result<RunTransition, AppError> {
+        let current: Option<String> = sqlx::query_scalar("SELECT status FROM runs WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut **tx)
+            .await?;
+        let from: RunStatus = current
+            .ok_or_else(|| AppError::NotFound(format!("Run '{}' not found", id)))?
+            .parse()?;
+
+        if !from.can_transition_to(to) {
+            return Err(AppError::InvalidState(format!(
+                "Run '{}' cannot move from {} to {}",
+                id, from, to
+            )));
+        }
+
+        // Guard on the previous status so a concurrent transition can't be overwritten
+        let result = sqlx::query(
+            "UPDATE runs SET status = ?, \
+                 completed_at = CASE WHEN ? THEN datetime('now') ELSE completed_at END, \
+                 queued_at = CASE WHEN ? THEN datetime('now') ELSE queued_at END, \
+                 error = CASE WHEN ? THEN ? ELSE error END \
+             WHERE id = ? AND status = ?",
+        )
+        .bind(to.as_str())
+        .bind(to.is_terminal())
+        .bind(to == RunStatus::Queued)
+        .bind(to == RunStatus::Failed)
+        .bind(&reason)
+        .bind(id)
+        .bind(from.as_str())
+        .execute(&mut **tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::InvalidState(format!(
+                "Run '{}' changed status concurrently",
+                id
+            )));
+        }
+
+        let at: String = sqlx::query_scalar(
+            "INSERT INTO run_events (run_id, from_status, to_status, reason) VALUES (?, ?, ?, ?) \
+             RETURNING created_at",
+        )
+        .bind(id)
+        .bind(from.as_str())
+        .bind(to.as_str())
+        .bind(&reason)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(RunTransition {
+            run_id: id.to_string(),
+            from: Some(from),
+            to,
+            reason,
+            at,
+        })
+    }
+
+    /// Get the recorded status history of a run, oldest first
+    #[tracing::instrument(level = "debug", skip(self), err(level = "debug"))]
+    pub async fn list_events(&self, id: &str) -> Result<Vec<RunTransition>, AppError> {
+        let rows = sqlx::query(
+            "SELECT run_id, from_status, to_status, reason, created_at FROM run_events \
+             WHERE run_id = ? ORDER BY id",
+        )
+        .bind(id)
+        .fetch_all(self.database.pool())
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let from: Option<String> = row.try_get("from_status")?;
+                let to: String = row.try_get("to_status")?;
+                Ok(RunTransition {
+                    run_id: row.try_get("run_id")?,
+                    from: from.map(|s| s.parse()).transpose()?,
+                    to: to.parse()?,
+                    reason: row.try_get("reason")?,
+                    at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Send a committed transition to subscribers
+    pub fn publish(&self, transition: RunTransition) {
+        // No subscribers is fine; the transition is already persisted
+        let _ = self.transitions.send(transition);
+    }
+}
+
+/// Convert a row selected with [`RUN_COLUMNS`] into a [`Run`]
+pub fn run_from_row(row: &SqliteRow) -> Result<Run, AppError> {
+    let status: String = row.try_get("status")?;
+    Ok(Run {
+        id: row.try_get("id")?,
+        task: row.try_get("task")?,
+        status: status.parse()?,
+        started_at: row.try_get("started_at")?,
+        completed_at: row.try_get("completed_at")?,
+        error: row.try_get("error")?,
+        agent: row.try_get("agent")?,
+        priority: row.try_get("priority")?,
+        queued_at: row.try_get("queued_at")?,
+    })
+}
@@ -0,0 +1,468 @@
+use crate::types::config::{McpServerConfig, SandboxConfig, SandboxReport};
+use crate::types::errors::AppError;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::process::Command;
+
+/// Variables kept from the app's environment in clean environment mode
+const INHERITED_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "TZ",
+    "TMPDIR",
+    // Windows needs these to load system libraries and find profiles
+    "SYSTEMROOT",
+    "WINDIR",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PATHEXT",
+];
+
+/// Build the command for a server with its configured protections, and report
+/// which of them are in effect
+pub fn command(
+    config: &McpServerConfig,
+    env_overrides: &HashMap<String, String>,
+) -> Result<(Command, SandboxReport), AppError> {
+    let sandbox = &config.sandbox;
+    let mut report = SandboxReport::default();
+
+    let bwrap = if sandbox.bubblewrap {
+        let bwrap = find_bubblewrap();
+        if bwrap.is_none() {
+            if sandbox.require_bubblewrap {
+                return Err(AppError::Process(
+                    "Sandbox requires bubblewrap, but 'bwrap' was not found".to_string(),
+                ));
+            }
+            report.unavailable.push("bubblewrap".to_string());
+        }
+        bwrap
+    } else {
+        None
+    };
+
+    let mut cmd = match &bwrap {
+        Some(bwrap) => {
+            let mut cmd = Command::new(bwrap);
+            cmd.args(bubblewrap_args(config));
+            cmd.arg("--").arg(&config.command);
+            report.bubblewrap = true;
+            report.network_isolated = sandbox.isolate_network;
+            cmd
+        }
+        None => {
+            if sandbox.isolate_network {
+                report.unavailable.push("network isolation".to_string());
+            }
+            Command::new(&config.command)
+        }
+    };
+    cmd.args(&config.args);
+
+    if sandbox.clean_env {
+        cmd.env_clear();
+        for key in INHERITED_ENV {
+            if let Some(value) = std::env::var_os(key) {
+                cmd.env(key, value);
+            }
+        }
+        report.clean_env = true;
+    }
+
+    // Set environment variables from config
+    for (key, value) in &config.env {
+        cmd.env(key, value);
+    }
+
+    // Apply environment overrides (e.g., from keyring)
+    for (key, value) in env_overrides {
+        cmd.env(key, value);
+    }
+
+    if let Some(cwd) = &config.cwd {
+        if !cwd.is_dir() {
+            return Err(AppError::Validation(format!(
+                "Working directory '{}' does not exist",
+                cwd.display()
+            )));
+        }
+        cmd.current_dir(cwd);
+        report.cwd = Some(cwd.clone());
+    }
+
+    apply_limits(&mut cmd, sandbox, &mut report);
+
+    Ok((cmd, report))
+}
+
+/// Arguments giving the server new namespaces, a read-only view of the
+/// filesystem and a private `/tmp`; the working directory stays writable
+fn bubblewrap_args(config: &McpServerConfig) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "--die-with-parent",
+        "--new-session",
+        "--unshare-all",
+        "--ro-bind",
+        "/",
+        "/",
+        "--dev",
+        "/dev",
+        "--proc",
+        "/proc",
+        "--tmpfs",
+        "/tmp",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    if !config.sandbox.isolate_network {
+        args.push("--share-net".to_string());
+    }
+    if let Some(cwd) = &config.cwd {
+        let cwd = cwd.to_string_lossy().into_owned();
+        args.extend(["--bind".to_string(), cwd.clone(), cwd.clone()]);
+        args.extend(["--chdir".to_string(), cwd]);
+    }
+    args
+}
+
+/// Locate `bwrap` on `PATH`; bubblewrap only exists on Linux
+fn find_bubblewrap() -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join("bwrap"))
+        .find(|path| path.is_file())
+}
+
+#[cfg(target_os = "linux")]
+fn apply_limits(cmd: &mut Command, sandbox: &SandboxConfig, report: &mut SandboxReport) {
+    const MIB: u64 = 1024 * 1024;
+    /// Renders an applied limit for the sandbox report
+    type Describe = fn(u64) -> String;
+    let requested: [(_, Option<u64>, Describe); 3] = [
+        (
+            libc::RLIMIT_AS,
+            sandbox
+                .max_address_space_mb
+                .map(|mb| mb.saturating_mul(MIB)),
+            |bytes| format!("address_space={}MiB", bytes / MIB),
+        ),
+        (libc::RLIMIT_CPU, sandbox.max_cpu_seconds, |seconds| {
+            format!("cpu={}s", seconds)
+        }),
+        (libc::RLIMIT_NOFILE, sandbox.max_open_files, |files| {
+            format!("open_files={}", files)
+        }),
+    ];
+
+    let mut limits = Vec::new();
+    for (resource, value, describe) in requested {
+        let Some(value) = value else {
+            continue;
+        };
+        // Lowering the hard limit is allowed, raising it is not; a request
+        // above the current hard limit gets the hard limit instead
+        let mut current = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // SAFETY: getrlimit only writes to the rlimit it is given
+        let hard = if unsafe { libc::getrlimit(resource, &mut current) } == 0 {
+            current.rlim_max
+        } else {
+            u64::MAX
+        };
+        let capped = value > hard;
+        let value = value.min(hard);
+
+        let mut shown = describe(value);
+        if capped {
+            shown.push_str(" (capped at the hard limit)");
+        }
+        report.limits.push(shown);
+        limits.push((resource, value));
+    }
+    if limits.is_empty() {
+        return;
+    }
+
+    // SAFETY: the closure runs between fork and exec and only calls
+    // setrlimit, which is async-signal-safe
+    unsafe {
+        cmd.pre_exec(move || {
+            for (resource, value) in &limits {
+                let limit = libc::rlimit {
+                    rlim_cur: *value as libc::rlim_t,
+                    rlim_max: *value as libc::rlim_t,
+                };
+                if libc::setrlimit(*resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn apply_limits(_cmd: &mut Command, sandbox: &SandboxConfig, report: &mut SandboxReport) {
+    let requested = [
+        (sandbox.max_address_space_mb.is_some(), "address space limit"),
+        (sandbox.max_cpu_seconds.is_some(), "cpu limit"),
+        (sandbox.max_open_files.is_some(), "open file limit"),
+    ];
+    for (requested, name) in requested {
+        if requested {
+            report.unavailable.push(name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::ffi::OsStr;
+
+    fn server(command: &str, args: &[&str], settings: Value) -> McpServerConfig {
+        let mut config = json!({ "command": command, "args": args });
+        for (key, value) in settings.as_object().unwrap() {
+            config[key] = value.clone();
+        }
+        serde_json::from_value(config).unwrap()
+    }
+
+    fn overrides(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Run a sandboxed command and return its stdout
+    async fn output(config: &McpServerConfig, env_overrides: &HashMap<String, String>) -> String {
+        let (mut cmd, _) = command(config, env_overrides).unwrap();
+        let output = cmd.output().await.unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn unsandboxed_servers_run_their_command_with_config_and_keyring_env() {
+        let config = server(
+            "npx",
+            &["-y", "server"],
+            json!({ "env": { "MODE": "config", "SHARED": "config" } }),
+        );
+        let (cmd, report) = command(&config, &overrides(&[("SHARED", "keyring")])).unwrap();
+        let cmd = cmd.as_std();
+
+        assert_eq!(cmd.get_program(), "npx");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["-y", "server"]);
+        let envs: HashMap<&OsStr, Option<&OsStr>> = cmd.get_envs().collect();
+        assert_eq!(envs[OsStr::new("MODE")], Some(OsStr::new("config")));
+        // Keyring secrets win over the configured environment
+        assert_eq!(envs[OsStr::new("SHARED")], Some(OsStr::new("keyring")));
+        assert_eq!(cmd.get_current_dir(), None);
+
+        assert!(report.limits.is_empty());
+        assert!(!report.clean_env);
+        assert!(!report.bubblewrap);
+        assert!(!report.network_isolated);
+        assert_eq!(report.cwd, None);
+        assert!(report.unavailable.is_empty());
+    }
+
+    #[tokio::test]
+    async fn clean_env_keeps_only_allowlisted_config_and_keyring_variables() {
+        let config = server(
+            "sh",
+            &["-c", "env"],
+            json!({ "env": { "MODE": "config" }, "sandbox": { "cleanEnv": true } }),
+        );
+        let env_overrides = overrides(&[("API_TOKEN", "secret")]);
+        let (_, report) = command(&config, &env_overrides).unwrap();
+        assert!(report.clean_env);
+
+        let output = output(&config, &env_overrides).await;
+        let vars: HashMap<&str, &str> = output
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .collect();
+        assert_eq!(vars["MODE"], "config");
+        assert_eq!(vars["API_TOKEN"], "secret");
+        // The shell sets a few variables of its own
+        let shell_vars = ["PWD", "SHLVL", "_", "OLDPWD"];
+        for key in vars.keys() {
+            assert!(
+                INHERITED_ENV.contains(key)
+                    || shell_vars.contains(key)
+                    || ["MODE", "API_TOKEN"].contains(key),
+                "{} leaked into the clean environment",
+                key
+            );
+        }
+        if let Ok(path) = std::env::var("PATH") {
+            assert_eq!(vars["PATH"], path);
+        }
+    }
+
+    #[tokio::test]
+    async fn servers_run_in_their_working_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = server("pwd", &[], json!({ "cwd": dir.path() }));
+        let (cmd, report) = command(&config, &HashMap::new()).unwrap();
+        assert_eq!(cmd.as_std().get_current_dir(), Some(dir.path()));
+        assert_eq!(report.cwd.as_deref(), Some(dir.path()));
+        assert_eq!(
+            output(&config, &HashMap::new()).await.trim_end(),
+            dir.path().canonicalize().unwrap().to_str().unwrap()
+        );
+
+        let config = server("pwd", &[], json!({ "cwd": dir.path().join("missing") }));
+        assert!(matches!(
+            command(&config, &HashMap::new()),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn bubblewrap_mounts_a_read_only_root_with_a_writable_cwd() {
+        let config = server("server", &[], json!({ "sandbox": { "bubblewrap": true } }));
+        let args = bubblewrap_args(&config);
+        assert_eq!(
+            args,
+            [
+                "--die-with-parent",
+                "--new-session",
+                "--unshare-all",
+                "--ro-bind",
+                "/",
+                "/",
+                "--dev",
+                "/dev",
+                "--proc",
+                "/proc",
+                "--tmpfs",
+                "/tmp",
+                "--share-net",
+            ]
+        );
+
+        let config = server(
+            "server",
+            &[],
+            json!({ "cwd": "/srv/work", "sandbox": { "bubblewrap": true, "isolateNetwork": true } }),
+        );
+        let args = bubblewrap_args(&config);
+        assert!(!args.contains(&"--share-net".to_string()));
+        assert_eq!(
+            args[args.len() - 5..],
+            ["--bind", "/srv/work", "/srv/work", "--chdir", "/srv/work"]
+        );
+    }
+
+    #[test]
+    fn bubblewrap_wraps_the_command_or_is_reported_unavailable() {
+        let config = server(
+            "server",
+            &["--stdio"],
+            json!({ "sandbox": { "bubblewrap": true, "isolateNetwork": true } }),
+        );
+        let (cmd, report) = command(&config, &HashMap::new()).unwrap();
+        let cmd = cmd.as_std();
+        let args: Vec<_> = cmd.get_args().collect();
+
+        match find_bubblewrap() {
+            Some(bwrap) => {
+                assert_eq!(cmd.get_program(), bwrap.as_os_str());
+                assert_eq!(args[args.len() - 3..], ["--", "server", "--stdio"]);
+                assert!(report.bubblewrap);
+                assert!(report.network_isolated);
+                assert!(report.unavailable.is_empty());
+            }
+            None => {
+                assert_eq!(cmd.get_program(), "server");
+                assert_eq!(args, ["--stdio"]);
+                assert!(!report.bubblewrap);
+                assert!(!report.network_isolated);
+                assert_eq!(report.unavailable, ["bubblewrap", "network isolation"]);
+
+                let config = server(
+                    "server",
+                    &[],
+                    json!({ "sandbox": { "bubblewrap": true, "requireBubblewrap": true } }),
+                );
+                assert!(matches!(
+                    command(&config, &HashMap::new()),
+                    Err(AppError::Process(_))
+                ));
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn resource_limits_apply_to_the_server() {
+        let config = server(
+            "sh",
+            &["-c", "ulimit -v; ulimit -t; ulimit -n; ulimit -Hn"],
+            json!({ "sandbox": { "maxAddressSpaceMb": 4096, "maxCpuSeconds": 30, "maxOpenFiles": 64 } }),
+        );
+        let (_, report) = command(&config, &HashMap::new()).unwrap();
+        assert_eq!(
+            report.limits,
+            ["address_space=4096MiB", "cpu=30s", "open_files=64"]
+        );
+
+        // `ulimit -v` reports KiB
+        assert_eq!(
+            output(&config, &HashMap::new()).await,
+            format!("{}\n30\n64\n64\n", 4096 * 1024)
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn limits_above_the_hard_limit_are_capped() {
+        let mut current = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        assert_eq!(
+            unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut current) },
+            0
+        );
+        let hard = current.rlim_max;
+        if hard == libc::RLIM_INFINITY {
+            return;
+        }
+
+        let config = server(
+            "sh",
+            &["-c", "ulimit -n"],
+            json!({ "sandbox": { "maxOpenFiles": hard + 1000 } }),
+        );
+        let (_, report) = command(&config, &HashMap::new()).unwrap();
+        assert_eq!(
+            report.limits,
+            [format!("open_files={} (capped at the hard limit)", hard)]
+        );
+        assert_eq!(
+            output(&config, &HashMap::new()).await,
+            format!("{}\n", hard)
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use crate::services::cron_schedule::{format_sqlite_datetime, parse_sqlite_datetime, CronSchedule};
+use crate::services::triggers::TriggerStore;
+use crate::types::errors::AppError;
+use crate::types::trigger::{MisfirePolicy, Trigger, TriggerSpec};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Longest the scheduler sleeps between checks
+///
+/// Bounded so wall-clock jumps (system sleep, clock changes) are noticed promptly.
+const MAX_SLEEP: Duration = Duration::from_secs(30);
+/// A fire time this late or less still counts as on time rather than missed
+const MISFIRE_GRACE: chrono::Duration = chrono::Duration::seconds(60);
+/// Upper bound on runs created for one trigger when catching up with `fire_all`
+const MAX_CATCH_UP: usize = 100;
+
+/// Fires cron triggers on schedule
+///
+/// Each trigger's next fire time is persisted, so fire times that pass while the
+/// app is closed or the machine sleeps are detected on the next check and handled
+/// according to the trigger's [`MisfirePolicy`].
+#[derive(Clone)]
+pub struct Scheduler {
+    trigger_store: TriggerStore,
+    /// Serializes checks so a fire time is never handled twice
+    tick_lock: Arc<Mutex<()>>,
+}
+
+impl Scheduler {
+    /// Create a new Scheduler for the triggers in `trigger_store`
+    pub fn new(trigger_store: TriggerStore) -> Self {
+        Self {
+            trigger_store,
+            tick_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Fire due triggers until the trigger store is dropped
+    pub async fn run(&self) {
+        let mut changes = self.trigger_store.subscribe();
+
+        loop {
+            let next = match self.tick(Utc::now()).await {
+                Ok(next) => next,
+                Err(e) => {
+                    tracing::warn!("Scheduler check failed: {}", e);
+                    None
+                }
+            };
+            let wait = next
+                .and_then(|next| (next - Utc::now()).to_std().ok())
+                .map_or(MAX_SLEEP, |wait| wait.min(MAX_SLEEP));
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                changed = changes.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fire every cron trigger due at `now`, returning the earliest upcoming fire time
+    pub async fn tick(&self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, AppError> {
+        let _guard = self.tick_lock.lock().await;
+        let mut earliest: Option<DateTime<Utc>> = None;
+
+        for trigger in self.trigger_store.list(Some("cron")).await? {
+            if !trigger.enabled {
+                continue;
+            }
+            let TriggerSpec::Cron {
+                expression,
+                timezone,
+                misfire_policy,
+            } = &trigger.spec
+            else {
+                continue;
+            };
+            let checked = match CronSchedule::parse(expression, timezone) {
+                Ok(schedule) => {
+                    self.check_trigger(&trigger, &schedule, *misfire_policy, now)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match checked {
+                Ok(Some(next)) => earliest = Some(earliest.map_or(next, |e| e.min(next))),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to schedule trigger '{}': {}", trigger.name, e),
+            }
+        }
+
+        Ok(earliest)
+    }
+
+    /// Fire a trigger if due and persist its next fire time
+    async fn check_trigger(
+        &self,
+        trigger: &Trigger,
+        schedule: &CronSchedule,
+        misfire_policy: MisfirePolicy,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, AppError> {
+        let due = match &trigger.next_fire_at {
+            Some(next) => parse_sqlite_datetime(next)?,
+            // New or edited: start from the next fire time after now
+            None => {
+                let next = schedule.next_after(now);
+                self.save_schedule(trigger, None, next).await?;
+                return Ok(next);
+            }
+        };
+        if due > now {
+            return Ok(Some(due));
+        }
+
+        let to_fire = fire_times(schedule, misfire_policy, due, now);
+
+        let fired_at = format_sqlite_datetime(now);
+        let name = trigger.name.clone();
+        for time in &to_fire {
+            let scheduled_for = schedule.to_local_rfc3339(*time);
+            let fired_at = schedule.to_local_rfc3339(now);
+            let firing = self
+                .trigger_store
+                .fire(
+                    trigger,
+                    |key| match key {
+                        "trigger_name" => Some(name.clone()),
+                        "scheduled_for" => Some(scheduled_for.clone()),
+                        "fired_at" => Some(fired_at.clone()),
+                        _ => None,
+                    },
+                    Some(&format_sqlite_datetime(*time)),
+                    None,
+                )
+                .await?;
+            if let Some(error) = firing.error {
+                tracing::warn!(
+                    "Trigger '{}' failed to submit a run: {}",
+                    trigger.name,
+                    error
+                );
+            }
+        }
+
+        let next = schedule.next_after(now);
+        let last_fired_at = (!to_fire.is_empty()).then_some(fired_at);
+        self.save_schedule(trigger, last_fired_at, next).await?;
+        Ok(next)
+    }
+
+    async fn save_schedule(
+        &self,
+        trigger: &Trigger,
+        last_fired_at: Option<String>,
+        next: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError> {
+        self.trigger_store
+            .set_schedule(
+                &trigger.id,
+                trigger.revision,
+                last_fired_at.as_deref(),
+                next.map(format_sqlite_datetime).as_deref(),
+            )
+            .await
+    }
+}
+
+/// Fire times to handle for a trigger first due at `due`, as of `now`
+///
+/// Missed times are walked lazily, so only `fire_all` is capped and the
+/// other policies always see the most recent ones.
+fn fire_times(
+    schedule: &CronSchedule,
+    misfire_policy: MisfirePolicy,
+    due: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let missed = std::iter::once(due)
+        .chain(schedule.upcoming(due))
+        .take_while(|time| *time <= now);
+    match misfire_policy {
+        MisfirePolicy::Skip => missed
+            .skip_while(|time| now - *time > MISFIRE_GRACE)
+            .collect(),
+        MisfirePolicy::FireOnce => missed.last().into_iter().collect(),
+        MisfirePolicy::FireAll => missed.take(MAX_CATCH_UP).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, hour, minute, second)
+            .unwrap()
+    }
+
+    #[test]
+    fn policies_pick_recent_slots_after_long_outage() {
+        let schedule = CronSchedule::parse("* * * * *", "UTC").unwrap();
+        // 300 missed minutes, the last one 10 seconds ago
+        let due = at(0, 0, 0);
+        let now = at(5, 0, 10);
+
+        assert_eq!(
+            fire_times(&schedule, MisfirePolicy::Skip, due, now),
+            [at(5, 0, 0)]
+        );
+        assert_eq!(
+            fire_times(&schedule, MisfirePolicy::FireOnce, due, now),
+            [at(5, 0, 0)]
+        );
+        let all = fire_times(&schedule, MisfirePolicy::FireAll, due, now);
+        assert_eq!(all.len(), MAX_CATCH_UP);
+        assert_eq!(all[0], due);
+    }
+
+    #[test]
+    fn skip_drops_slots_outside_grace() {
+        let schedule = CronSchedule::parse("0 * * * *", "UTC").unwrap();
+        let now = at(3, 30, 0);
+        assert!(fire_times(&schedule, MisfirePolicy::Skip, at(1, 0, 0), now).is_empty());
+        assert_eq!(
+            fire_times(&schedule, MisfirePolicy::FireOnce, at(1, 0, 0), now),
+            [at(3, 0, 0)]
+        );
+        assert_eq!(
+            fire_times(&schedule, MisfirePolicy::FireAll, at(1, 0, 0), now),
+            [at(1, 0, 0), at(2, 0, 0), at(3, 0, 0)]
+        );
+    }
+}
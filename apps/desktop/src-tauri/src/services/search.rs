@@ -0,0 +1,382 @@
+use crate::types::errors::AppError;
+use crate::types::search::{MessageSearchHit, MessageSearchQuery};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+
+/// Markers wrapped around matched terms in snippets
+const HIGHLIGHT_START: &str = "<mark>";
+const HIGHLIGHT_END: &str = "</mark>";
+
+/// Control characters FTS5 places around matches, replaced by the highlight
+/// markers once the snippet text has been escaped
+const MATCH_START: char = '\u{2}';
+const MATCH_END: char = '\u{3}';
+
+/// Number of tokens included in each snippet
+const SNIPPET_TOKENS: i64 = 16;
+
+/// Convert free text into an FTS5 query that matches every term literally.
+///
+/// Each term is quoted so user input can't inject FTS5 operators or cause
+/// syntax errors (e.g. unbalanced quotes, `AND`, `NEAR`, column filters).
+pub fn to_fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// HTML-escape a snippet from FTS5, then mark its matches
+///
+/// Message text is untrusted (model output, tool results), and the snippet is
+/// rendered as HTML to show the highlights.
+fn highlight(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            MATCH_START => html.push_str(HIGHLIGHT_START),
+            MATCH_END => html.push_str(HIGHLIGHT_END),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+/// Search run messages, ranked by BM25 relevance
+#[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+pub async fn search_messages(
+    pool: &SqlitePool,
+    query: &MessageSearchQuery,
+) -> Result<Vec<MessageSearchHit>, AppError> {
+    let Some(fts_query) = to_fts_query(&query.query) else {
+        return Ok(Vec::new());
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT m.id, m.run_id, m.role, m.created_at, \
+                r.task, r.status, r.started_at, r.completed_at, \
+                snippet(messages_fts, 0, ",
+    );
+    builder
+        .push_bind(MATCH_START.to_string())
+        .push(", ")
+        .push_bind(MATCH_END.to_string())
+        .push(", '…', ")
+        .push_bind(SNIPPET_TOKENS)
+        .push(
+            ") AS snippet, bm25(messages_fts) AS score \
+             FROM messages_fts \
+             JOIN messages m ON m.id = messages_fts.rowid \
+             JOIN runs r ON r.id = m.run_id \
+             WHERE messages_fts MATCH ",
+        )
+        .push_bind(fts_query);
+
+    if !query.statuses.is_empty() {
+        builder.push(" AND r.status IN (");
+        let mut separated = builder.separated(", ");
+        for status in &query.statuses {
+            separated.push_bind(status.clone());
+        }
+        separated.push_unseparated(")");
+    }
+
+    // datetime() normalizes both stored and ISO 8601 input formats before comparing
+    if let Some(from) = &query.from {
+        builder
+            .push(" AND datetime(m.created_at) >= datetime(")
+            .push_bind(from.clone())
+            .push(")");
+    }
+    if let Some(to) = &query.to {
+        builder
+            .push(" AND datetime(m.created_at) <= datetime(")
+            .push_bind(to.clone())
+            .push(")");
+    }
+
+    builder
+        .push(" ORDER BY score, m.id DESC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset as i64);
+
+    let rows = builder.build().fetch_all(pool).await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(MessageSearchHit {
+                message_id: row.try_get("id")?,
+                run_id: row.try_get("run_id")?,
+                run_task: row.try_get("task")?,
+                run_status: row.try_get("status")?,
+                run_started_at: row.try_get("started_at")?,
+                run_completed_at: row.try_get("completed_at")?,
+                role: row.try_get("role")?,
+                created_at: row.try_get("created_at")?,
+                snippet: highlight(row.try_get("snippet")?),
+                score: row.try_get("score")?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::Database;
+    use crate::services::messages;
+    use crate::services::run_store::RunStore;
+    use crate::types::message::MessagePart;
+    use crate::types::run::{NewRun, RunStatus};
+
+    fn query(text: &str) -> MessageSearchQuery {
+        MessageSearchQuery {
+            query: text.to_string(),
+            statuses: Vec::new(),
+            from: None,
+            to: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    async fn create_run(run_store: &RunStore, task: &str) -> String {
+        run_store
+            .create_run(&NewRun {
+                task: task.to_string(),
+                agent: "default".to_string(),
+                priority: 0,
+            })
+            .await
+            .unwrap()
+            .id
+    }
+
+    async fn add_message(database: &Database, run_id: &str, text: &str, created_at: &str) {
+        let mut tx = database.pool().begin().await.unwrap();
+        messages::insert_in_tx(
+            &mut tx,
+            run_id,
+            "user",
+            &[MessagePart::Text {
+                text: text.to_string(),
+            }],
+            Some(created_at),
+        )
+        .await
+        .unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    async fn search(database: &Database, query: &MessageSearchQuery) -> Vec<String> {
+        search_messages(database.pool(), query)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.run_task)
+            .collect()
+    }
+
+    #[test]
+    fn terms_are_quoted() {
+        assert_eq!(
+            to_fts_query("deploy  the app").unwrap(),
+            r#""deploy" "the" "app""#
+        );
+        assert_eq!(to_fts_query("").as_deref(), None);
+        assert_eq!(to_fts_query(" \t\n").as_deref(), None);
+    }
+
+    #[test]
+    fn quotes_and_operators_are_escaped() {
+        assert_eq!(to_fts_query(r#"say "hi"#).unwrap(), r#""say" """hi""#);
+        assert_eq!(
+            to_fts_query("a AND b OR NOT c").unwrap(),
+            r#""a" "AND" "b" "OR" "NOT" "c""#
+        );
+        assert_eq!(
+            to_fts_query("content:x NEAR(a b) pre* ^start").unwrap(),
+            r#""content:x" "NEAR(a" "b)" "pre*" "^start""#
+        );
+    }
+
+    #[tokio::test]
+    async fn operators_in_queries_match_literally() {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        let run_id = create_run(&run_store, "music").await;
+        add_message(&database, &run_id, "rock and roll", "2024-01-01 00:00:00").await;
+
+        assert_eq!(search(&database, &query("rock AND roll")).await, ["music"]);
+        // As an operator this would match; as a term "not" is absent
+        assert!(search(&database, &query("rock NOT jazz")).await.is_empty());
+        for text in [
+            r#""rock"#,
+            "(rock",
+            "content:rock",
+            "roll*",
+            "NEAR(rock roll)",
+        ] {
+            search_messages(database.pool(), &query(text))
+                .await
+                .unwrap_or_else(|e| panic!("query {:?} failed: {}", text, e));
+        }
+        assert!(search(&database, &query("   ")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn results_are_filtered_by_run_status() {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        let pending = create_run(&run_store, "pending").await;
+        let cancelled = create_run(&run_store, "cancelled").await;
+        run_store
+            .transition(&cancelled, RunStatus::Cancelled, None)
+            .await
+            .unwrap();
+        for run_id in [&pending, &cancelled] {
+            add_message(&database, run_id, "backup the disk", "2024-01-01 00:00:00").await;
+        }
+
+        let mut filtered = query("backup");
+        filtered.statuses = vec!["cancelled".to_string()];
+        assert_eq!(search(&database, &filtered).await, ["cancelled"]);
+        filtered.statuses = vec!["cancelled".to_string(), "pending".to_string()];
+        assert_eq!(search(&database, &filtered).await.len(), 2);
+        filtered.statuses = vec!["completed".to_string()];
+        assert!(search(&database, &filtered).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn results_are_filtered_by_inclusive_dates() {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        for (task, created_at) in [
+            ("january", "2024-01-15 12:00:00"),
+            ("february", "2024-02-15 12:00:00"),
+            ("march", "2024-03-15 12:00:00"),
+        ] {
+            let run_id = create_run(&run_store, task).await;
+            add_message(&database, &run_id, "monthly report", created_at).await;
+        }
+
+        // ISO 8601 bounds compare against the stored SQLite timestamps
+        let mut filtered = query("report");
+        filtered.from = Some("2024-02-15T12:00:00Z".to_string());
+        let mut tasks = search(&database, &filtered).await;
+        tasks.sort();
+        assert_eq!(tasks, ["february", "march"]);
+
+        filtered.to = Some("2024-02-15T12:00:00Z".to_string());
+        assert_eq!(search(&database, &filtered).await, ["february"]);
+
+        filtered.from = None;
+        filtered.to = Some("2024-02-01".to_string());
+        assert_eq!(search(&database, &filtered).await, ["january"]);
+    }
+
+    #[tokio::test]
+    async fn snippets_highlight_matches() {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        let run_id = create_run(&run_store, "task").await;
+        let long = format!(
+            "{} the deployment failed {}",
+            "word ".repeat(40),
+            "word ".repeat(40)
+        );
+        add_message(&database, &run_id, &long, "2024-01-01 00:00:00").await;
+
+        let hits = search_messages(database.pool(), &query("deployments"))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        let snippet = &hits[0].snippet;
+        // Porter stemming matches the plural
+        assert!(snippet.contains("<mark>deployment</mark>"), "{}", snippet);
+        assert!(
+            snippet.starts_with('…') && snippet.ends_with('…'),
+            "{}",
+            snippet
+        );
+        assert!(snippet.split_whitespace().count() <= SNIPPET_TOKENS as usize + 2);
+        assert_eq!(hits[0].run_id, run_id);
+        assert_eq!(hits[0].run_status, "pending");
+    }
+
+    #[tokio::test]
+    async fn snippets_escape_markup() {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        let run_id = create_run(&run_store, "task").await;
+        add_message(
+            &database,
+            &run_id,
+            r#"<script>alert("payload")</script> & <img src=x onerror='x'>"#,
+            "2024-01-01 00:00:00",
+        )
+        .await;
+
+        let hits = search_messages(database.pool(), &query("payload"))
+            .await
+            .unwrap();
+        assert_eq!(
+            hits[0].snippet,
+            "&lt;script&gt;alert(&quot;<mark>payload</mark>&quot;)&lt;/script&gt; \
+             &amp; &lt;img src=x onerror=&#39;x&#39;&gt;"
+        );
+    }
+
+    #[test]
+    fn stray_match_markers_only_produce_highlights() {
+        assert_eq!(highlight("a\u{2}<b>\u{3}"), "a<mark>&lt;b&gt;</mark>");
+    }
+
+    #[tokio::test]
+    async fn limits_are_capped_and_offsets_page() {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        let run_id = create_run(&run_store, "task").await;
+        for _ in 0..3 {
+            add_message(&database, &run_id, "same text", "2024-01-01 00:00:00").await;
+        }
+
+        let mut paged = query("same");
+        paged.limit = Some(2);
+        let first = search_messages(database.pool(), &paged).await.unwrap();
+        paged.offset = Some(2);
+        let second = search_messages(database.pool(), &paged).await.unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 1);
+        assert!(first
+            .iter()
+            .all(|hit| hit.message_id != second[0].message_id));
+
+        paged.limit = Some(u32::MAX);
+        paged.offset = None;
+        assert_eq!(
+            search_messages(database.pool(), &paged)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+}
@@ -0,0 +1,403 @@
+use crate::services::logging;
+use crate::services::notifications;
+use crate::services::task_queue;
+use crate::types::errors::AppError;
+use crate::types::settings::{Settings, SETTINGS_VERSION};
+use crate::types::shortcut::Accelerator;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::watch;
+
+/// Rewrites a settings document from one version to the next
+type Migration = fn(&mut Map<String, Value>) -> Result<(), AppError>;
+
+/// Version of the first settings file; files without a `version` field are
+/// read as this version
+const FIRST_VERSION: u32 = 1;
+
+/// Migrations indexed by the version they upgrade from
+///
+/// Entry `n` turns a version `FIRST_VERSION + n` document into the next
+/// version. Append an entry and bump `SETTINGS_VERSION` when a field is
+/// renamed, moved or changes meaning; added fields only need a default.
+const MIGRATIONS: &[Migration] = &[];
+const _: () = assert!(MIGRATIONS.len() == (SETTINGS_VERSION - FIRST_VERSION) as usize);
+
+/// Persisted application settings
+///
+/// Services subscribe to be told about changes; the latest settings are
+/// always available from the receiver.
+#[derive(Clone)]
+pub struct SettingsStore {
+    path: PathBuf,
+    changes: watch::Sender<Settings>,
+    /// Serializes read-modify-write updates
+    write_lock: Arc<Mutex<()>>,
+    load_error: Option<String>,
+}
+
+impl SettingsStore {
+    /// Load settings from `path`, migrating older versions and creating the
+    /// file with defaults if it does not exist
+    ///
+    /// A file that cannot be parsed, migrated or validated is left as it is
+    /// and the defaults are used until settings are next saved; the reason is
+    /// kept for `load_error`, since logging is configured from the settings.
+    pub fn load(path: PathBuf) -> Result<Self, AppError> {
+        let (settings, load_error) = if path.exists() {
+            let contents = fs::read_to_string(&path)?;
+            match parse(&contents) {
+                Ok((settings, migrated)) => {
+                    if migrated {
+                        save(&settings, &path)?;
+                    }
+                    (settings, None)
+                }
+                Err(e) => (Settings::default(), Some(e.to_string())),
+            }
+        } else {
+            let settings = Settings::default();
+            save(&settings, &path)?;
+            (settings, None)
+        };
+
+        Ok(Self {
+            path,
+            changes: watch::channel(settings).0,
+            write_lock: Arc::new(Mutex::new(())),
+            load_error,
+        })
+    }
+
+    /// Why the settings file was ignored in favor of the defaults, if it was
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// Current settings
+    pub fn get(&self) -> Settings {
+        self.changes.borrow().clone()
+    }
+
+    /// Subscribe to settings changes
+    pub fn subscribe(&self) -> watch::Receiver<Settings> {
+        self.changes.subscribe()
+    }
+
+    /// Change part of the current settings
+    pub fn modify(&self, change: impl FnOnce(&mut Settings)) -> Result<Settings, AppError> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut settings = self.get();
+        change(&mut settings);
+        self.replace(settings)
+    }
+
+    /// Change part of the current settings along with side effects that must
+    /// agree with what is saved
+    ///
+    /// `change` runs under the write lock and may fail, leaving the settings
+    /// as they were. If saving then fails, `revert` is given the previous
+    /// settings to undo whatever `change` applied.
+    pub fn modify_with(
+        &self,
+        change: impl FnOnce(&mut Settings) -> Result<(), AppError>,
+        revert: impl FnOnce(&Settings),
+    ) -> Result<Settings, AppError> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let previous = self.get();
+        let mut settings = previous.clone();
+        change(&mut settings)?;
+        self.replace(settings).inspect_err(|_| revert(&previous))
+    }
+
+    fn replace(&self, mut settings: Settings) -> Result<Settings, AppError> {
+        settings.version = SETTINGS_VERSION;
+        validate(&settings)?;
+        save(&settings, &self.path)?;
+        self.changes.send_replace(settings.clone());
+        Ok(settings)
+    }
+}
+
+/// Check settings before they are saved
+pub fn validate(settings: &Settings) -> Result<(), AppError> {
+    if settings.secrets_service.trim().is_empty() {
+        return Err(AppError::Validation(
+            "secrets_service must not be empty".to_string(),
+        ));
+    }
+    if settings
+        .mcp_config_path
+        .as_ref()
+        .is_some_and(|path| !path.is_absolute())
+    {
+        return Err(AppError::Validation(
+            "mcp_config_path must be an absolute path".to_string(),
+        ));
+    }
+    if settings.webhooks.address.port() == 0 {
+        return Err(AppError::Validation(
+            "Webhook listener port must not be 0".to_string(),
+        ));
+    }
+    settings
+        .global_shortcut
+        .accelerator
+        .parse::<Accelerator>()?;
+    notifications::validate_settings(&settings.notifications)?;
+    task_queue::validate_limits(&settings.queue)?;
+    logging::validate_settings(&settings.logging)?;
+    Ok(())
+}
+
+/// Parse, migrate and validate the contents of a settings file
+///
+/// Returns the settings and whether any migration ran.
+fn parse(contents: &str) -> Result<(Settings, bool), AppError> {
+    let document: Value = serde_json::from_str(contents)
+        .map_err(|e| AppError::Config(format!("Failed to parse settings: {}", e)))?;
+    let (settings, migrated) = migrate(document, MIGRATIONS)?;
+    validate(&settings)?;
+    Ok((settings, migrated))
+}
+
+/// Bring a settings document up to the version after the last of `migrations`
+///
+/// Returns the settings and whether any migration ran.
+fn migrate(document: Value, migrations: &[Migration]) -> Result<(Settings, bool), AppError> {
+    let Value::Object(mut document) = document else {
+        return Err(AppError::Config(
+            "Failed to parse settings: expected a JSON object".to_string(),
+        ));
+    };
+
+    let latest = FIRST_VERSION + migrations.len() as u32;
+    let version = match document.get("version") {
+        None => FIRST_VERSION,
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= FIRST_VERSION)
+            .ok_or_else(|| AppError::Config(format!("Invalid settings version {}", value)))?,
+    };
+    if version > latest {
+        return Err(AppError::Config(format!(
+            "Settings version {} is newer than this version of the app supports ({})",
+            version, latest
+        )));
+    }
+
+    for migration in &migrations[(version - FIRST_VERSION) as usize..] {
+        migration(&mut document)?;
+    }
+    document.insert("version".to_string(), Value::from(latest));
+
+    let settings = serde_json::from_value(Value::Object(document))
+        .map_err(|e| AppError::Config(format!("Failed to parse settings: {}", e)))?;
+    Ok((settings, version < latest))
+}
+
+/// Write settings to a file
+fn save(settings: &Settings, path: &Path) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| AppError::Io(format!("Failed to serialize settings: {}", e)))?;
+
+    // Write then rename so a crash never leaves a truncated file
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Get the default path for the settings file
+pub fn default_settings_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("settings.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::queue::QueueLimits;
+    use crate::types::settings::DEFAULT_SECRETS_SERVICE;
+
+    #[test]
+    fn failed_save_reverts_applied_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SettingsStore::load(default_settings_path(dir.path())).unwrap();
+
+        let mut reverted = None;
+        let result = store.modify_with(
+            |settings| {
+                settings.window.start_hidden = true;
+                // Rejected by validation when saving
+                settings.secrets_service = String::new();
+                Ok(())
+            },
+            |previous| reverted = Some(previous.clone()),
+        );
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert!(!reverted.unwrap().window.start_hidden);
+        assert!(!store.get().window.start_hidden);
+
+        let saved = store
+            .modify_with(
+                |settings| {
+                    settings.window.start_hidden = true;
+                    Ok(())
+                },
+                |_| panic!("nothing to revert"),
+            )
+            .unwrap();
+        assert!(saved.window.start_hidden);
+        let reloaded = SettingsStore::load(default_settings_path(dir.path())).unwrap();
+        assert!(reloaded.get().window.start_hidden);
+    }
+
+    /// A version 1 file as written by the first release with settings,
+    /// before the logging section existed
+    const FIRST_RELEASE: &str = r#"{
+        "version": 1,
+        "window": { "show_in_dock": true, "start_hidden": false, "hide_on_blur": true },
+        "secrets_service": "com.aios.secrets",
+        "mcp_config_path": null,
+        "global_shortcut": { "enabled": true, "accelerator": "Alt+Space", "action": "toggle" },
+        "notifications": { "enabled": false, "coalesce_window_ms": 2000, "do_not_disturb": null },
+        "queue": { "max_concurrent": 2, "max_per_agent": 1, "agent_limits": { "coder": 2 } },
+        "webhooks": { "address": "127.0.0.1:8787" }
+    }"#;
+
+    #[test]
+    fn first_release_files_load_with_defaults_for_new_fields() {
+        let (settings, migrated) = parse(FIRST_RELEASE).unwrap();
+        assert!(!migrated);
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert!(settings.window.show_in_dock);
+        assert!(settings.window.hide_on_blur);
+        assert_eq!(settings.global_shortcut.accelerator, "Alt+Space");
+        assert!(!settings.notifications.enabled);
+        assert_eq!(settings.queue.max_concurrent, 2);
+        assert_eq!(settings.queue.agent_limits["coder"], 2);
+        assert_eq!(settings.webhooks.address.port(), 8787);
+        assert_eq!(settings.logging, Default::default());
+    }
+
+    #[test]
+    fn files_without_a_version_are_read_as_the_first_version() {
+        let (settings, _) = parse(r#"{ "window": { "start_hidden": true } }"#).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert!(settings.window.start_hidden);
+        assert_eq!(settings.secrets_service, DEFAULT_SECRETS_SERVICE);
+    }
+
+    /// Version 1 called `window.hide_on_blur` `window.autohide`
+    fn rename_autohide(document: &mut Map<String, Value>) -> Result<(), AppError> {
+        if let Some(Value::Object(window)) = document.get_mut("window") {
+            if let Some(autohide) = window.remove("autohide") {
+                window.insert("hide_on_blur".to_string(), autohide);
+            }
+        }
+        Ok(())
+    }
+
+    /// Version 2 kept `max_concurrent` at the top level
+    fn move_max_concurrent(document: &mut Map<String, Value>) -> Result<(), AppError> {
+        if let Some(max_concurrent) = document.remove("max_concurrent") {
+            let queue = document
+                .entry("queue")
+                .or_insert_with(|| serde_json::json!({ "max_concurrent": 4, "max_per_agent": 1 }));
+            queue["max_concurrent"] = max_concurrent;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn migrations_run_from_the_file_version_onwards() {
+        let migrations: &[Migration] = &[rename_autohide, move_max_concurrent];
+
+        let v1 = serde_json::json!({
+            "version": 1,
+            "window": { "autohide": true },
+            "max_concurrent": 3,
+        });
+        let (settings, migrated) = migrate(v1, migrations).unwrap();
+        assert!(migrated);
+        assert_eq!(settings.version, 3);
+        assert!(settings.window.hide_on_blur);
+        assert_eq!(settings.queue.max_concurrent, 3);
+
+        // A version 2 file already has the new name; only the second migration runs
+        let v2 = serde_json::json!({
+            "version": 2,
+            "window": { "autohide": true },
+            "max_concurrent": 3,
+        });
+        let (settings, migrated) = migrate(v2, migrations).unwrap();
+        assert!(migrated);
+        assert!(!settings.window.hide_on_blur);
+        assert_eq!(settings.queue.max_concurrent, 3);
+
+        let v3 = serde_json::json!({ "version": 3, "window": { "hide_on_blur": true } });
+        let (settings, migrated) = migrate(v3, migrations).unwrap();
+        assert!(!migrated);
+        assert!(settings.window.hide_on_blur);
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        for version in [
+            serde_json::json!(0),
+            serde_json::json!(SETTINGS_VERSION + 1),
+            serde_json::json!("1"),
+        ] {
+            let document = serde_json::json!({ "version": version });
+            assert!(matches!(
+                migrate(document, MIGRATIONS),
+                Err(AppError::Config(_))
+            ));
+        }
+        assert!(matches!(
+            migrate(serde_json::json!([]), MIGRATIONS),
+            Err(AppError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_files_fall_back_to_defaults_without_being_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = default_settings_path(dir.path());
+
+        for contents in [
+            r#"{ "version": 1, "logging": { "level": "loud" } }"#,
+            r#"{ "version": 1, "queue": { "max_concurrent": 0, "max_per_agent": 1 } }"#,
+            r#"{ "version": 1, "global_shortcut": { "accelerator": "Ctrl+Nope" } }"#,
+            r#"{ "version": 1, "window": "#,
+        ] {
+            fs::write(&path, contents).unwrap();
+            let store = SettingsStore::load(path.clone()).unwrap();
+            assert!(store.load_error().is_some(), "{}", contents);
+            assert_eq!(
+                store.get().queue.max_concurrent,
+                QueueLimits::default().max_concurrent
+            );
+            assert_eq!(store.get().logging, Default::default());
+            assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+        }
+
+        fs::write(&path, FIRST_RELEASE).unwrap();
+        let store = SettingsStore::load(path).unwrap();
+        assert!(store.load_error().is_none());
+        assert_eq!(store.get().queue.max_concurrent, 2);
+    }
+}
@@ -0,0 +1,619 @@
+use crate::types::errors::AppError;
+use backoff::backoff::Backoff;
+use backoff::ExponentialBackoff;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, ChildStdout, Command};
+use tokio::sync::{watch, RwLock};
+
+/// Prefix of the stdout line the backend prints once it is listening
+const PORT_LINE_PREFIX: &str = "BACKEND_PORT:";
+
+/// Environment variable carrying the per-launch auth token to the backend
+pub const TOKEN_ENV: &str = "AIOS_BACKEND_TOKEN";
+
+/// Timeout for requests proxied to the backend
+const PROXY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Status of the backend sidecar
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidecarStatus {
+    Stopped,
+    Starting,
+    Running,
+    Restarting,
+    Failed(String),
+}
+
+/// Snapshot of the backend sidecar returned to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendInfo {
+    pub status: SidecarStatus,
+    /// Only the Rust side talks to the backend, so the port is not sent to the webview
+    #[serde(skip)]
+    pub port: Option<u16>,
+    pub pid: Option<u32>,
+    pub restart_count: u32,
+    pub last_error: Option<String>,
+}
+
+/// Settings for launching and supervising the sidecar
+#[derive(Debug, Clone)]
+pub struct SidecarConfig {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    /// How long to wait for the `BACKEND_PORT:` line and the first healthy `/health`
+    pub startup_timeout: Duration,
+    /// Interval between `/health` polls while running
+    pub health_interval: Duration,
+    /// Consecutive failed health polls before the process is restarted
+    pub max_health_failures: u32,
+    /// Uptime after which a crash no longer counts towards the restart backoff
+    pub stable_after: Duration,
+}
+
+impl SidecarConfig {
+    /// Config for the bundled `backend` binary, which Tauri places next to the app executable
+    pub fn bundled(name: &str) -> Result<Self, AppError> {
+        let exe = std::env::current_exe()?;
+        let dir = exe
+            .parent()
+            .ok_or_else(|| AppError::NotFound("Executable directory not found".into()))?;
+        let program = dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX));
+
+        Ok(Self {
+            program,
+            args: Vec::new(),
+            env: HashMap::new(),
+            startup_timeout: Duration::from_secs(15),
+            health_interval: Duration::from_secs(10),
+            max_health_failures: 3,
+            stable_after: Duration::from_secs(60),
+        })
+    }
+}
+
+/// A response from the backend, as returned by the proxy command
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendResponse {
+    pub status: u16,
+    /// Parsed JSON body, or the raw text for non-JSON responses
+    pub body: serde_json::Value,
+}
+
+/// Parse the port announced by the backend on stdout
+pub fn parse_port_line(line: &str) -> Option<u16> {
+    line.trim()
+        .strip_prefix(PORT_LINE_PREFIX)?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Supervisor for the Node backend sidecar process
+///
+/// Launches the sidecar, waits for it to announce its port and answer `/health`,
+/// and restarts it with exponential backoff when it exits or stops responding.
+pub struct SidecarManager {
+    config: SidecarConfig,
+    info: RwLock<BackendInfo>,
+    shutdown: watch::Sender<bool>,
+    client: reqwest::Client,
+    /// Random bearer token the backend requires on every request; never leaves the Rust side
+    token: String,
+}
+
+impl SidecarManager {
+    /// Create a new SidecarManager; spawn [`SidecarManager::supervise`] to launch the process
+    pub fn new(config: SidecarConfig) -> Self {
+        let (shutdown, _) = watch::channel(false);
+        Self {
+            config,
+            info: RwLock::new(BackendInfo {
+                status: SidecarStatus::Stopped,
+                port: None,
+                pid: None,
+                restart_count: 0,
+                last_error: None,
+            }),
+            shutdown,
+            client: reqwest::Client::new(),
+            token: generate_token(),
+        }
+    }
+
+    /// Stop supervising, kill the sidecar and wait (briefly) for it to be gone
+    pub async fn stop(&self) {
+        let _ = self.shutdown.send(true);
+
+        let deadline = Instant::now() + Duration::from_secs(3);
+        while Instant::now() < deadline {
+            if matches!(
+                self.info.read().await.status,
+                SidecarStatus::Stopped | SidecarStatus::Failed(_)
+            ) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    /// Get the current sidecar status and port
+    pub async fn info(&self) -> BackendInfo {
+        self.info.read().await.clone()
+    }
+
+    /// Get the port the backend listens on, if it is running
+    pub async fn port(&self) -> Option<u16> {
+        let info = self.info.read().await;
+        match info.status {
+            SidecarStatus::Running => info.port,
+            _ => None,
+        }
+    }
+
+    /// Run the supervision loop until [`SidecarManager::stop`] is called
+    ///
+    /// Launches the sidecar and relaunches it whenever it exits or stops responding.
+    pub async fn supervise(&self) {
+        let mut shutdown = self.shutdown.subscribe();
+        let mut backoff = restart_backoff();
+
+        loop {
+            if *shutdown.borrow() {
+                break;
+            }
+            self.update(|info| {
+                info.status = SidecarStatus::Starting;
+                info.port = None;
+                info.pid = None;
+            })
+            .await;
+
+            let started_at = Instant::now();
+            let outcome = match self.launch().await {
+                Ok((child, port, stdout)) => {
+                    self.update(|info| {
+                        info.status = SidecarStatus::Running;
+                        info.port = Some(port);
+                        info.pid = child.id();
+                        info.last_error = None;
+                    })
+                    .await;
+                    self.monitor(child, port, stdout, &mut shutdown).await
+                }
+                Err(LaunchError::Fatal(e)) => {
+                    tracing::error!("Backend sidecar cannot be started: {}", e);
+                    self.update(|info| {
+                        info.status = SidecarStatus::Failed(e.clone());
+                        info.last_error = Some(e);
+                    })
+                    .await;
+                    return;
+                }
+                Err(LaunchError::Retryable(e)) => Err(e),
+            };
+
+            let error = match outcome {
+                Ok(()) => break, // Shutdown requested
+                Err(e) => e,
+            };
+
+            tracing::error!("Backend sidecar stopped: {}", error);
+            if started_at.elapsed() >= self.config.stable_after {
+                backoff.reset();
+            }
+            self.update(|info| {
+                info.status = SidecarStatus::Restarting;
+                info.port = None;
+                info.pid = None;
+                info.restart_count += 1;
+                info.last_error = Some(error);
+            })
+            .await;
+
+            let delay = backoff.next_backoff().unwrap_or(backoff.max_interval);
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+
+        self.update(|info| {
+            info.status = SidecarStatus::Stopped;
+            info.port = None;
+            info.pid = None;
+        })
+        .await;
+    }
+
+    /// Spawn the process and wait until it has announced its port and is healthy
+    async fn launch(&self) -> Result<(Child, u16, BufReader<ChildStdout>), LaunchError> {
+        let mut cmd = Command::new(&self.config.program);
+        cmd.args(&self.config.args)
+            .envs(&self.config.env)
+            .env(TOKEN_ENV, &self.token)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true);
+
+        let mut child = cmd.spawn().map_err(|e| {
+            let message = format!("Failed to spawn '{}': {}", self.config.program.display(), e);
+            if e.kind() == std::io::ErrorKind::NotFound {
+                LaunchError::Fatal(message)
+            } else {
+                LaunchError::Retryable(message)
+            }
+        })?;
+
+        let mut stdout = BufReader::new(
+            child
+                .stdout
+                .take()
+                .ok_or_else(|| LaunchError::Retryable("Sidecar stdout unavailable".into()))?,
+        );
+
+        let deadline = tokio::time::Instant::now() + self.config.startup_timeout;
+
+        let port = tokio::time::timeout_at(deadline, read_port(&mut stdout))
+            .await
+            .map_err(|_| {
+                LaunchError::Retryable(format!(
+                    "Timed out after {:?} waiting for {}",
+                    self.config.startup_timeout, PORT_LINE_PREFIX
+                ))
+            })?
+            .map_err(LaunchError::Retryable)?;
+
+        loop {
+            if self.check_health(port).await {
+                return Ok((child, port, stdout));
+            }
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(LaunchError::Retryable(format!(
+                    "Exited during startup with {}",
+                    status
+                )));
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(LaunchError::Retryable(format!(
+                    "/health did not succeed within {:?}",
+                    self.config.startup_timeout
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    }
+
+    /// Watch a running sidecar until it exits, becomes unhealthy, or shutdown is requested
+    ///
+    /// Returns `Ok(())` on shutdown and `Err(reason)` when the process must be restarted.
+    async fn monitor(
+        &self,
+        mut child: Child,
+        port: u16,
+        mut stdout: BufReader<ChildStdout>,
+        shutdown: &mut watch::Receiver<bool>,
+    ) -> Result<(), String> {
+        let mut interval = tokio::time::interval(self.config.health_interval);
+        interval.tick().await; // The first tick fires immediately
+        let mut failures = 0;
+        let mut line = String::new();
+        let mut stdout_open = true;
+
+        loop {
+            tokio::select! {
+                status = child.wait() => {
+                    return Err(match status {
+                        Ok(status) => format!("Exited with {}", status),
+                        Err(e) => format!("Failed to wait for process: {}", e),
+                    });
+                }
+                _ = shutdown.changed() => {
+                    let _ = child.kill().await;
+                    return Ok(());
+                }
+                _ = interval.tick() => {
+                    if self.check_health(port).await {
+                        failures = 0;
+                    } else {
+                        failures += 1;
+                        if failures >= self.config.max_health_failures {
+                            let _ = child.kill().await;
+                            return Err(format!("{} consecutive failed health checks", failures));
+                        }
+                    }
+                }
+                // Keep draining stdout so the pipe never fills up and blocks the backend
+                read = stdout.read_line(&mut line), if stdout_open => {
+                    if matches!(read, Ok(0) | Err(_)) {
+                        stdout_open = false;
+                    }
+                    line.clear();
+                }
+            }
+        }
+    }
+
+    /// Send an authenticated request to the backend on behalf of the webview
+    pub async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<BackendResponse, AppError> {
+        check_path(path)?;
+        let method = reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+            .map_err(|_| AppError::Validation(format!("Invalid HTTP method '{}'", method)))?;
+        let port = self
+            .port()
+            .await
+            .ok_or_else(|| AppError::InvalidState("Backend sidecar is not running".into()))?;
+
+        let mut request = self
+            .client
+            .request(method, format!("http://127.0.0.1:{}{}", port, path))
+            .bearer_auth(&self.token)
+            .timeout(PROXY_TIMEOUT);
+        if let Some(body) = body {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                AppError::Timeout(format!("Backend request to {} timed out", path))
+            } else {
+                AppError::Process(format!("Backend request failed: {}", e))
+            }
+        })?;
+        let status = response.status().as_u16();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| AppError::Process(format!("Failed to read backend response: {}", e)))?;
+        let body = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+
+        Ok(BackendResponse { status, body })
+    }
+
+    async fn check_health(&self, port: u16) -> bool {
+        self.client
+            .get(format!("http://127.0.0.1:{}/health", port))
+            .bearer_auth(&self.token)
+            .timeout(Duration::from_secs(2))
+            .send()
+            .await
+            .map(|response| response.status().is_success())
+            .unwrap_or(false)
+    }
+
+    async fn update(&self, f: impl FnOnce(&mut BackendInfo)) {
+        let mut info = self.info.write().await;
+        f(&mut info);
+    }
+}
+
+/// Delays between relaunches; never gives up
+fn restart_backoff() -> ExponentialBackoff {
+    ExponentialBackoff {
+        initial_interval: Duration::from_millis(500),
+        max_interval: Duration::from_secs(30),
+        max_elapsed_time: None,
+        ..Default::default()
+    }
+}
+
+/// Only plain paths are proxied, so the webview can't point the proxy at
+/// another host or outside the backend's routes
+fn check_path(path: &str) -> Result<(), AppError> {
+    let route = path.split(['?', '#']).next().unwrap_or_default();
+    if !path.starts_with('/') || path.starts_with("//") {
+        return Err(AppError::Validation(format!(
+            "Backend path must start with a single '/': '{}'",
+            path
+        )));
+    }
+    if path.contains('\\')
+        || path.chars().any(|c| c.is_ascii_control())
+        || route.split('/').any(|segment| segment == "..")
+    {
+        return Err(AppError::Validation(format!(
+            "Invalid backend path '{}'",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// Why a launch attempt failed
+enum LaunchError {
+    /// Retrying cannot help (e.g. the binary is missing)
+    Fatal(String),
+    Retryable(String),
+}
+
+/// Generate a random 256-bit token, hex encoded
+fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    hex::encode(bytes)
+}
+
+/// Read stdout lines until the port announcement
+async fn read_port(stdout: &mut BufReader<ChildStdout>) -> Result<u16, String> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = stdout
+            .read_line(&mut line)
+            .await
+            .map_err(|e| format!("Failed to read sidecar stdout: {}", e))?;
+        if read == 0 {
+            return Err("Sidecar closed stdout before announcing its port".into());
+        }
+        if let Some(port) = parse_port_line(&line) {
+            return Ok(port);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn shell(script: &str, startup_timeout: Duration) -> SidecarManager {
+        SidecarManager::new(SidecarConfig {
+            program: PathBuf::from("sh"),
+            args: vec!["-c".to_string(), script.to_string()],
+            env: HashMap::new(),
+            startup_timeout,
+            health_interval: Duration::from_secs(10),
+            max_health_failures: 3,
+            stable_after: Duration::from_secs(60),
+        })
+    }
+
+    fn launch_error(result: Result<(Child, u16, BufReader<ChildStdout>), LaunchError>) -> String {
+        match result {
+            Err(LaunchError::Retryable(e)) => e,
+            Err(LaunchError::Fatal(e)) => panic!("unexpected fatal error: {}", e),
+            Ok(_) => panic!("launch should have failed"),
+        }
+    }
+
+    #[test]
+    fn port_lines_are_parsed() {
+        assert_eq!(parse_port_line("BACKEND_PORT:8080"), Some(8080));
+        assert_eq!(parse_port_line("  BACKEND_PORT: 41234 \r\n"), Some(41234));
+        assert_eq!(parse_port_line("BACKEND_PORT:65535"), Some(65535));
+        for line in [
+            "",
+            "Listening on 8080",
+            "backend_port:8080",
+            "INFO BACKEND_PORT:8080",
+            "BACKEND_PORT:",
+            "BACKEND_PORT:http",
+            "BACKEND_PORT:8080abc",
+            "BACKEND_PORT:-1",
+            "BACKEND_PORT:65536",
+        ] {
+            assert_eq!(parse_port_line(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn proxied_paths_stay_on_the_backend() {
+        for path in [
+            "/",
+            "/health",
+            "/api/runs?status=running&after=..",
+            "/a..b/c",
+        ] {
+            assert!(check_path(path).is_ok(), "{:?}", path);
+        }
+        for path in [
+            "",
+            "api/runs",
+            "//evil.example/api",
+            "http://evil.example/api",
+            "https://127.0.0.1:1/",
+            "/\\evil.example",
+            "/api/../admin",
+            "/..",
+            "/api/..?x=1",
+            "/api\nHost: evil",
+        ] {
+            assert!(
+                matches!(check_path(path), Err(AppError::Validation(_))),
+                "{:?}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn restart_delays_grow_to_a_cap() {
+        let mut backoff = restart_backoff();
+        let delays: Vec<Duration> = (0..30).map(|_| backoff.next_backoff().unwrap()).collect();
+        // Randomized by up to half the interval either way
+        assert!(delays[0] >= Duration::from_millis(250) && delays[0] <= Duration::from_millis(750));
+        assert!(delays[29] >= Duration::from_secs(15) && delays[29] <= Duration::from_secs(45));
+        assert!(delays.iter().all(|delay| *delay <= Duration::from_secs(45)));
+
+        backoff.reset();
+        assert!(backoff.next_backoff().unwrap() <= Duration::from_millis(750));
+    }
+
+    #[tokio::test]
+    async fn startup_times_out_without_a_port_line() {
+        let manager = shell("echo starting; exec sleep 30", Duration::from_millis(300));
+        let error = launch_error(manager.launch().await);
+        assert!(error.contains("Timed out"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn startup_times_out_without_a_healthy_backend() {
+        // Port 1 refuses connections, so /health never succeeds
+        let manager = shell(
+            "echo BACKEND_PORT:1; exec sleep 30",
+            Duration::from_millis(600),
+        );
+        let error = launch_error(manager.launch().await);
+        assert!(error.contains("/health"), "{}", error);
+
+        let manager = shell("echo BACKEND_PORT:1; exit 2", Duration::from_secs(5));
+        let error = launch_error(manager.launch().await);
+        assert!(error.contains("Exited during startup"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn missing_binaries_fail_without_restarting() {
+        let mut manager = shell("", Duration::from_secs(1));
+        manager.config.program = PathBuf::from("/nonexistent/backend");
+        manager.supervise().await;
+
+        let info = manager.info().await;
+        assert!(matches!(info.status, SidecarStatus::Failed(_)));
+        assert_eq!(info.restart_count, 0);
+    }
+
+    #[tokio::test]
+    async fn crashing_sidecars_are_restarted_until_stopped() {
+        let manager = Arc::new(shell("exit 1", Duration::from_secs(1)));
+        let supervisor = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.supervise().await }
+        });
+        for _ in 0..100 {
+            if manager.info().await.restart_count >= 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let info = manager.info().await;
+        assert!(info.restart_count >= 2);
+        assert!(info.last_error.unwrap().contains("closed stdout"));
+
+        manager.stop().await;
+        supervisor.await.unwrap();
+        assert_eq!(manager.info().await.status, SidecarStatus::Stopped);
+    }
+
+    #[tokio::test]
+    async fn the_port_is_not_sent_to_the_webview() {
+        let manager = shell("", Duration::from_secs(1));
+        manager.update(|info| info.port = Some(41234)).await;
+        let info = serde_json::to_value(manager.info().await).unwrap();
+        assert!(info.get("port").is_none(), "{}", info);
+        assert!(info.get("status").is_some());
+    }
+}
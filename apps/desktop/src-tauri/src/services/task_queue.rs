@@ -0,0 +1,448 @@
+use crate::services::database::Database;
+use crate::services::run_store::{run_from_row, RunStore, RUN_COLUMNS};
+use crate::types::errors::AppError;
+use crate::types::queue::{QueueLimits, QueueSnapshot};
+use crate::types::run::{NewRun, Run, RunStatus};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+
+/// Reason recorded when a run is put back in the queue after an app restart
+const INTERRUPTED_REASON: &str = "interrupted";
+
+/// Queue of background agent runs, persisted in the `runs` table
+///
+/// Submitted runs wait in `queued` until a slot is free under both the global and
+/// the per-agent limit, then are dispatched by moving them to `running`; agent
+/// runtimes pick them up from the `run-status-changed` event. Higher priorities
+/// go first, ties in submission order.
+#[derive(Clone)]
+pub struct TaskQueue {
+    database: Database,
+    run_store: RunStore,
+    limits: Arc<RwLock<QueueLimits>>,
+    paused: Arc<AtomicBool>,
+    /// Serializes dispatch so two passes can't fill the same slot
+    dispatch_lock: Arc<Mutex<()>>,
+}
+
+impl TaskQueue {
+    /// Create a new TaskQueue on top of the run store
+    pub fn new(database: Database, run_store: RunStore, limits: QueueLimits) -> Self {
+        Self {
+            database,
+            run_store,
+            limits: Arc::new(RwLock::new(limits)),
+            paused: Arc::new(AtomicBool::new(false)),
+            dispatch_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Queue a new run and dispatch it if a slot is free
+    pub async fn submit(&self, new_run: &NewRun) -> Result<Run, AppError> {
+        if new_run.task.trim().is_empty() {
+            return Err(AppError::Validation("Task must not be empty".to_string()));
+        }
+        if new_run.agent.trim().is_empty() {
+            return Err(AppError::Validation("Agent must not be empty".to_string()));
+        }
+
+        let run = self.run_store.create_run(new_run).await?;
+        self.run_store
+            .transition(&run.id, RunStatus::Queued, None)
+            .await?;
+        self.dispatch().await?;
+
+        self.run_store.get_run(&run.id).await
+    }
+
+    /// Cancel a queued or executing run, freeing its slot
+    pub async fn cancel(&self, id: &str) -> Result<Run, AppError> {
+        self.run_store
+            .transition(id, RunStatus::Cancelled, Some("cancelled".to_string()))
+            .await?;
+        self.dispatch().await?;
+
+        self.run_store.get_run(id).await
+    }
+
+    /// Stop dispatching queued runs; executing runs are left alone
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resume dispatching queued runs
+    pub async fn resume(&self) -> Result<(), AppError> {
+        self.paused.store(false, Ordering::SeqCst);
+        self.dispatch().await
+    }
+
+    /// Whether dispatching is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Get the current concurrency limits
+    pub fn limits(&self) -> QueueLimits {
+        self.limits
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the concurrency limits and dispatch into any new slots
+    pub async fn set_limits(&self, limits: QueueLimits) -> Result<(), AppError> {
+        validate_limits(&limits)?;
+        *self.limits.write().unwrap_or_else(PoisonError::into_inner) = limits;
+        self.dispatch().await
+    }
+
+    /// Get the active and queued runs
+    pub async fn snapshot(&self) -> Result<QueueSnapshot, AppError> {
+        let active = self
+            .run_store
+            .list_runs(&[RunStatus::Running, RunStatus::AwaitingInput])
+            .await?;
+        let queued = self.list_queued().await?;
+
+        Ok(QueueSnapshot {
+            paused: self.is_paused(),
+            limits: self.limits(),
+            active,
+            queued,
+        })
+    }
+
+    /// Re-queue runs left `running` by a previous session, then keep dispatching
+    /// whenever a run frees its slot
+    pub async fn run(&self) {
+        // Subscribe before recovering so no transition is missed in between
+        let mut transitions = self.run_store.subscribe();
+
+        if let Err(e) = self.requeue_interrupted().await {
+            tracing::warn!("Failed to re-queue interrupted runs: {}", e);
+        }
+        if let Err(e) = self.dispatch().await {
+            tracing::warn!("Failed to dispatch queued runs: {}", e);
+        }
+
+        loop {
+            match transitions.recv().await {
+                Ok(transition) => {
+                    let freed_slot = transition.from.is_some_and(|from| from.is_active())
+                        && !transition.to.is_active();
+                    if !freed_slot {
+                        continue;
+                    }
+                }
+                // Missed events; re-check the queue to be safe
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            }
+
+            if let Err(e) = self.dispatch().await {
+                tracing::warn!("Failed to dispatch queued runs: {}", e);
+            }
+        }
+    }
+
+    /// Put runs that were executing when the app last exited back in the queue
+    async fn requeue_interrupted(&self) -> Result<(), AppError> {
+        for run in self.run_store.list_runs(&[RunStatus::Running]).await? {
+            match self
+                .run_store
+                .transition(
+                    &run.id,
+                    RunStatus::Queued,
+                    Some(INTERRUPTED_REASON.to_string()),
+                )
+                .await
+            {
+                // Someone else moved it already
+                Ok(_) | Err(AppError::InvalidState(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Queued runs in dispatch order
+    async fn list_queued(&self) -> Result<Vec<Run>, AppError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM runs WHERE status = ? \
+             ORDER BY priority DESC, datetime(queued_at), rowid",
+            RUN_COLUMNS
+        ))
+        .bind(RunStatus::Queued.as_str())
+        .fetch_all(self.database.pool())
+        .await?;
+
+        rows.iter().map(run_from_row).collect()
+    }
+
+    /// Move queued runs to `running` while slots are free
+    async fn dispatch(&self) -> Result<(), AppError> {
+        let _guard = self.dispatch_lock.lock().await;
+        if self.is_paused() {
+            return Ok(());
+        }
+
+        let limits = self.limits();
+        let active = self
+            .run_store
+            .list_runs(&[RunStatus::Running, RunStatus::AwaitingInput])
+            .await?;
+        let mut total = active.len();
+        let mut per_agent: HashMap<String, usize> = HashMap::new();
+        for run in &active {
+            *per_agent.entry(run.agent.clone()).or_default() += 1;
+        }
+
+        for run in self.list_queued().await? {
+            if total >= limits.max_concurrent {
+                break;
+            }
+            let agent_count = per_agent.entry(run.agent.clone()).or_default();
+            if *agent_count >= limits.limit_for(&run.agent) {
+                continue;
+            }
+
+            match self
+                .run_store
+                .transition(&run.id, RunStatus::Running, None)
+                .await
+            {
+                Ok(_) => {
+                    *agent_count += 1;
+                    total += 1;
+                }
+                // Cancelled between listing and dispatching
+                Err(AppError::InvalidState(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check concurrency limits before they are applied
+pub fn validate_limits(limits: &QueueLimits) -> Result<(), AppError> {
+    if limits.max_concurrent == 0 {
+        return Err(AppError::Validation(
+            "max_concurrent must be at least 1".to_string(),
+        ));
+    }
+    if limits.max_per_agent == 0 {
+        return Err(AppError::Validation(
+            "max_per_agent must be at least 1".to_string(),
+        ));
+    }
+    // Sorted so the reported agent does not depend on map order
+    let mut agents: Vec<_> = limits.agent_limits.iter().collect();
+    agents.sort();
+    if let Some((agent, _)) = agents.into_iter().find(|(_, limit)| **limit == 0) {
+        return Err(AppError::Validation(format!(
+            "Limit for agent '{}' must be at least 1",
+            agent
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn zero_limits_are_rejected() {
+        assert!(validate_limits(&QueueLimits::default()).is_ok());
+
+        let limits = QueueLimits {
+            max_concurrent: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            validate_limits(&limits),
+            Err(AppError::Validation(_))
+        ));
+
+        let limits = QueueLimits {
+            max_per_agent: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            validate_limits(&limits),
+            Err(AppError::Validation(_))
+        ));
+
+        let limits = QueueLimits {
+            agent_limits: HashMap::from([("a".to_string(), 1), ("b".to_string(), 0)]),
+            ..Default::default()
+        };
+        let Err(AppError::Validation(message)) = validate_limits(&limits) else {
+            panic!("override of 0 was accepted");
+        };
+        assert!(message.contains("'b'"));
+    }
+
+    async fn queue(limits: QueueLimits) -> TaskQueue {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        TaskQueue::new(database, run_store, limits)
+    }
+
+    fn new_run(task: &str, agent: &str, priority: i64) -> NewRun {
+        NewRun {
+            task: task.to_string(),
+            agent: agent.to_string(),
+            priority,
+        }
+    }
+
+    fn tasks(runs: &[Run]) -> Vec<&str> {
+        runs.iter().map(|run| run.task.as_str()).collect()
+    }
+
+    async fn active_tasks(queue: &TaskQueue) -> Vec<String> {
+        let mut active: Vec<_> = queue
+            .snapshot()
+            .await
+            .unwrap()
+            .active
+            .into_iter()
+            .map(|run| run.task)
+            .collect();
+        active.sort();
+        active
+    }
+
+    #[tokio::test]
+    async fn higher_priorities_dispatch_first_then_submission_order() {
+        let queue = queue(QueueLimits {
+            max_concurrent: 1,
+            ..Default::default()
+        })
+        .await;
+        queue.pause();
+        for (task, priority) in [("low 1", 0), ("high 1", 5), ("low 2", 0), ("high 2", 5)] {
+            let run = queue.submit(&new_run(task, task, priority)).await.unwrap();
+            assert_eq!(run.status, RunStatus::Queued);
+        }
+
+        let snapshot = queue.snapshot().await.unwrap();
+        assert!(snapshot.paused);
+        assert!(snapshot.active.is_empty());
+        assert_eq!(
+            tasks(&snapshot.queued),
+            ["high 1", "high 2", "low 1", "low 2"]
+        );
+
+        queue.resume().await.unwrap();
+        let mut dispatched = Vec::new();
+        for _ in 0..4 {
+            let active = queue.snapshot().await.unwrap().active;
+            assert_eq!(active.len(), 1);
+            dispatched.push(active[0].task.clone());
+            queue
+                .run_store
+                .transition(&active[0].id, RunStatus::Completed, None)
+                .await
+                .unwrap();
+            queue.dispatch().await.unwrap();
+        }
+        assert_eq!(dispatched, ["high 1", "high 2", "low 1", "low 2"]);
+        assert!(queue.snapshot().await.unwrap().active.is_empty());
+    }
+
+    #[tokio::test]
+    async fn global_and_per_agent_limits_cap_dispatch() {
+        let queue = queue(QueueLimits {
+            max_concurrent: 3,
+            max_per_agent: 1,
+            agent_limits: HashMap::from([("b".to_string(), 2)]),
+        })
+        .await;
+        let mut ids = HashMap::new();
+        for (task, agent) in [
+            ("a1", "a"),
+            ("a2", "a"),
+            ("b1", "b"),
+            ("b2", "b"),
+            ("b3", "b"),
+            ("c1", "c"),
+        ] {
+            let run = queue.submit(&new_run(task, agent, 0)).await.unwrap();
+            ids.insert(task, run.id);
+        }
+
+        // a is held to one run and b to two, which fills the three global slots
+        assert_eq!(active_tasks(&queue).await, ["a1", "b1", "b2"]);
+        assert_eq!(
+            tasks(&queue.snapshot().await.unwrap().queued),
+            ["a2", "b3", "c1"]
+        );
+
+        // Freeing a slot of b admits the next run of b, skipping a2
+        queue.cancel(&ids["b1"]).await.unwrap();
+        assert_eq!(active_tasks(&queue).await, ["a1", "b2", "b3"]);
+
+        // Raising the global limit admits c1 but not a2
+        queue
+            .set_limits(QueueLimits {
+                max_concurrent: 10,
+                max_per_agent: 1,
+                agent_limits: HashMap::from([("b".to_string(), 2)]),
+            })
+            .await
+            .unwrap();
+        assert_eq!(active_tasks(&queue).await, ["a1", "b2", "b3", "c1"]);
+        assert_eq!(tasks(&queue.snapshot().await.unwrap().queued), ["a2"]);
+
+        // Runs awaiting input keep their slot
+        queue
+            .run_store
+            .transition(&ids["a1"], RunStatus::AwaitingInput, None)
+            .await
+            .unwrap();
+        queue.dispatch().await.unwrap();
+        assert_eq!(tasks(&queue.snapshot().await.unwrap().queued), ["a2"]);
+
+        queue.cancel(&ids["a1"]).await.unwrap();
+        assert_eq!(active_tasks(&queue).await, ["a2", "b2", "b3", "c1"]);
+    }
+
+    #[tokio::test]
+    async fn interrupted_runs_are_requeued() {
+        let queue = queue(QueueLimits::default()).await;
+        let interrupted = queue.submit(&new_run("task", "a", 0)).await.unwrap();
+        assert_eq!(interrupted.status, RunStatus::Running);
+        let waiting = queue.submit(&new_run("task", "b", 0)).await.unwrap();
+        queue
+            .run_store
+            .transition(&waiting.id, RunStatus::AwaitingInput, None)
+            .await
+            .unwrap();
+
+        // As if the app restarted with dispatching paused
+        queue.pause();
+        queue.requeue_interrupted().await.unwrap();
+
+        let run = queue.run_store.get_run(&interrupted.id).await.unwrap();
+        assert_eq!(run.status, RunStatus::Queued);
+        let events = queue.run_store.list_events(&interrupted.id).await.unwrap();
+        let last = events.last().unwrap();
+        assert_eq!(last.from, Some(RunStatus::Running));
+        assert_eq!(last.reason.as_deref(), Some(INTERRUPTED_REASON));
+        // Runs waiting for input are not executing, so they are left alone
+        let run = queue.run_store.get_run(&waiting.id).await.unwrap();
+        assert_eq!(run.status, RunStatus::AwaitingInput);
+
+        queue.resume().await.unwrap();
+        let run = queue.run_store.get_run(&interrupted.id).await.unwrap();
+        assert_eq!(run.status, RunStatus::Running);
+    }
+}
@@ -0,0 +1,603 @@
+use crate::services::cron_schedule::CronSchedule;
+use crate::services::database::Database;
+use crate::services::file_watcher::{WatchPattern, MAX_DEBOUNCE_MS};
+use crate::services::task_queue::TaskQueue;
+use crate::types::errors::AppError;
+use crate::types::run::NewRun;
+use crate::types::trigger::{
+    SignatureScheme, Trigger, TriggerDefinition, TriggerFiring, TriggerSpec,
+};
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+use tokio::sync::watch;
+
+/// Columns selected for [`Trigger`] rows
+const TRIGGER_COLUMNS: &str = "id, name, enabled, task_template, agent, priority, spec, \
+                               last_fired_at, next_fire_at, created_at, updated_at, revision";
+
+/// Store for trigger definitions and their firing log
+///
+/// Trigger runners (the cron scheduler, file watchers, the webhook listener)
+/// subscribe to [`TriggerStore::subscribe`] to reload definitions after changes,
+/// and call [`TriggerStore::fire`] to submit a run.
+#[derive(Clone)]
+pub struct TriggerStore {
+    database: Database,
+    task_queue: TaskQueue,
+    changes: watch::Sender<u64>,
+}
+
+impl TriggerStore {
+    /// Create a new TriggerStore submitting runs to `task_queue`
+    pub fn new(database: Database, task_queue: TaskQueue) -> Self {
+        let (changes, _) = watch::channel(0);
+        Self {
+            database,
+            task_queue,
+            changes,
+        }
+    }
+
+    /// Get notified whenever a trigger is created, updated or deleted
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.changes.subscribe()
+    }
+
+    /// List triggers, optionally of one kind, oldest first
+    #[tracing::instrument(level = "debug", skip(self), err(level = "debug"))]
+    pub async fn list(&self, kind: Option<&str>) -> Result<Vec<Trigger>, AppError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM triggers WHERE ? IS NULL OR kind = ? ORDER BY datetime(created_at), rowid",
+            TRIGGER_COLUMNS
+        ))
+        .bind(kind)
+        .bind(kind)
+        .fetch_all(self.database.pool())
+        .await?;
+
+        rows.iter().map(trigger_from_row).collect()
+    }
+
+    /// Get a trigger by id
+    #[tracing::instrument(level = "debug", skip(self), err(level = "debug"))]
+    pub async fn get(&self, id: &str) -> Result<Trigger, AppError> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM triggers WHERE id = ?",
+            TRIGGER_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(self.database.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Trigger '{}' not found", id)))?;
+
+        trigger_from_row(&row)
+    }
+
+    /// Create a trigger
+    #[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+    pub async fn create(&self, definition: &TriggerDefinition) -> Result<Trigger, AppError> {
+        validate(definition)?;
+        let id = uuid::Uuid::new_v4().to_string();
+
+        let row = sqlx::query(&format!(
+            "INSERT INTO triggers (id, name, kind, enabled, task_template, agent, priority, spec) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?) RETURNING {}",
+            TRIGGER_COLUMNS
+        ))
+        .bind(&id)
+        .bind(&definition.name)
+        .bind(definition.spec.kind())
+        .bind(definition.enabled)
+        .bind(&definition.task_template)
+        .bind(&definition.agent)
+        .bind(definition.priority)
+        .bind(encode_spec(&definition.spec)?)
+        .fetch_one(self.database.pool())
+        .await?;
+
+        self.notify();
+        trigger_from_row(&row)
+    }
+
+    /// Replace a trigger's definition, resetting its schedule
+    #[tracing::instrument(level = "debug", skip(self, definition), err(level = "debug"))]
+    pub async fn update(
+        &self,
+        id: &str,
+        definition: &TriggerDefinition,
+    ) -> Result<Trigger, AppError> {
+        validate(definition)?;
+
+        let row = sqlx::query(&format!(
+            "UPDATE triggers SET name = ?, kind = ?, enabled = ?, task_template = ?, agent = ?, \
+                 priority = ?, spec = ?, next_fire_at = NULL, updated_at = datetime('now'), \
+                 revision = revision + 1 \
+             WHERE id = ? RETURNING {}",
+            TRIGGER_COLUMNS
+        ))
+        .bind(&definition.name)
+        .bind(definition.spec.kind())
+        .bind(definition.enabled)
+        .bind(&definition.task_template)
+        .bind(&definition.agent)
+        .bind(definition.priority)
+        .bind(encode_spec(&definition.spec)?)
+        .bind(id)
+        .fetch_optional(self.database.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Trigger '{}' not found", id)))?;
+
+        self.notify();
+        trigger_from_row(&row)
+    }
+
+    /// Delete a trigger and its firing log
+    #[tracing::instrument(level = "debug", skip(self), err(level = "debug"))]
+    pub async fn delete(&self, id: &str) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM triggers WHERE id = ?")
+            .bind(id)
+            .execute(self.database.pool())
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Trigger '{}' not found", id)));
+        }
+
+        self.notify();
+        Ok(())
+    }
+
+    /// Record scheduling progress for a trigger
+    ///
+    /// Skipped if the trigger was edited since it was read at `revision`, so a
+    /// stale schedule never overwrites the reset done by [`TriggerStore::update`].
+    #[tracing::instrument(level = "debug", skip(self), err(level = "debug"))]
+    pub async fn set_schedule(
+        &self,
+        id: &str,
+        revision: i64,
+        last_fired_at: Option<&str>,
+        next_fire_at: Option<&str>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE triggers SET last_fired_at = COALESCE(?, last_fired_at), next_fire_at = ? \
+             WHERE id = ? AND revision = ?",
+        )
+        .bind(last_fired_at)
+        .bind(next_fire_at)
+        .bind(id)
+        .bind(revision)
+        .execute(self.database.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// Submit a run for a trigger and record the firing
+    ///
+    /// `resolve` supplies values for the placeholders in the task template. A
+    /// failed submission is recorded on the returned firing rather than returned
+    /// as an error.
+    #[tracing::instrument(level = "debug", skip_all, fields(trigger_id = %trigger.id), err(level = "debug"))]
+    pub async fn fire(
+        &self,
+        trigger: &Trigger,
+        resolve: impl Fn(&str) -> Option<String>,
+        scheduled_for: Option<&str>,
+        detail: Option<serde_json::Value>,
+    ) -> Result<TriggerFiring, AppError> {
+        let task = render_template(&trigger.task_template, resolve);
+        let submitted = self
+            .task_queue
+            .submit(&NewRun {
+                task,
+                agent: trigger.agent.clone(),
+                priority: trigger.priority,
+            })
+            .await;
+        let (run_id, error) = match submitted {
+            Ok(run) => (Some(run.id), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        let row = sqlx::query(
+            "INSERT INTO trigger_firings (trigger_id, run_id, scheduled_for, detail, error) \
+             VALUES (?, ?, ?, ?, ?) RETURNING id, fired_at",
+        )
+        .bind(&trigger.id)
+        .bind(&run_id)
+        .bind(scheduled_for)
+        .bind(detail.as_ref().map(|d| d.to_string()))
+        .bind(&error)
+        .fetch_one(self.database.pool())
+        .await?;
+
+        Ok(TriggerFiring {
+            id: row.try_get("id")?,
+            trigger_id: trigger.id.clone(),
+            run_id,
+            scheduled_for: scheduled_for.map(str::to_string),
+            detail,
+            error,
+            fired_at: row.try_get("fired_at")?,
+        })
+    }
+
+    /// List recent firings, newest first, optionally for one trigger
+    #[tracing::instrument(level = "debug", skip(self), err(level = "debug"))]
+    pub async fn list_firings(
+        &self,
+        trigger_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<TriggerFiring>, AppError> {
+        let rows = sqlx::query(
+            "SELECT id, trigger_id, run_id, scheduled_for, detail, error, fired_at \
+             FROM trigger_firings WHERE ? IS NULL OR trigger_id = ? ORDER BY id DESC LIMIT ?",
+        )
+        .bind(trigger_id)
+        .bind(trigger_id)
+        .bind(limit)
+        .fetch_all(self.database.pool())
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let detail: Option<String> = row.try_get("detail")?;
+                Ok(TriggerFiring {
+                    id: row.try_get("id")?,
+                    trigger_id: row.try_get("trigger_id")?,
+                    run_id: row.try_get("run_id")?,
+                    scheduled_for: row.try_get("scheduled_for")?,
+                    detail: detail.and_then(|d| serde_json::from_str(&d).ok()),
+                    error: row.try_get("error")?,
+                    fired_at: row.try_get("fired_at")?,
+                })
+            })
+            .collect()
+    }
+
+    fn notify(&self) {
+        self.changes.send_modify(|version| *version += 1);
+    }
+}
+
+/// Replace `{{ name }}` placeholders using `resolve`; unknown placeholders are left as-is
+pub fn render_template(template: &str, resolve: impl Fn(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + 2 + end + 2];
+        output.push_str(&rest[..start]);
+        match resolve(placeholder[2..placeholder.len() - 2].trim()) {
+            Some(value) => output.push_str(&value),
+            None => output.push_str(placeholder),
+        }
+        rest = &rest[start + placeholder.len()..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn validate(definition: &TriggerDefinition) -> Result<(), AppError> {
+    if definition.name.trim().is_empty() {
+        return Err(AppError::Validation(
+            "Trigger name must not be empty".to_string(),
+        ));
+    }
+    if definition.task_template.trim().is_empty() {
+        return Err(AppError::Validation(
+            "Task template must not be empty".to_string(),
+        ));
+    }
+    if definition.agent.trim().is_empty() {
+        return Err(AppError::Validation("Agent must not be empty".to_string()));
+    }
+
+    match &definition.spec {
+        TriggerSpec::Cron {
+            expression,
+            timezone,
+            ..
+        } => {
+            CronSchedule::parse(expression, timezone)?;
+        }
+        TriggerSpec::FileWatch {
+            paths,
+            recursive,
+            debounce_ms,
+            ..
+        } => {
+            if paths.is_empty() {
+                return Err(AppError::Validation(
+                    "File watch trigger needs at least one path".to_string(),
+                ));
+            }
+            for path in paths {
+                WatchPattern::parse(path, *recursive)?;
+            }
+            if *debounce_ms > MAX_DEBOUNCE_MS {
+                return Err(AppError::Validation(format!(
+                    "debounce_ms must be at most {}",
+                    MAX_DEBOUNCE_MS
+                )));
+            }
+        }
+        TriggerSpec::Webhook {
+            signature,
+            secret_key,
+            rate_limit_per_minute,
+        } => {
+            let has_secret = secret_key.as_deref().is_some_and(|k| !k.trim().is_empty());
+            if *signature != SignatureScheme::None && !has_secret {
+                return Err(AppError::Validation(
+                    "Signed webhooks need a secret_key".to_string(),
+                ));
+            }
+            if *rate_limit_per_minute == 0 {
+                return Err(AppError::Validation(
+                    "rate_limit_per_minute must be at least 1".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn encode_spec(spec: &TriggerSpec) -> Result<String, AppError> {
+    serde_json::to_string(spec)
+        .map_err(|e| AppError::Validation(format!("Invalid trigger spec: {}", e)))
+}
+
+fn trigger_from_row(row: &SqliteRow) -> Result<Trigger, AppError> {
+    let spec: String = row.try_get("spec")?;
+    Ok(Trigger {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        enabled: row.try_get("enabled")?,
+        task_template: row.try_get("task_template")?,
+        agent: row.try_get("agent")?,
+        priority: row.try_get("priority")?,
+        spec: serde_json::from_str(&spec)
+            .map_err(|e| AppError::Database(format!("Invalid stored trigger spec: {}", e)))?,
+        last_fired_at: row.try_get("last_fired_at")?,
+        next_fire_at: row.try_get("next_fire_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        revision: row.try_get("revision")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::run_store::RunStore;
+
+    async fn trigger_store() -> TriggerStore {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        let task_queue = TaskQueue::new(database.clone(), run_store, Default::default());
+        TriggerStore::new(database, task_queue)
+    }
+
+    fn cron(expression: &str) -> TriggerDefinition {
+        TriggerDefinition {
+            name: "nightly".to_string(),
+            enabled: true,
+            task_template: "Run the nightly report".to_string(),
+            agent: "default".to_string(),
+            priority: 0,
+            spec: TriggerSpec::Cron {
+                expression: expression.to_string(),
+                timezone: "UTC".to_string(),
+                misfire_policy: Default::default(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn schedules_computed_before_an_edit_are_discarded() {
+        let store = trigger_store().await;
+        let read = store.create(&cron("0 0 * * *")).await.unwrap();
+
+        // Edited within the same second as the scheduler's read
+        let edited = store.update(&read.id, &cron("0 12 * * *")).await.unwrap();
+        assert_eq!(edited.revision, read.revision + 1);
+        store
+            .set_schedule(&read.id, read.revision, None, Some("2030-01-01 00:00:00"))
+            .await
+            .unwrap();
+        assert_eq!(store.get(&read.id).await.unwrap().next_fire_at, None);
+
+        store
+            .set_schedule(
+                &read.id,
+                edited.revision,
+                Some("2029-12-31 12:00:00"),
+                Some("2030-01-01 12:00:00"),
+            )
+            .await
+            .unwrap();
+        let scheduled = store.get(&read.id).await.unwrap();
+        assert_eq!(
+            scheduled.next_fire_at.as_deref(),
+            Some("2030-01-01 12:00:00")
+        );
+        assert_eq!(
+            scheduled.last_fired_at.as_deref(),
+            Some("2029-12-31 12:00:00")
+        );
+    }
+
+    fn rejected(spec: TriggerSpec) -> bool {
+        matches!(
+            validate(&TriggerDefinition {
+                spec,
+                ..cron("0 0 * * *")
+            }),
+            Err(AppError::Validation(_))
+        )
+    }
+
+    #[test]
+    fn templates_keep_unknown_placeholders() {
+        let resolve = |key: &str| (key == "path").then(|| "/notes/a.md".to_string());
+        assert_eq!(
+            render_template("Summarize {{ path }} for {{missing}}", resolve),
+            "Summarize /notes/a.md for {{missing}}"
+        );
+        assert_eq!(
+            render_template("{{path}}{{path}}", resolve),
+            "/notes/a.md/notes/a.md"
+        );
+        // Unterminated and empty placeholders
+        assert_eq!(render_template("{{ path", resolve), "{{ path");
+        assert_eq!(render_template("{{}} }}", resolve), "{{}} }}");
+        assert_eq!(render_template("", resolve), "");
+    }
+
+    #[tokio::test]
+    async fn triggers_are_created_listed_updated_and_deleted() {
+        let store = trigger_store().await;
+        let mut changes = store.subscribe();
+
+        let nightly = store.create(&cron("0 0 * * *")).await.unwrap();
+        assert!(changes.has_changed().unwrap());
+        changes.mark_unchanged();
+        let watch = store
+            .create(&TriggerDefinition {
+                name: "notes".to_string(),
+                spec: TriggerSpec::FileWatch {
+                    paths: vec!["/home/me/notes/*.md".to_string()],
+                    recursive: false,
+                    events: Vec::new(),
+                    debounce_ms: 500,
+                },
+                ..cron("0 0 * * *")
+            })
+            .await
+            .unwrap();
+        assert_eq!(nightly.revision, 0);
+
+        let all: Vec<String> = store
+            .list(None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(all, [nightly.id.clone(), watch.id.clone()]);
+        let crons = store.list(Some("cron")).await.unwrap();
+        assert_eq!(crons.len(), 1);
+        assert_eq!(crons[0].id, nightly.id);
+
+        store
+            .set_schedule(&nightly.id, 0, None, Some("2030-01-01 00:00:00"))
+            .await
+            .unwrap();
+        changes.mark_unchanged();
+        let updated = store
+            .update(
+                &nightly.id,
+                &TriggerDefinition {
+                    enabled: false,
+                    ..cron("0 6 * * *")
+                },
+            )
+            .await
+            .unwrap();
+        assert!(changes.has_changed().unwrap());
+        assert!(!updated.enabled);
+        assert_eq!(updated.next_fire_at, None);
+        let TriggerSpec::Cron { expression, .. } = &store.get(&nightly.id).await.unwrap().spec
+        else {
+            panic!("spec kind changed");
+        };
+        assert_eq!(expression, "0 6 * * *");
+
+        store.delete(&nightly.id).await.unwrap();
+        assert!(matches!(
+            store.get(&nightly.id).await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            store.delete(&nightly.id).await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            store.update(&nightly.id, &cron("0 0 * * *")).await,
+            Err(AppError::NotFound(_))
+        ));
+        assert_eq!(store.list(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn invalid_definitions_are_not_stored() {
+        let store = trigger_store().await;
+        let blank_name = TriggerDefinition {
+            name: " ".to_string(),
+            ..cron("0 0 * * *")
+        };
+        assert!(matches!(
+            store.create(&blank_name).await,
+            Err(AppError::Validation(_))
+        ));
+        let existing = store.create(&cron("0 0 * * *")).await.unwrap();
+        assert!(matches!(
+            store.update(&existing.id, &cron("0 0 * *")).await,
+            Err(AppError::Validation(_))
+        ));
+        assert_eq!(store.list(None).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn definitions_are_validated() {
+        for definition in [
+            TriggerDefinition {
+                task_template: "".to_string(),
+                ..cron("0 0 * * *")
+            },
+            TriggerDefinition {
+                agent: "\t".to_string(),
+                ..cron("0 0 * * *")
+            },
+            cron("0 0 31 2 * * nope"),
+        ] {
+            assert!(matches!(
+                validate(&definition),
+                Err(AppError::Validation(_))
+            ));
+        }
+        assert!(rejected(TriggerSpec::Cron {
+            expression: "0 0 * * *".to_string(),
+            timezone: "Nowhere/Special".to_string(),
+            misfire_policy: Default::default(),
+        }));
+
+        let file_watch = |paths: &[&str], debounce_ms| TriggerSpec::FileWatch {
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            recursive: true,
+            events: Vec::new(),
+            debounce_ms,
+        };
+        assert!(!rejected(file_watch(&["/notes"], MAX_DEBOUNCE_MS)));
+        assert!(rejected(file_watch(&[], 500)));
+        assert!(rejected(file_watch(&["/notes", "relative"], 500)));
+        assert!(rejected(file_watch(&["/notes/[*.md"], 500)));
+        assert!(rejected(file_watch(&["/notes"], MAX_DEBOUNCE_MS + 1)));
+
+        let webhook =
+            |signature, secret_key: Option<&str>, rate_limit_per_minute| TriggerSpec::Webhook {
+                signature,
+                secret_key: secret_key.map(str::to_string),
+                rate_limit_per_minute,
+            };
+        assert!(!rejected(webhook(SignatureScheme::None, None, 1)));
+        assert!(!rejected(webhook(SignatureScheme::Github, Some("gh"), 60)));
+        assert!(rejected(webhook(SignatureScheme::Github, None, 60)));
+        assert!(rejected(webhook(SignatureScheme::Generic, Some(" "), 60)));
+        assert!(rejected(webhook(SignatureScheme::None, None, 0)));
+    }
+}
@@ -0,0 +1,761 @@
+use crate::services::database::Database;
+use crate::services::keyring_service::KeyringService;
+use crate::services::triggers::TriggerStore;
+use crate::types::errors::AppError;
+use crate::types::trigger::{
+    DeliveryOutcome, SignatureScheme, Trigger, TriggerSpec, WebhookConfig, WebhookDelivery,
+};
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::Row;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// Maximum age of a generic-scheme signature timestamp
+const MAX_TIMESTAMP_SKEW_SECS: i64 = 300;
+/// Window the per-trigger rate limit is counted over
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+/// Deliveries kept in the log per trigger; older rows are pruned
+const MAX_LOGGED_DELIVERIES: i64 = 1000;
+
+const GITHUB_SIGNATURE_HEADER: &str = "x-hub-signature-256";
+const GENERIC_SIGNATURE_HEADER: &str = "x-aios-signature";
+const GENERIC_TIMESTAMP_HEADER: &str = "x-aios-timestamp";
+
+/// Embedded HTTP listener turning webhook deliveries into runs
+///
+/// Each `webhook` trigger is reachable at `POST /hooks/<trigger id>`. Every
+/// delivery to an existing trigger is recorded in `webhook_deliveries`, which
+/// keeps the most recent `MAX_LOGGED_DELIVERIES` per trigger.
+pub struct WebhookServer {
+    config: WebhookConfig,
+    database: Database,
+    trigger_store: TriggerStore,
+    keyring: Arc<KeyringService>,
+    /// Recent delivery times per trigger, for rate limiting
+    deliveries: Mutex<HashMap<String, VecDeque<Instant>>>,
+    local_addr: RwLock<Option<SocketAddr>>,
+}
+
+impl WebhookServer {
+    /// Create a new WebhookServer
+    pub fn new(
+        config: WebhookConfig,
+        database: Database,
+        trigger_store: TriggerStore,
+        keyring: Arc<KeyringService>,
+    ) -> Self {
+        Self {
+            config,
+            database,
+            trigger_store,
+            keyring,
+            deliveries: Mutex::new(HashMap::new()),
+            local_addr: RwLock::new(None),
+        }
+    }
+
+    /// Bind the listener and serve deliveries until the process exits
+    pub async fn serve(self: Arc<Self>) -> Result<(), AppError> {
+        let listener = tokio::net::TcpListener::bind(self.config.address).await?;
+        *self
+            .local_addr
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(listener.local_addr()?);
+
+        let router = Router::new()
+            .route("/hooks/{trigger_id}", post(handle_delivery))
+            .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+            .with_state(self);
+        axum::serve(listener, router).await?;
+        Ok(())
+    }
+
+    /// URL that delivers to a trigger, once the listener is bound
+    pub fn url_for(&self, trigger_id: &str) -> Option<String> {
+        self.local_addr
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .map(|addr| format!("http://{}/hooks/{}", addr, trigger_id))
+    }
+
+    /// List recent deliveries, newest first, optionally for one trigger
+    pub async fn list_deliveries(
+        &self,
+        trigger_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, AppError> {
+        let rows = sqlx::query(
+            "SELECT id, trigger_id, outcome, status_code, body_size, run_id, error, received_at \
+             FROM webhook_deliveries WHERE ? IS NULL OR trigger_id = ? \
+             ORDER BY id DESC LIMIT ?",
+        )
+        .bind(trigger_id)
+        .bind(trigger_id)
+        .bind(limit)
+        .fetch_all(self.database.pool())
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let outcome: String = row.try_get("outcome")?;
+                let status_code: i64 = row.try_get("status_code")?;
+                Ok(WebhookDelivery {
+                    id: row.try_get("id")?,
+                    trigger_id: row.try_get("trigger_id")?,
+                    outcome: outcome.parse()?,
+                    status_code: status_code as u16,
+                    body_size: row.try_get("body_size")?,
+                    run_id: row.try_get("run_id")?,
+                    error: row.try_get("error")?,
+                    received_at: row.try_get("received_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Handle a delivery to an existing trigger, returning what to log
+    async fn deliver(
+        &self,
+        trigger: &Trigger,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> (DeliveryOutcome, StatusCode, Option<String>, Option<String>) {
+        // The keyring is only read for enabled, signed webhooks
+        let secret = match &trigger.spec {
+            TriggerSpec::Webhook {
+                signature,
+                secret_key,
+                ..
+            } if trigger.enabled && *signature != SignatureScheme::None => {
+                match self.secret(secret_key.as_deref()).await {
+                    Ok(secret) => Some(secret),
+                    Err(e) => {
+                        return (
+                            DeliveryOutcome::Failed,
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            None,
+                            Some(e.to_string()),
+                        )
+                    }
+                }
+            }
+            _ => None,
+        };
+        self.deliver_with_secret(trigger, secret.as_deref(), headers, body)
+            .await
+    }
+
+    /// Handle a delivery once the trigger's secret, if any, has been read
+    async fn deliver_with_secret(
+        &self,
+        trigger: &Trigger,
+        secret: Option<&str>,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> (DeliveryOutcome, StatusCode, Option<String>, Option<String>) {
+        let TriggerSpec::Webhook {
+            signature,
+            rate_limit_per_minute,
+            ..
+        } = &trigger.spec
+        else {
+            return (
+                DeliveryOutcome::Disabled,
+                StatusCode::NOT_FOUND,
+                None,
+                Some("Trigger is not a webhook".to_string()),
+            );
+        };
+        if !trigger.enabled {
+            return (DeliveryOutcome::Disabled, StatusCode::NOT_FOUND, None, None);
+        }
+        if *signature != SignatureScheme::None {
+            let Some(secret) = secret else {
+                return (
+                    DeliveryOutcome::Failed,
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    None,
+                    Some("Webhook secret was not read".to_string()),
+                );
+            };
+            if let Err(e) = verify_signature(*signature, secret.as_bytes(), headers, body) {
+                return (
+                    DeliveryOutcome::InvalidSignature,
+                    StatusCode::UNAUTHORIZED,
+                    None,
+                    Some(e.to_string()),
+                );
+            }
+        }
+        // Only authenticated deliveries count, so forged requests cannot use up the limit
+        if !self.allow_delivery(&trigger.id, *rate_limit_per_minute) {
+            return (
+                DeliveryOutcome::RateLimited,
+                StatusCode::TOO_MANY_REQUESTS,
+                None,
+                None,
+            );
+        }
+
+        let text = String::from_utf8_lossy(body);
+        let json: Option<Value> = serde_json::from_slice(body).ok();
+        let fired = self
+            .trigger_store
+            .fire(
+                trigger,
+                |key| match key {
+                    "trigger_name" => Some(trigger.name.clone()),
+                    "body" => Some(text.to_string()),
+                    path if path.starts_with('$') => json
+                        .as_ref()
+                        .and_then(|json| json_path(json, path))
+                        .map(|value| match value {
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        }),
+                    _ => None,
+                },
+                None,
+                Some(serde_json::json!({ "body_size": body.len() })),
+            )
+            .await;
+
+        match fired {
+            Ok(firing) => match firing.error {
+                None => (
+                    DeliveryOutcome::Accepted,
+                    StatusCode::ACCEPTED,
+                    firing.run_id,
+                    None,
+                ),
+                Some(error) => (
+                    DeliveryOutcome::Failed,
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    None,
+                    Some(error),
+                ),
+            },
+            Err(e) => (
+                DeliveryOutcome::Failed,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                None,
+                Some(e.to_string()),
+            ),
+        }
+    }
+
+    /// Count a delivery against the trigger's per-minute limit
+    fn allow_delivery(&self, trigger_id: &str, per_minute: u32) -> bool {
+        let now = Instant::now();
+        let mut deliveries = self
+            .deliveries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let recent = deliveries.entry(trigger_id.to_string()).or_default();
+        while recent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= RATE_LIMIT_WINDOW)
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= per_minute as usize {
+            return false;
+        }
+        recent.push_back(now);
+        true
+    }
+
+    async fn secret(&self, key: Option<&str>) -> Result<String, AppError> {
+        let key = key
+            .ok_or_else(|| AppError::Validation("Webhook has no secret_key".to_string()))?
+            .to_string();
+        self.keyring
+            .run_blocking(move |keyring| keyring.get_secret(&key))
+            .await
+    }
+
+    async fn record(
+        &self,
+        trigger_id: &str,
+        outcome: DeliveryOutcome,
+        status: StatusCode,
+        body_size: usize,
+        run_id: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO webhook_deliveries \
+             (trigger_id, outcome, status_code, body_size, run_id, error) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(trigger_id)
+        .bind(outcome.as_str())
+        .bind(status.as_u16())
+        .bind(body_size as i64)
+        .bind(run_id)
+        .bind(error)
+        .execute(self.database.pool())
+        .await?;
+
+        sqlx::query(
+            "DELETE FROM webhook_deliveries WHERE trigger_id = ? AND id <= \
+             (SELECT id FROM webhook_deliveries WHERE trigger_id = ? \
+              ORDER BY id DESC LIMIT 1 OFFSET ?)",
+        )
+        .bind(trigger_id)
+        .bind(trigger_id)
+        .bind(MAX_LOGGED_DELIVERIES)
+        .execute(self.database.pool())
+        .await?;
+        Ok(())
+    }
+}
+
+async fn handle_delivery(
+    State(server): State<Arc<WebhookServer>>,
+    Path(trigger_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let trigger = match server.trigger_store.get(&trigger_id).await {
+        Ok(trigger) => trigger,
+        Err(AppError::NotFound(_)) => return json_response(StatusCode::NOT_FOUND, None, None),
+        Err(e) => {
+            tracing::warn!("Failed to load webhook trigger '{}': {}", trigger_id, e);
+            return json_response(StatusCode::INTERNAL_SERVER_ERROR, None, None);
+        }
+    };
+
+    let (outcome, status, run_id, error) = server.deliver(&trigger, &headers, &body).await;
+    if let Err(e) = server
+        .record(
+            &trigger.id,
+            outcome,
+            status,
+            body.len(),
+            run_id.as_deref(),
+            error.as_deref(),
+        )
+        .await
+    {
+        tracing::warn!(
+            "Failed to log webhook delivery for '{}': {}",
+            trigger.name,
+            e
+        );
+    }
+
+    // Signature details stay in the delivery log rather than going back to the caller
+    let message = (outcome != DeliveryOutcome::InvalidSignature)
+        .then_some(error)
+        .flatten();
+    json_response(status, run_id, message)
+}
+
+fn json_response(status: StatusCode, run_id: Option<String>, error: Option<String>) -> Response {
+    let body = serde_json::json!({
+        "status": status.canonical_reason().unwrap_or_default(),
+        "run_id": run_id,
+        "error": error,
+    });
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        body.to_string(),
+    )
+        .into_response()
+}
+
+/// Check a delivery's HMAC-SHA256 signature against `secret`
+pub fn verify_signature(
+    scheme: SignatureScheme,
+    secret: &[u8],
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), AppError> {
+    let header_value = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| AppError::Validation(format!("Missing {} header", name)))
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .map_err(|e| AppError::Validation(format!("Invalid webhook secret: {}", e)))?;
+
+    let signature = match scheme {
+        SignatureScheme::None => return Ok(()),
+        SignatureScheme::Github => {
+            mac.update(body);
+            header_value(GITHUB_SIGNATURE_HEADER)?
+                .strip_prefix("sha256=")
+                .ok_or_else(|| AppError::Validation("Signature must start with sha256=".into()))?
+        }
+        SignatureScheme::Generic => {
+            let timestamp = header_value(GENERIC_TIMESTAMP_HEADER)?;
+            let sent_at: i64 = timestamp
+                .parse()
+                .map_err(|_| AppError::Validation("Invalid signature timestamp".to_string()))?;
+            // abs_diff cannot overflow on extreme attacker-chosen timestamps
+            let skew = chrono::Utc::now().timestamp().abs_diff(sent_at);
+            if skew > MAX_TIMESTAMP_SKEW_SECS as u64 {
+                return Err(AppError::Validation(
+                    "Signature timestamp is too old".to_string(),
+                ));
+            }
+            mac.update(timestamp.as_bytes());
+            mac.update(b".");
+            mac.update(body);
+            header_value(GENERIC_SIGNATURE_HEADER)?
+        }
+    };
+
+    let expected = hex::decode(signature)
+        .map_err(|_| AppError::Validation("Signature is not hex".to_string()))?;
+    mac.verify_slice(&expected)
+        .map_err(|_| AppError::Validation("Signature mismatch".to_string()))
+}
+
+/// Resolve a JSONPath subset (`$`, `.key`, `['key']`, `[index]`) against a value
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut rest = path.strip_prefix('$')?;
+    let mut current = value;
+
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            current = current.get(&after_dot[..end])?;
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']')?;
+            let segment = after_bracket[..end].trim();
+            current = match segment
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .or_else(|| segment.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+            {
+                Some(key) => current.get(key)?,
+                None => current.get(segment.parse::<usize>().ok()?)?,
+            };
+            rest = &after_bracket[end + 1..];
+        } else {
+            return None;
+        }
+    }
+
+    Some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::run_store::RunStore;
+    use crate::services::task_queue::TaskQueue;
+    use crate::types::trigger::TriggerDefinition;
+
+    async fn server(dir: &std::path::Path) -> WebhookServer {
+        let database = Database::in_memory().await.unwrap();
+        let run_store = RunStore::new(database.clone());
+        let task_queue = TaskQueue::new(database.clone(), run_store, Default::default());
+        let trigger_store = TriggerStore::new(database.clone(), task_queue);
+        let keyring = KeyringService::new("aios-webhook-test", dir.to_path_buf()).unwrap();
+        WebhookServer::new(
+            WebhookConfig::default(),
+            database,
+            trigger_store,
+            Arc::new(keyring),
+        )
+    }
+
+    async fn webhook(server: &WebhookServer, signature: SignatureScheme) -> Trigger {
+        server
+            .trigger_store
+            .create(&TriggerDefinition {
+                name: "hook".to_string(),
+                enabled: true,
+                task_template: "Handle {{ body }}".to_string(),
+                agent: "default".to_string(),
+                priority: 0,
+                spec: TriggerSpec::Webhook {
+                    signature,
+                    secret_key: Some("aios-webhook-test-missing".to_string()),
+                    rate_limit_per_minute: 1,
+                },
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rate_limit_applies_to_accepted_deliveries() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = server(dir.path()).await;
+        let trigger = webhook(&server, SignatureScheme::None).await;
+
+        let (outcome, status, run_id, _) = server.deliver(&trigger, &HeaderMap::new(), b"{}").await;
+        assert_eq!(outcome, DeliveryOutcome::Accepted);
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert!(run_id.is_some());
+
+        let (outcome, status, _, _) = server.deliver(&trigger, &HeaderMap::new(), b"{}").await;
+        assert_eq!(outcome, DeliveryOutcome::RateLimited);
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn unauthenticated_deliveries_do_not_use_up_the_rate_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = server(dir.path()).await;
+        let trigger = webhook(&server, SignatureScheme::Github).await;
+        let body = br#"{"action":"opened"}"#;
+
+        for _ in 0..3 {
+            let forged = github_headers(b"wrong secret", body);
+            let (outcome, status, run_id, _) = server
+                .deliver_with_secret(&trigger, Some(SECRET), &forged, body)
+                .await;
+            assert_eq!(outcome, DeliveryOutcome::InvalidSignature);
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert!(run_id.is_none());
+        }
+
+        let signed = github_headers(SECRET.as_bytes(), body);
+        let (outcome, _, run_id, _) = server
+            .deliver_with_secret(&trigger, Some(SECRET), &signed, body)
+            .await;
+        assert_eq!(outcome, DeliveryOutcome::Accepted);
+        assert!(run_id.is_some());
+
+        // The limit of one per minute is now used up by the signed delivery
+        let (outcome, _, _, _) = server
+            .deliver_with_secret(&trigger, Some(SECRET), &signed, body)
+            .await;
+        assert_eq!(outcome, DeliveryOutcome::RateLimited);
+    }
+
+    #[tokio::test]
+    async fn delivery_log_is_pruned_per_trigger() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = server(dir.path()).await;
+        let trigger = webhook(&server, SignatureScheme::None).await;
+
+        for _ in 0..MAX_LOGGED_DELIVERIES + 5 {
+            server
+                .record(
+                    &trigger.id,
+                    DeliveryOutcome::RateLimited,
+                    StatusCode::TOO_MANY_REQUESTS,
+                    0,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let deliveries = server
+            .list_deliveries(Some(&trigger.id), MAX_LOGGED_DELIVERIES * 2)
+            .await
+            .unwrap();
+        assert_eq!(deliveries.len() as i64, MAX_LOGGED_DELIVERIES);
+        // The oldest rows are the ones removed
+        assert_eq!(deliveries.last().unwrap().id, 6);
+    }
+
+    const SECRET: &str = "It's a Secret to Everybody";
+
+    fn hmac_hex(secret: &[u8], parts: &[&[u8]]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        for part in parts {
+            mac.update(part);
+        }
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn github_headers(secret: &[u8], body: &[u8]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let signature = format!("sha256={}", hmac_hex(secret, &[body]));
+        headers.insert(GITHUB_SIGNATURE_HEADER, signature.parse().unwrap());
+        headers
+    }
+
+    fn generic_headers(secret: &[u8], timestamp: i64, body: &[u8]) -> HeaderMap {
+        let timestamp = timestamp.to_string();
+        let signature = hmac_hex(secret, &[timestamp.as_bytes(), b".", body]);
+        let mut headers = HeaderMap::new();
+        headers.insert(GENERIC_TIMESTAMP_HEADER, timestamp.parse().unwrap());
+        headers.insert(GENERIC_SIGNATURE_HEADER, signature.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn github_signatures_are_verified() {
+        let body = b"Hello, World!";
+        let headers = github_headers(SECRET.as_bytes(), body);
+        verify_signature(SignatureScheme::Github, SECRET.as_bytes(), &headers, body).unwrap();
+
+        // Known-good signature from GitHub's webhook documentation
+        let mut documented = HeaderMap::new();
+        documented.insert(
+            GITHUB_SIGNATURE_HEADER,
+            "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+                .parse()
+                .unwrap(),
+        );
+        verify_signature(
+            SignatureScheme::Github,
+            SECRET.as_bytes(),
+            &documented,
+            body,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn forged_github_signatures_are_rejected() {
+        let body = b"Hello, World!";
+        let forged = github_headers(b"another secret", body);
+        assert!(
+            verify_signature(SignatureScheme::Github, SECRET.as_bytes(), &forged, body).is_err()
+        );
+
+        // A valid signature does not carry over to a different body
+        let signed = github_headers(SECRET.as_bytes(), body);
+        assert!(verify_signature(
+            SignatureScheme::Github,
+            SECRET.as_bytes(),
+            &signed,
+            b"Goodbye, World!"
+        )
+        .is_err());
+
+        let mut unprefixed = HeaderMap::new();
+        unprefixed.insert(
+            GITHUB_SIGNATURE_HEADER,
+            hmac_hex(SECRET.as_bytes(), &[body]).parse().unwrap(),
+        );
+        assert!(verify_signature(
+            SignatureScheme::Github,
+            SECRET.as_bytes(),
+            &unprefixed,
+            body
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn generic_signatures_cover_the_timestamp() {
+        let body = b"{}";
+        let now = chrono::Utc::now().timestamp();
+        let headers = generic_headers(SECRET.as_bytes(), now, body);
+        verify_signature(SignatureScheme::Generic, SECRET.as_bytes(), &headers, body).unwrap();
+
+        // Replaying the signature with a different timestamp fails
+        let mut replayed = headers.clone();
+        replayed.insert(
+            GENERIC_TIMESTAMP_HEADER,
+            (now - 1).to_string().parse().unwrap(),
+        );
+        assert!(
+            verify_signature(SignatureScheme::Generic, SECRET.as_bytes(), &replayed, body).is_err()
+        );
+
+        let forged = generic_headers(b"another secret", now, body);
+        assert!(
+            verify_signature(SignatureScheme::Generic, SECRET.as_bytes(), &forged, body).is_err()
+        );
+    }
+
+    #[test]
+    fn stale_generic_timestamps_are_rejected() {
+        let body = b"{}";
+        let now = chrono::Utc::now().timestamp();
+        for timestamp in [
+            now - MAX_TIMESTAMP_SKEW_SECS - 60,
+            now + MAX_TIMESTAMP_SKEW_SECS + 60,
+            i64::MIN,
+            i64::MAX,
+        ] {
+            // Correctly signed, but outside the allowed skew
+            let headers = generic_headers(SECRET.as_bytes(), timestamp, body);
+            let err = verify_signature(SignatureScheme::Generic, SECRET.as_bytes(), &headers, body)
+                .unwrap_err();
+            assert!(err.to_string().contains("too old"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn missing_signature_headers_are_rejected() {
+        let body = b"{}";
+        for scheme in [SignatureScheme::Github, SignatureScheme::Generic] {
+            let err =
+                verify_signature(scheme, SECRET.as_bytes(), &HeaderMap::new(), body).unwrap_err();
+            assert!(err.to_string().contains("Missing"), "{}", err);
+        }
+
+        // A timestamp alone is not enough for the generic scheme
+        let mut headers = generic_headers(SECRET.as_bytes(), chrono::Utc::now().timestamp(), body);
+        headers.remove(GENERIC_SIGNATURE_HEADER);
+        assert!(
+            verify_signature(SignatureScheme::Generic, SECRET.as_bytes(), &headers, body).is_err()
+        );
+
+        verify_signature(
+            SignatureScheme::None,
+            SECRET.as_bytes(),
+            &HeaderMap::new(),
+            body,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn json_path_resolves_keys_and_indexes() {
+        let value = serde_json::json!({
+            "action": "opened",
+            "pull_request": { "title": "Fix", "labels": [{ "name": "bug" }, { "name": "ui" }] },
+            "dotted.key": 1,
+        });
+
+        assert_eq!(json_path(&value, "$"), Some(&value));
+        assert_eq!(json_path(&value, "$.action"), Some(&Value::from("opened")));
+        assert_eq!(
+            json_path(&value, "$.pull_request.title"),
+            Some(&Value::from("Fix"))
+        );
+        assert_eq!(
+            json_path(&value, "$.pull_request.labels[1].name"),
+            Some(&Value::from("ui"))
+        );
+        assert_eq!(
+            json_path(&value, "$['pull_request'][\"labels\"][0]['name']"),
+            Some(&Value::from("bug"))
+        );
+        assert_eq!(json_path(&value, "$['dotted.key']"), Some(&Value::from(1)));
+    }
+
+    #[test]
+    fn json_path_misses_are_none() {
+        let value = serde_json::json!({ "items": [1, 2], "name": "x" });
+
+        assert_eq!(json_path(&value, "$.missing"), None);
+        assert_eq!(json_path(&value, "$.items[2]"), None);
+        assert_eq!(json_path(&value, "$.items[first]"), None);
+        assert_eq!(json_path(&value, "$.name.inner"), None);
+        assert_eq!(json_path(&value, "$.items[0"), None);
+        // Paths must start at the root
+        assert_eq!(json_path(&value, "name"), None);
+        assert_eq!(json_path(&value, "$name"), None);
+    }
+}
@@ -1,19 +1,69 @@
+use crate::services::attachment_store::AttachmentStore;
+use crate::services::database::Database;
+use crate::services::global_shortcut::GlobalShortcutService;
 use crate::services::keyring_service::KeyringService;
+use crate::services::logging::Logging;
 use crate::services::mcp_manager::McpManager;
+use crate::services::notifications::NotificationService;
+use crate::services::run_store::RunStore;
+use crate::services::settings::SettingsStore;
+use crate::services::sidecar_manager::SidecarManager;
+use crate::services::task_queue::TaskQueue;
+use crate::services::triggers::TriggerStore;
+use crate::services::webhook_server::WebhookServer;
 use std::sync::Arc;
 
 /// Application state managed by Tauri
-/// Wrapped in Mutex when added to Tauri's managed state for thread safety
+///
+/// Every service synchronizes internally, so the state is managed without an
+/// outer lock and unrelated commands never wait on each other.
 pub struct AppState {
-    pub keyring_service: KeyringService,
+    pub keyring_service: Arc<KeyringService>,
     pub mcp_manager: Arc<McpManager>,
+    pub database: Database,
+    pub attachment_store: AttachmentStore,
+    pub run_store: RunStore,
+    pub sidecar_manager: Arc<SidecarManager>,
+    pub task_queue: TaskQueue,
+    pub trigger_store: TriggerStore,
+    pub webhook_server: Arc<WebhookServer>,
+    pub notification_service: NotificationService,
+    pub global_shortcut: Arc<GlobalShortcutService>,
+    pub settings_store: SettingsStore,
+    pub logging: Arc<Logging>,
 }
 
 impl AppState {
-    pub fn new(keyring_service: KeyringService, mcp_manager: McpManager) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        keyring_service: Arc<KeyringService>,
+        mcp_manager: McpManager,
+        database: Database,
+        attachment_store: AttachmentStore,
+        run_store: RunStore,
+        sidecar_manager: SidecarManager,
+        task_queue: TaskQueue,
+        trigger_store: TriggerStore,
+        webhook_server: WebhookServer,
+        notification_service: NotificationService,
+        global_shortcut: GlobalShortcutService,
+        settings_store: SettingsStore,
+        logging: Arc<Logging>,
+    ) -> Self {
         Self {
             keyring_service,
             mcp_manager: Arc::new(mcp_manager),
+            database,
+            attachment_store,
+            run_store,
+            sidecar_manager: Arc::new(sidecar_manager),
+            task_queue,
+            trigger_store,
+            webhook_server: Arc::new(webhook_server),
+            notification_service,
+            global_shortcut: Arc::new(global_shortcut),
+            settings_store,
+            logging,
         }
     }
 }
@@ -0,0 +1,297 @@
+use crate::state::AppState;
+use crate::types::config::McpServerStatus;
+use crate::types::errors::AppError;
+use crate::types::run::{Run, RunStatus};
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager};
+
+/// Identifier of the system tray icon
+pub const TRAY_ID: &str = "main";
+
+/// Menu item id prefix for cancelling an active run
+const CANCEL_RUN_PREFIX: &str = "cancel-run:";
+
+/// Menu item id prefix for toggling an MCP server
+const MCP_TOGGLE_PREFIX: &str = "mcp:";
+
+/// Longest task text shown in a menu label
+const MAX_LABEL_CHARS: usize = 40;
+
+/// Everything the tray menu is rendered from
+struct TraySnapshot {
+    active_runs: Vec<Run>,
+    mcp_servers: Vec<(String, McpServerStatus)>,
+    queue_paused: bool,
+}
+
+/// Create the tray icon and keep its menu in sync with run and MCP server status
+pub fn create(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(
+        app,
+        &TraySnapshot {
+            active_runs: Vec::new(),
+            mcp_servers: Vec::new(),
+            queue_paused: false,
+        },
+    )?;
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(app.default_window_icon().cloned().expect("no app icon"))
+        .icon_as_template(true)
+        .tooltip("AIOS")
+        .menu(&menu)
+        .on_menu_event(handle_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            // Toggle window visibility on tray icon click
+            if let TrayIconEvent::Click { .. } = event {
+                if let Some(app) = tray.app_handle().get_webview_window("main") {
+                    if app.is_visible().unwrap_or(false) {
+                        let _ = app.hide();
+                    } else {
+                        let _ = app.show();
+                        let _ = app.set_focus();
+                    }
+                }
+            }
+        })
+        .build(app)?;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move { watch_status(app).await });
+
+    Ok(())
+}
+
+/// Rebuild the tray menu whenever a run or MCP server changes status
+async fn watch_status(app: AppHandle) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let (mut transitions, mut mcp_changes) = {
+        let state = app.state::<AppState>();
+        (state.run_store.subscribe(), state.mcp_manager.subscribe())
+    };
+
+    loop {
+        if let Err(e) = refresh(&app).await {
+            tracing::warn!("Failed to refresh tray menu: {}", e);
+        }
+
+        tokio::select! {
+            received = transitions.recv() => match received {
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+            changed = mcp_changes.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Rebuild the tray menu, tooltip and badge from current state
+pub async fn refresh(app: &AppHandle) -> Result<(), AppError> {
+    let (task_queue, mcp_manager) = {
+        let state = app.state::<AppState>();
+        (state.task_queue.clone(), state.mcp_manager.clone())
+    };
+
+    let queue = task_queue.snapshot().await?;
+    let mut mcp_servers: Vec<_> = mcp_manager.list_servers().await.into_iter().collect();
+    mcp_servers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let snapshot = TraySnapshot {
+        active_runs: queue.active,
+        mcp_servers,
+        queue_paused: queue.paused,
+    };
+
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let menu = build_menu(app, &snapshot).map_err(tray_error)?;
+    tray.set_menu(Some(menu)).map_err(tray_error)?;
+
+    let count = snapshot.active_runs.len();
+    let tooltip = match count {
+        0 => "AIOS — idle".to_string(),
+        1 => "AIOS — 1 active run".to_string(),
+        n => format!("AIOS — {} active runs", n),
+    };
+    tray.set_tooltip(Some(tooltip)).map_err(tray_error)?;
+
+    // The title renders next to the icon on macOS and acts as a count badge
+    let badge = (count > 0).then(|| count.to_string());
+    tray.set_title(badge).map_err(tray_error)?;
+
+    Ok(())
+}
+
+/// Build the tray menu for a snapshot
+fn build_menu(app: &AppHandle, snapshot: &TraySnapshot) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+    menu.append(&MenuItem::with_id(app, "show", "Show", true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    // Running agents, each with a cancel action
+    let runs = Submenu::with_id(
+        app,
+        "runs",
+        format!("Running Agents ({})", snapshot.active_runs.len()),
+        true,
+    )?;
+    if snapshot.active_runs.is_empty() {
+        runs.append(&MenuItem::new(app, "No active runs", false, None::<&str>)?)?;
+    }
+    for run in &snapshot.active_runs {
+        let entry = Submenu::new(app, run_label(run), true)?;
+        entry.append(&MenuItem::with_id(
+            app,
+            format!("{}{}", CANCEL_RUN_PREFIX, run.id),
+            "Cancel",
+            true,
+            None::<&str>,
+        )?)?;
+        runs.append(&entry)?;
+    }
+    menu.append(&runs)?;
+
+    // MCP servers, checked while running
+    let servers = Submenu::with_id(app, "mcp", "MCP Servers", true)?;
+    if snapshot.mcp_servers.is_empty() {
+        servers.append(&MenuItem::new(
+            app,
+            "No servers configured",
+            false,
+            None::<&str>,
+        )?)?;
+    }
+    for (name, status) in &snapshot.mcp_servers {
+        let (label, enabled) = match status {
+            McpServerStatus::Starting => (format!("{} (starting)", name), false),
+            McpServerStatus::Failed(_) => (format!("{} (failed)", name), true),
+            McpServerStatus::Unhealthy(_) => (format!("{} (unhealthy)", name), true),
+            _ => (name.clone(), true),
+        };
+        servers.append(&CheckMenuItem::with_id(
+            app,
+            format!("{}{}", MCP_TOGGLE_PREFIX, name),
+            label,
+            enabled,
+            matches!(
+                status,
+                McpServerStatus::Running | McpServerStatus::Unhealthy(_)
+            ),
+            None::<&str>,
+        )?)?;
+    }
+    menu.append(&servers)?;
+
+    let pause_label = if snapshot.queue_paused {
+        "Resume All Agents"
+    } else {
+        "Pause All Agents"
+    };
+    menu.append(&MenuItem::with_id(
+        app,
+        "toggle-pause",
+        pause_label,
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)?;
+
+    Ok(menu)
+}
+
+/// Menu label for an active run
+fn run_label(run: &Run) -> String {
+    let mut task: String = run.task.chars().take(MAX_LABEL_CHARS).collect();
+    if run.task.chars().count() > MAX_LABEL_CHARS {
+        task.push('…');
+    }
+    match run.status {
+        RunStatus::AwaitingInput => format!("{}: {} (awaiting input)", run.agent, task),
+        _ => format!("{}: {}", run.agent, task),
+    }
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    match id {
+        "show" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        "quit" => {
+            app.exit(0);
+        }
+        "toggle-pause" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let task_queue = app.state::<AppState>().task_queue.clone();
+                let result = if task_queue.is_paused() {
+                    task_queue.resume().await
+                } else {
+                    task_queue.pause();
+                    Ok(())
+                };
+                if let Err(e) = result {
+                    tracing::warn!("Failed to resume queue: {}", e);
+                }
+                // Pausing does not change any run status, so refresh directly
+                if let Err(e) = refresh(&app).await {
+                    tracing::warn!("Failed to refresh tray menu: {}", e);
+                }
+            });
+        }
+        _ => {
+            if let Some(run_id) = id.strip_prefix(CANCEL_RUN_PREFIX) {
+                let app = app.clone();
+                let run_id = run_id.to_string();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<AppState>();
+                    if let Err(e) = crate::commands::queue::cancel_task(run_id, state).await {
+                        tracing::warn!("Failed to cancel run from tray: {}", e);
+                    }
+                });
+            } else if let Some(name) = id.strip_prefix(MCP_TOGGLE_PREFIX) {
+                let app = app.clone();
+                let name = name.to_string();
+                tauri::async_runtime::spawn(async move { toggle_mcp_server(app, name).await });
+            }
+        }
+    }
+}
+
+/// Start a stopped or failed MCP server, or stop a running one
+async fn toggle_mcp_server(app: AppHandle, name: String) {
+    let mcp_manager = app.state::<AppState>().mcp_manager.clone();
+    let running = matches!(
+        mcp_manager.list_servers().await.get(&name),
+        Some(McpServerStatus::Running | McpServerStatus::Unhealthy(_))
+    );
+
+    let state = app.state::<AppState>();
+    let result = if running {
+        crate::commands::mcp::stop_mcp_server(name.clone(), state).await
+    } else {
+        crate::commands::mcp::start_mcp_server(name.clone(), state).await
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to toggle MCP server '{}' from tray: {}", name, e);
+        // Check items toggle themselves when clicked; restore the real state
+        if let Err(e) = refresh(&app).await {
+            tracing::warn!("Failed to refresh tray menu: {}", e);
+        }
+    }
+}
+
+fn tray_error(e: tauri::Error) -> AppError {
+    AppError::Process(format!("Failed to update tray: {}", e))
+}
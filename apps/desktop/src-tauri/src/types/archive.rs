@@ -0,0 +1,55 @@
+use crate::types::message::StoredMessage;
+use crate::types::run::{Run, RunTransition};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Current version of the run archive format
+pub const RUN_ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+/// On-disk format of a run archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    /// Single JSON document with attachments inlined as base64
+    Json,
+    /// Zip file with `run.json` and attachments under `attachments/<hash>`
+    Zip,
+}
+
+/// A portable snapshot of a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunArchive {
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub run: Run,
+    pub events: Vec<RunTransition>,
+    pub messages: Vec<StoredMessage>,
+    /// MCP tool calls made during the run, paired with their results.
+    /// Derived from `messages`; informational only and ignored on import.
+    #[serde(default)]
+    pub tool_calls: Vec<ArchivedToolCall>,
+    pub attachments: Vec<ArchivedAttachment>,
+}
+
+/// An MCP tool call and its result, if one was recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedToolCall {
+    pub message_id: i64,
+    pub tool_use_id: String,
+    pub server: String,
+    pub tool: String,
+    pub arguments: Value,
+    pub result: Option<String>,
+    pub is_error: Option<bool>,
+}
+
+/// An attachment referenced by the archived messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedAttachment {
+    pub hash: String,
+    pub mime_type: String,
+    pub size: i64,
+    /// Inline contents for JSON archives; zip archives store the file separately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_base64: Option<String>,
+}
@@ -1,30 +1,214 @@
+use crate::types::metrics::{ServerHealth, ServerMetrics};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Configuration for a single MCP server
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct McpServerConfig {
     pub command: String,
     pub args: Vec<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Working directory, the app's own when unset
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub startup: StartupConfig,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub health: HealthCheckConfig,
+    /// Start when the app launches
+    #[serde(default)]
+    pub autostart: bool,
+    /// Wait this long after the server's dependencies are running before
+    /// starting it at launch
+    #[serde(default)]
+    pub start_delay_ms: u64,
+    /// Servers that must be running before this one starts; they are started
+    /// first
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Start on the first tool call instead of at launch, and stop again
+    /// once idle for `idle_timeout_ms`
+    #[serde(default)]
+    pub on_demand: bool,
+    #[serde(default = "default_idle_timeout_ms")]
+    pub idle_timeout_ms: u64,
+    /// Time allowed for a tool call to be answered
+    #[serde(default = "default_tool_timeout_ms")]
+    pub tool_timeout_ms: u64,
 }
 
-/// MCP configuration matching Claude Desktop format
+fn default_idle_timeout_ms() -> u64 {
+    300_000
+}
+
+fn default_tool_timeout_ms() -> u64 {
+    60_000
+}
+
+impl McpServerConfig {
+    /// How the app talks MCP to the server
+    ///
+    /// Always stdio: readiness checks such as `Tcp` only decide when a server
+    /// is started, not how the app connects to it.
+    pub fn transport(&self) -> McpTransport {
+        McpTransport::Stdio
+    }
+}
+
+/// How the app talks MCP to a server
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum McpTransport {
+    Stdio,
+}
+
+/// How a server is judged started, and how failed starts are retried
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct McpConfig {
-    #[serde(rename = "mcpServers")]
-    pub mcp_servers: HashMap<String, McpServerConfig>,
+#[serde(default, rename_all = "camelCase")]
+pub struct StartupConfig {
+    pub readiness: Readiness,
+    /// Time allowed for the server to become ready on each attempt
+    pub ready_timeout_ms: u64,
+    /// A server that exits within this long after becoming ready has failed
+    /// to start
+    pub grace_period_ms: u64,
+    /// Attempts before the server is reported as failed
+    pub max_attempts: u32,
+    /// Delay before the first retry; later delays grow exponentially
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            readiness: Readiness::default(),
+            ready_timeout_ms: 30_000,
+            grace_period_ms: 2_000,
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+        }
+    }
 }
 
-impl Default for McpConfig {
+/// Periodic MCP `ping` checks of a running server
+///
+/// Only servers with a stdio connection, i.e. handshake readiness, are
+/// checked; for others the checks are reported as inactive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HealthCheckConfig {
+    pub enabled: bool,
+    /// Time between pings, honoured to the nearest second
+    pub interval_ms: u64,
+    /// Time allowed for each ping to be answered
+    pub timeout_ms: u64,
+    /// Consecutive failed pings before the server is unhealthy
+    pub failure_threshold: u32,
+    pub restart: RestartPolicy,
+}
+
+impl Default for HealthCheckConfig {
     fn default() -> Self {
         Self {
-            mcp_servers: HashMap::new(),
+            enabled: true,
+            interval_ms: 30_000,
+            timeout_ms: 5_000,
+            failure_threshold: 3,
+            restart: RestartPolicy::default(),
         }
     }
 }
 
+/// What to do with a server that became unhealthy or exited unexpectedly
+///
+/// Applies to exits whether or not health checks are enabled.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestartPolicy {
+    /// Leave it running and report it as unhealthy, or as failed once exited
+    Never,
+    /// Kill it if it is still running and start it again, waiting longer
+    /// after each restart and giving up after several in a short time
+    #[default]
+    OnFailure,
+}
+
+/// Restrictions applied to a server process
+///
+/// Resource limits and bubblewrap isolation are only available on Linux;
+/// elsewhere they are reported as unavailable.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SandboxConfig {
+    /// Virtual address space limit in MiB (`RLIMIT_AS`)
+    ///
+    /// This caps mapped memory, not resident memory: runtimes that reserve
+    /// large address ranges up front, such as Node, Go or the JVM, need far
+    /// more than they actually use and may fail to start under a tight limit.
+    pub max_address_space_mb: Option<u64>,
+    /// CPU time limit in seconds, after which the server is killed
+    pub max_cpu_seconds: Option<u64>,
+    /// Limit on open file descriptors
+    pub max_open_files: Option<u64>,
+    /// Start from an empty environment with only `PATH`, `HOME`, locale and
+    /// temp directory variables inherited, plus `env` and keyring secrets
+    pub clean_env: bool,
+    /// Run inside new namespaces with a read-only root via `bwrap`, when it
+    /// is installed
+    pub bubblewrap: bool,
+    /// Refuse to start rather than run unsandboxed when `bwrap` is missing
+    pub require_bubblewrap: bool,
+    /// Cut off network access inside the bubblewrap sandbox
+    pub isolate_network: bool,
+}
+
+/// Protections actually applied to a running server
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SandboxReport {
+    /// Applied resource limits, e.g. `address_space=512MiB`
+    pub limits: Vec<String>,
+    pub clean_env: bool,
+    pub cwd: Option<PathBuf>,
+    pub bubblewrap: bool,
+    pub network_isolated: bool,
+    /// Requested protections that could not be applied
+    pub unavailable: Vec<String>,
+}
+
+/// How to tell that a server is ready
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Readiness {
+    /// Complete the MCP `initialize` handshake over stdio
+    #[default]
+    Handshake,
+    /// Wait until a TCP connection to `address` succeeds, for servers that
+    /// open a port once started; MCP is still spoken over stdio
+    Tcp { address: String },
+    /// Run a command until it exits successfully
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Ready as soon as the process has outlived the grace period
+    None,
+}
+
+/// MCP configuration matching Claude Desktop format
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct McpConfig {
+    #[serde(rename = "mcpServers")]
+    pub mcp_servers: HashMap<String, McpServerConfig>,
+}
+
 /// Status of an MCP server process
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -32,5 +216,55 @@ pub enum McpServerStatus {
     Stopped,
     Starting,
     Running,
+    /// Running, but failing its health checks
+    Unhealthy(String),
     Failed(String),
 }
+
+/// A configured server as listed by `list_mcp_servers`
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServerEntry {
+    pub name: String,
+    pub status: McpServerStatus,
+    pub transport: McpTransport,
+    /// Command and arguments with secrets redacted
+    pub command_line: String,
+    pub pid: Option<u32>,
+    /// RFC 3339 time the running process was spawned
+    pub started_at: Option<String>,
+    /// Times the server was spawned again after its first launch, retries
+    /// included
+    pub restart_count: u32,
+    /// Exit code of the last process that exited, `None` if it was killed
+    /// by a signal or none has exited
+    pub last_exit_code: Option<i32>,
+    pub last_error: Option<String>,
+    /// What the server reported in its `initialize` response
+    pub server_info: Option<McpServerInfo>,
+    pub protocol_version: Option<String>,
+    pub capabilities: Option<serde_json::Value>,
+    /// Tools the server offered when it started
+    pub tool_count: Option<usize>,
+    /// Resource usage while the server is running
+    pub metrics: Option<ServerMetrics>,
+    /// Health check results while the server is running
+    pub health: Option<ServerHealth>,
+}
+
+/// Name and version a server reports in its `initialize` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerInfo {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+}
+
+/// Result of the MCP `initialize` handshake
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResult {
+    pub protocol_version: String,
+    #[serde(default)]
+    pub capabilities: serde_json::Value,
+    pub server_info: McpServerInfo,
+}
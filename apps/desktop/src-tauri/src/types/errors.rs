@@ -1,8 +1,19 @@
+use serde::ser::SerializeStruct;
+
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
     #[error("Keyring error: {0}")]
     Keyring(String),
 
+    #[error("Secret not found in keyring: {0}")]
+    KeyringNoEntry(String),
+
+    #[error("Keyring unavailable: {0}")]
+    KeyringPlatformFailure(String),
+
+    #[error("Ambiguous keyring entry: {message}")]
+    KeyringAmbiguous { message: String, candidates: usize },
+
     #[error("Process error: {0}")]
     Process(String),
 
@@ -14,27 +25,243 @@ pub enum AppError {
 
     #[error("Database error: {0}")]
     Database(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Invalid state: {0}")]
+    InvalidState(String),
+
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Permission denied: {0}")]
+    Permission(String),
+}
+
+impl AppError {
+    /// Stable machine-readable code the frontend can match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Keyring(_) => "keyring_error",
+            AppError::KeyringNoEntry(_) => "keyring_no_entry",
+            AppError::KeyringPlatformFailure(_) => "keyring_platform_failure",
+            AppError::KeyringAmbiguous { .. } => "keyring_ambiguous",
+            AppError::Process(_) => "process_error",
+            AppError::NotFound(_) => "not_found",
+            AppError::Io(_) => "io_error",
+            AppError::Database(_) => "database_error",
+            AppError::Config(_) => "config_error",
+            AppError::Validation(_) => "validation_error",
+            AppError::InvalidState(_) => "invalid_state",
+            AppError::Protocol(_) => "protocol_error",
+            AppError::Timeout(_) => "timeout",
+            AppError::Permission(_) => "permission_denied",
+        }
+    }
+
+    /// Whether the same request may succeed if tried again unchanged
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            AppError::KeyringPlatformFailure(_) | AppError::Timeout(_)
+        )
+    }
+
+    /// Structured context beyond the message
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            AppError::KeyringAmbiguous { candidates, .. } => {
+                Some(serde_json::json!({ "candidates": candidates }))
+            }
+            _ => None,
+        }
+    }
 }
 
-// Manual Serialize implementation to convert AppError to string
+// Serialized as `{ code, message, details, retryable }` for the frontend
 impl serde::Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let mut error = serializer.serialize_struct("AppError", 4)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("details", &self.details())?;
+        error.serialize_field("retryable", &self.is_retryable())?;
+        error.end()
     }
 }
 
 // From implementations for common error types
 impl From<keyring::Error> for AppError {
     fn from(err: keyring::Error) -> Self {
-        AppError::Keyring(err.to_string())
+        match err {
+            keyring::Error::NoEntry => AppError::KeyringNoEntry(err.to_string()),
+            keyring::Error::PlatformFailure(_) => AppError::KeyringPlatformFailure(err.to_string()),
+            keyring::Error::NoStorageAccess(_) => AppError::Permission(err.to_string()),
+            keyring::Error::Ambiguous(ref credentials) => AppError::KeyringAmbiguous {
+                candidates: credentials.len(),
+                message: err.to_string(),
+            },
+            keyring::Error::BadEncoding(_)
+            | keyring::Error::TooLong(_, _)
+            | keyring::Error::Invalid(_, _) => AppError::Validation(err.to_string()),
+            _ => AppError::Keyring(err.to_string()),
+        }
     }
 }
 
 impl From<std::io::Error> for AppError {
     fn from(err: std::io::Error) -> Self {
-        AppError::Io(err.to_string())
+        match err.kind() {
+            std::io::ErrorKind::PermissionDenied => AppError::Permission(err.to_string()),
+            std::io::ErrorKind::TimedOut => AppError::Timeout(err.to_string()),
+            _ => AppError::Io(err.to_string()),
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        AppError::Database(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn wire(error: AppError) -> serde_json::Value {
+        serde_json::to_value(&error).unwrap()
+    }
+
+    #[test]
+    fn errors_serialize_to_the_wire_shape() {
+        assert_eq!(
+            wire(AppError::NotFound("Run 'x' not found".to_string())),
+            json!({
+                "code": "not_found",
+                "message": "Not found: Run 'x' not found",
+                "details": null,
+                "retryable": false,
+            })
+        );
+        assert_eq!(
+            wire(AppError::Timeout("Tool call took too long".to_string())),
+            json!({
+                "code": "timeout",
+                "message": "Timed out: Tool call took too long",
+                "details": null,
+                "retryable": true,
+            })
+        );
+        assert_eq!(
+            wire(AppError::KeyringAmbiguous {
+                message: "2 matches".to_string(),
+                candidates: 2,
+            }),
+            json!({
+                "code": "keyring_ambiguous",
+                "message": "Ambiguous keyring entry: 2 matches",
+                "details": { "candidates": 2 },
+                "retryable": false,
+            })
+        );
+    }
+
+    #[test]
+    fn codes_and_retryability_are_stable() {
+        let message = || "message".to_string();
+        let cases = [
+            (AppError::Keyring(message()), "keyring_error", false),
+            (
+                AppError::KeyringNoEntry(message()),
+                "keyring_no_entry",
+                false,
+            ),
+            (
+                AppError::KeyringPlatformFailure(message()),
+                "keyring_platform_failure",
+                true,
+            ),
+            (AppError::Process(message()), "process_error", false),
+            (AppError::NotFound(message()), "not_found", false),
+            (AppError::Io(message()), "io_error", false),
+            (AppError::Database(message()), "database_error", false),
+            (AppError::Config(message()), "config_error", false),
+            (AppError::Validation(message()), "validation_error", false),
+            (AppError::InvalidState(message()), "invalid_state", false),
+            (AppError::Protocol(message()), "protocol_error", false),
+            (AppError::Timeout(message()), "timeout", true),
+            (AppError::Permission(message()), "permission_denied", false),
+        ];
+        for (error, code, retryable) in cases {
+            let wire = wire(error);
+            assert_eq!(wire["code"], code);
+            assert_eq!(wire["retryable"], retryable, "{}", code);
+            assert_eq!(wire["details"], serde_json::Value::Null, "{}", code);
+        }
+    }
+
+    #[test]
+    fn keyring_errors_map_to_their_own_codes() {
+        let platform = || Box::new(std::io::Error::other("locked")) as Box<_>;
+        let credential = || {
+            Box::new(keyring::mock::MockCredential::default())
+                as Box<keyring::credential::Credential>
+        };
+
+        assert!(matches!(
+            AppError::from(keyring::Error::NoEntry),
+            AppError::KeyringNoEntry(_)
+        ));
+        assert!(matches!(
+            AppError::from(keyring::Error::PlatformFailure(platform())),
+            AppError::KeyringPlatformFailure(_)
+        ));
+        assert!(matches!(
+            AppError::from(keyring::Error::NoStorageAccess(platform())),
+            AppError::Permission(_)
+        ));
+        assert!(matches!(
+            AppError::from(keyring::Error::TooLong("user".to_string(), 8)),
+            AppError::Validation(_)
+        ));
+        let ambiguous = AppError::from(keyring::Error::Ambiguous(vec![
+            credential(),
+            credential(),
+            credential(),
+        ]));
+        assert!(matches!(
+            ambiguous,
+            AppError::KeyringAmbiguous { candidates: 3, .. }
+        ));
+        assert_eq!(wire(ambiguous)["details"], json!({ "candidates": 3 }));
+    }
+
+    #[test]
+    fn io_errors_map_by_kind() {
+        use std::io::{Error, ErrorKind};
+        assert_eq!(
+            AppError::from(Error::from(ErrorKind::PermissionDenied)).code(),
+            "permission_denied"
+        );
+        assert_eq!(
+            AppError::from(Error::from(ErrorKind::TimedOut)).code(),
+            "timeout"
+        );
+        assert_eq!(
+            AppError::from(Error::from(ErrorKind::NotFound)).code(),
+            "io_error"
+        );
     }
 }
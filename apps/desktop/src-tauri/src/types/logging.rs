@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Log verbosity and retention
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingSettings {
+    /// Level for modules without an override: `off`, `error`, `warn`,
+    /// `info`, `debug` or `trace`
+    pub level: String,
+    /// Per-module overrides keyed by target, e.g.
+    /// `aios_desktop::services::mcp_manager` or `sqlx`
+    pub modules: BTreeMap<String, String>,
+    /// Number of daily log files kept in the `logs` directory
+    pub max_files: usize,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            modules: BTreeMap::new(),
+            max_files: 7,
+        }
+    }
+}
+
+/// A log record read back from the JSON log file
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Event fields other than the message
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    /// Enclosing spans, outermost first
+    pub spans: Vec<serde_json::Value>,
+}
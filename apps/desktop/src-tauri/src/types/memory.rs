@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+fn default_namespace() -> String {
+    "default".to_string()
+}
+
+/// A memory to insert, or to update when `key` matches an existing entry in the namespace
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemoryUpsert {
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+    /// Caller-chosen identifier used for upserts; entries without a key are always inserted
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    pub content: String,
+    /// Embedding vector computed by the caller
+    pub embedding: Vec<f32>,
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+}
+
+/// A stored memory
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryRecord {
+    pub id: i64,
+    pub namespace: String,
+    pub key: Option<String>,
+    pub run_id: Option<String>,
+    pub content: String,
+    pub metadata: Map<String, Value>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Parameters for a memory search
+///
+/// At least one of `embedding` and `text` must be given. When both are set,
+/// results are ranked by a weighted blend of cosine similarity and BM25.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemorySearchQuery {
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+    #[serde(default)]
+    pub embedding: Option<Vec<f32>>,
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Exact-match filters on top-level metadata fields
+    #[serde(default)]
+    pub filters: Map<String, Value>,
+    #[serde(default)]
+    pub top_k: Option<usize>,
+    /// Weight of the vector score in hybrid ranking, between 0 and 1 (default 0.7)
+    #[serde(default)]
+    pub vector_weight: Option<f32>,
+    /// Drop hits whose combined score is below this threshold
+    #[serde(default)]
+    pub min_score: Option<f32>,
+}
+
+/// A ranked memory search hit
+#[derive(Debug, Clone, Serialize)]
+pub struct MemorySearchHit {
+    pub memory: MemoryRecord,
+    /// Combined score; higher is more relevant
+    pub score: f32,
+    /// Cosine similarity to the query embedding, if one was given
+    pub vector_score: Option<f32>,
+    /// Text relevance normalized to 0..1 among the text matches, if text was given
+    pub text_score: Option<f32>,
+}
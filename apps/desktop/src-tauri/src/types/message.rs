@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Roles accepted for new messages
+pub const MESSAGE_ROLES: &[&str] = &["system", "user", "assistant", "tool"];
+
+/// Reference to a file stored in the attachment store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    /// SHA-256 of the file contents, hex encoded
+    pub hash: String,
+    pub mime_type: String,
+    pub size: i64,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// One part of a structured message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagePart {
+    Text {
+        text: String,
+    },
+    /// A call to a tool exposed by an MCP server
+    ToolUse {
+        id: String,
+        server: String,
+        tool: String,
+        #[serde(default)]
+        arguments: Value,
+    },
+    /// The outcome of a `ToolUse` part, matched by `tool_use_id`
+    ToolResult {
+        tool_use_id: String,
+        /// Text rendering of the result
+        content: String,
+        /// Raw result payload as returned by the server
+        #[serde(default)]
+        data: Option<Value>,
+        #[serde(default)]
+        is_error: bool,
+    },
+    Image {
+        attachment: Attachment,
+    },
+    File {
+        attachment: Attachment,
+    },
+}
+
+impl MessagePart {
+    /// Kind stored in `message_parts.kind`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MessagePart::Text { .. } => "text",
+            MessagePart::ToolUse { .. } => "tool_use",
+            MessagePart::ToolResult { .. } => "tool_result",
+            MessagePart::Image { .. } => "image",
+            MessagePart::File { .. } => "file",
+        }
+    }
+}
+
+/// A message with its structured parts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub id: i64,
+    pub run_id: String,
+    pub role: String,
+    pub parts: Vec<MessagePart>,
+    pub created_at: String,
+}
+
+/// Input for storing a new attachment
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewAttachment {
+    pub mime_type: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// File contents, base64 encoded
+    pub data_base64: String,
+}
@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+/// Resource usage of a server's process tree at one point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessSample {
+    /// RFC 3339 time the sample was taken
+    pub timestamp: String,
+    /// CPU use since the previous sample, where 100 is one full core
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    pub open_fds: u64,
+    /// Processes in the tree, the server itself included
+    pub processes: u64,
+}
+
+/// Current and recent resource usage of a running server
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerMetrics {
+    pub pid: Option<u32>,
+    pub uptime_secs: u64,
+    /// Latest sample; `None` until the first sample or where `/proc` is
+    /// unavailable
+    pub current: Option<ProcessSample>,
+    /// Recent samples, oldest first
+    pub history: Vec<ProcessSample>,
+}
+
+/// Outcome of one health check ping
+#[derive(Debug, Clone, Serialize)]
+pub struct PingSample {
+    /// RFC 3339 time the ping was sent
+    pub timestamp: String,
+    /// Round trip time of an answered ping
+    pub latency_ms: Option<f64>,
+    /// Why the ping failed
+    pub error: Option<String>,
+}
+
+/// Health check state and recent ping latencies of a running server
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerHealth {
+    /// Whether the server is being pinged
+    pub active: bool,
+    /// Why the server is not being pinged
+    pub inactive_reason: Option<String>,
+    pub consecutive_failures: u32,
+    pub last_ping: Option<PingSample>,
+    /// Recent pings, oldest first
+    pub history: Vec<PingSample>,
+}
@@ -1,2 +1,14 @@
+pub mod archive;
 pub mod config;
 pub mod errors;
+pub mod logging;
+pub mod memory;
+pub mod message;
+pub mod metrics;
+pub mod notification;
+pub mod queue;
+pub mod run;
+pub mod search;
+pub mod settings;
+pub mod shortcut;
+pub mod trigger;
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    RunCompleted,
+    RunFailed,
+    InputRequested,
+    /// Several run updates coalesced into one notification
+    Summary,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::RunCompleted => "run_completed",
+            NotificationKind::RunFailed => "run_failed",
+            NotificationKind::InputRequested => "input_requested",
+            NotificationKind::Summary => "summary",
+        }
+    }
+}
+
+/// Action offered on a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationAction {
+    /// Show the run (or the run list for summaries)
+    Open,
+    Dismiss,
+    /// Answer a run waiting for input
+    Reply,
+}
+
+/// Whether a notification reached the sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationStatus {
+    Sent,
+    /// Held back by the do-not-disturb schedule
+    Suppressed,
+    Failed,
+}
+
+impl NotificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationStatus::Sent => "sent",
+            NotificationStatus::Suppressed => "suppressed",
+            NotificationStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A recorded notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: i64,
+    /// Run the notification is about; `None` for summaries
+    pub run_id: Option<String>,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub actions: Vec<NotificationAction>,
+    pub status: NotificationStatus,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub dismissed_at: Option<String>,
+}
+
+/// Daily local-time window in which notifications are suppressed
+///
+/// Times are `HH:MM`; a window whose end is before its start spans midnight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoNotDisturb {
+    pub start: String,
+    pub end: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_coalesce_window_ms() -> u64 {
+    3_000
+}
+
+/// Notification preferences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Run updates arriving within this window are sent as one notification
+    #[serde(default = "default_coalesce_window_ms")]
+    pub coalesce_window_ms: u64,
+    #[serde(default)]
+    pub do_not_disturb: Option<DoNotDisturb>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            coalesce_window_ms: default_coalesce_window_ms(),
+            do_not_disturb: None,
+        }
+    }
+}
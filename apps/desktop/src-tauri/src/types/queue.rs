@@ -0,0 +1,46 @@
+use crate::types::run::Run;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Concurrency limits enforced by the task queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueLimits {
+    /// Maximum number of runs executing at once across all agents
+    pub max_concurrent: usize,
+    /// Default maximum number of runs executing at once per agent
+    pub max_per_agent: usize,
+    /// Per-agent overrides of `max_per_agent`
+    #[serde(default)]
+    pub agent_limits: HashMap<String, usize>,
+}
+
+impl Default for QueueLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            max_per_agent: 2,
+            agent_limits: HashMap::new(),
+        }
+    }
+}
+
+impl QueueLimits {
+    /// Get the concurrency limit for an agent
+    pub fn limit_for(&self, agent: &str) -> usize {
+        self.agent_limits
+            .get(agent)
+            .copied()
+            .unwrap_or(self.max_per_agent)
+    }
+}
+
+/// Snapshot of the task queue returned by `list_queue`
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueSnapshot {
+    pub paused: bool,
+    pub limits: QueueLimits,
+    /// Runs occupying an execution slot (`running` or `awaiting_input`)
+    pub active: Vec<Run>,
+    /// Waiting runs in dispatch order
+    pub queued: Vec<Run>,
+}
@@ -0,0 +1,207 @@
+use crate::types::errors::AppError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Lifecycle status of a run
+///
+/// Allowed transitions:
+/// - `pending` → `queued`
+/// - `queued` → `running`
+/// - `running` → `awaiting_input`, `completed`, `failed`, or back to `queued`
+///   when the app restarts while the run was executing
+/// - `awaiting_input` → `running`, `failed`
+/// - any non-terminal status → `cancelled`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Pending,
+    Queued,
+    Running,
+    AwaitingInput,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl RunStatus {
+    /// Value stored in `runs.status`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Pending => "pending",
+            RunStatus::Queued => "queued",
+            RunStatus::Running => "running",
+            RunStatus::AwaitingInput => "awaiting_input",
+            RunStatus::Completed => "completed",
+            RunStatus::Failed => "failed",
+            RunStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether a run in this status occupies an execution slot
+    pub fn is_active(&self) -> bool {
+        matches!(self, RunStatus::Running | RunStatus::AwaitingInput)
+    }
+
+    /// Whether the run has finished and can no longer change status
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            RunStatus::Completed | RunStatus::Failed | RunStatus::Cancelled
+        )
+    }
+
+    /// Whether a run in this status may move to `next`
+    pub fn can_transition_to(&self, next: RunStatus) -> bool {
+        use RunStatus::*;
+        match (self, next) {
+            (Pending, Queued) => true,
+            (Queued, Running) => true,
+            (Running, Queued | AwaitingInput | Completed | Failed) => true,
+            (AwaitingInput, Running | Failed) => true,
+            (from, Cancelled) => !from.is_terminal(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RunStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(RunStatus::Pending),
+            "queued" => Ok(RunStatus::Queued),
+            "running" => Ok(RunStatus::Running),
+            "awaiting_input" => Ok(RunStatus::AwaitingInput),
+            "completed" => Ok(RunStatus::Completed),
+            "failed" => Ok(RunStatus::Failed),
+            "cancelled" => Ok(RunStatus::Cancelled),
+            other => Err(AppError::Validation(format!(
+                "Unknown run status '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Agent used when a run or trigger doesn't name one
+pub fn default_agent() -> String {
+    "default".to_string()
+}
+
+/// A run row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub id: String,
+    pub task: String,
+    pub status: RunStatus,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+    pub error: Option<String>,
+    /// Agent that executes the run; concurrency limits apply per agent
+    #[serde(default = "default_agent")]
+    pub agent: String,
+    /// Higher priorities are dispatched first
+    #[serde(default)]
+    pub priority: i64,
+    /// When the run last entered the queue
+    #[serde(default)]
+    pub queued_at: Option<String>,
+}
+
+/// Parameters for creating a run
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewRun {
+    pub task: String,
+    #[serde(default = "default_agent")]
+    pub agent: String,
+    #[serde(default)]
+    pub priority: i64,
+}
+
+/// A recorded status change, also emitted as the `run-status-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunTransition {
+    pub run_id: String,
+    /// `None` for the initial status of a newly created run
+    pub from: Option<RunStatus>,
+    pub to: RunStatus,
+    pub reason: Option<String>,
+    pub at: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [RunStatus; 7] = [
+        RunStatus::Pending,
+        RunStatus::Queued,
+        RunStatus::Running,
+        RunStatus::AwaitingInput,
+        RunStatus::Completed,
+        RunStatus::Failed,
+        RunStatus::Cancelled,
+    ];
+
+    #[test]
+    fn transitions_follow_the_state_machine() {
+        use RunStatus::*;
+        let allowed = [
+            (Pending, Queued),
+            (Pending, Cancelled),
+            (Queued, Running),
+            (Queued, Cancelled),
+            (Running, Queued),
+            (Running, AwaitingInput),
+            (Running, Completed),
+            (Running, Failed),
+            (Running, Cancelled),
+            (AwaitingInput, Running),
+            (AwaitingInput, Failed),
+            (AwaitingInput, Cancelled),
+        ];
+
+        for from in ALL {
+            for to in ALL {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    allowed.contains(&(from, to)),
+                    "{} -> {}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn terminal_statuses_have_no_transitions() {
+        for from in ALL.into_iter().filter(RunStatus::is_terminal) {
+            assert!(
+                ALL.iter().all(|to| !from.can_transition_to(*to)),
+                "{}",
+                from
+            );
+        }
+    }
+
+    #[test]
+    fn statuses_round_trip_through_their_stored_value() {
+        for status in ALL {
+            assert_eq!(status.as_str().parse::<RunStatus>().unwrap(), status);
+            assert_eq!(
+                serde_json::to_value(status).unwrap(),
+                serde_json::Value::from(status.as_str())
+            );
+        }
+        assert!("Completed".parse::<RunStatus>().is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Parameters for a full-text search over run messages
+#[derive(Debug, Clone, Deserialize)]
+pub struct MessageSearchQuery {
+    /// Free text; each whitespace-separated term must match
+    pub query: String,
+    /// Only return messages from runs in one of these statuses
+    #[serde(default)]
+    pub statuses: Vec<String>,
+    /// Inclusive lower bound on the message timestamp (ISO 8601)
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Inclusive upper bound on the message timestamp (ISO 8601)
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: Option<u32>,
+}
+
+/// A single ranked search hit
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageSearchHit {
+    pub message_id: i64,
+    pub run_id: String,
+    pub run_task: String,
+    pub run_status: String,
+    pub run_started_at: String,
+    pub run_completed_at: Option<String>,
+    pub role: String,
+    pub created_at: String,
+    /// HTML-escaped excerpt of the message with matches wrapped in `<mark>` tags
+    pub snippet: String,
+    /// BM25 relevance; lower is more relevant
+    pub score: f64,
+}
@@ -0,0 +1,70 @@
+use crate::types::logging::LoggingSettings;
+use crate::types::notification::NotificationSettings;
+use crate::types::queue::QueueLimits;
+use crate::types::shortcut::ShortcutSettings;
+use crate::types::trigger::WebhookConfig;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Version of the settings file written by this build
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Default keyring service name secrets are stored under
+pub const DEFAULT_SECRETS_SERVICE: &str = "com.aios.secrets";
+
+/// Main window behavior
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// Show a dock icon on macOS instead of running from the tray only
+    pub show_in_dock: bool,
+    /// Keep the window hidden at launch until summoned
+    pub start_hidden: bool,
+    /// Hide the window when it loses focus, like a spotlight launcher
+    pub hide_on_blur: bool,
+}
+
+/// Application settings persisted as `settings.json` in the app data directory
+///
+/// Missing fields take their defaults, so older files load after new fields
+/// are added; renamed or restructured fields need a migration instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub window: WindowSettings,
+    /// Keyring service name secrets are stored under; applies after restart
+    ///
+    /// Only set by editing the settings file; `update_settings` keeps the
+    /// current value, since the known-key index is not per service.
+    pub secrets_service: String,
+    /// MCP server configuration file, `mcp_config.json` in the app data
+    /// directory when unset; applies after restart
+    ///
+    /// Only set by editing the settings file; `update_settings` keeps the
+    /// current value, since the file decides which commands are run.
+    pub mcp_config_path: Option<PathBuf>,
+    pub global_shortcut: ShortcutSettings,
+    pub notifications: NotificationSettings,
+    pub queue: QueueLimits,
+    /// Webhook listener address; applies after restart
+    pub webhooks: WebhookConfig,
+    /// Log levels; `max_files` applies after restart
+    pub logging: LoggingSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            window: WindowSettings::default(),
+            secrets_service: DEFAULT_SECRETS_SERVICE.to_string(),
+            mcp_config_path: None,
+            global_shortcut: ShortcutSettings::default(),
+            notifications: NotificationSettings::default(),
+            queue: QueueLimits::default(),
+            webhooks: WebhookConfig::default(),
+            logging: LoggingSettings::default(),
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::types::errors::AppError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Default accelerator for summoning the launcher
+pub const DEFAULT_ACCELERATOR: &str = "CmdOrCtrl+Shift+Space";
+
+/// Modifier key of an accelerator, in canonical order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Modifier {
+    /// Command on macOS, Control elsewhere
+    CmdOrCtrl,
+    Ctrl,
+    Alt,
+    Shift,
+    /// Command on macOS, the Windows/Super key elsewhere
+    Super,
+}
+
+impl Modifier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Modifier::CmdOrCtrl => "CmdOrCtrl",
+            Modifier::Ctrl => "Ctrl",
+            Modifier::Alt => "Alt",
+            Modifier::Shift => "Shift",
+            Modifier::Super => "Super",
+        }
+    }
+
+    fn parse(token: &str) -> Option<Self> {
+        match token.to_ascii_lowercase().as_str() {
+            "cmdorctrl" | "cmdorcontrol" | "commandorctrl" | "commandorcontrol" => {
+                Some(Modifier::CmdOrCtrl)
+            }
+            "ctrl" | "control" => Some(Modifier::Ctrl),
+            "alt" | "option" => Some(Modifier::Alt),
+            "shift" => Some(Modifier::Shift),
+            "super" | "cmd" | "command" | "meta" | "win" => Some(Modifier::Super),
+            _ => None,
+        }
+    }
+}
+
+/// Parsed keyboard accelerator such as `CmdOrCtrl+Shift+Space`
+///
+/// Parsing is case-insensitive and accepts common aliases; `Display` renders
+/// the canonical form that is persisted and handed to the OS registration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accelerator {
+    /// Sorted and deduplicated
+    modifiers: Vec<Modifier>,
+    /// Canonical key name
+    key: String,
+}
+
+impl FromStr for Accelerator {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |reason: &str| AppError::Validation(format!("Invalid shortcut '{}': {}", s, reason));
+
+        if s.trim().is_empty() {
+            return Err(invalid("shortcut is empty"));
+        }
+
+        let mut modifiers = Vec::new();
+        let mut key: Option<String> = None;
+        for token in s.split('+').map(str::trim) {
+            if token.is_empty() {
+                return Err(invalid("empty key between '+' separators"));
+            }
+            if let Some(modifier) = Modifier::parse(token) {
+                if key.is_some() {
+                    return Err(invalid("modifiers must come before the key"));
+                }
+                if modifiers.contains(&modifier) {
+                    return Err(invalid(&format!("'{}' is repeated", modifier.as_str())));
+                }
+                modifiers.push(modifier);
+                continue;
+            }
+            if key.is_some() {
+                return Err(invalid("only one non-modifier key is allowed"));
+            }
+            key = Some(
+                canonical_key(token).ok_or_else(|| invalid(&format!("unknown key '{}'", token)))?,
+            );
+        }
+
+        let key = key.ok_or_else(|| invalid("missing a non-modifier key"))?;
+        // A bare letter or space would swallow normal typing system-wide
+        if modifiers.is_empty() && !is_function_key(&key) {
+            return Err(invalid("at least one modifier is required"));
+        }
+        modifiers.sort();
+
+        Ok(Self { modifiers, key })
+    }
+}
+
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier.as_str())?;
+        }
+        f.write_str(&self.key)
+    }
+}
+
+/// Canonical name of a non-modifier key, or `None` if it is not supported
+fn canonical_key(token: &str) -> Option<String> {
+    let lower = token.to_ascii_lowercase();
+
+    // Letters and digits
+    if lower.len() == 1 {
+        let c = lower.chars().next()?;
+        if c.is_ascii_alphanumeric() {
+            return Some(c.to_ascii_uppercase().to_string());
+        }
+    }
+
+    // Function keys F1-F24
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        if (1..=24).contains(&n) {
+            return Some(format!("F{}", n));
+        }
+    }
+
+    let key = match lower.as_str() {
+        "space" => "Space",
+        "enter" | "return" => "Enter",
+        "tab" => "Tab",
+        "escape" | "esc" => "Escape",
+        "backspace" => "Backspace",
+        "delete" | "del" => "Delete",
+        "insert" => "Insert",
+        "home" => "Home",
+        "end" => "End",
+        "pageup" => "PageUp",
+        "pagedown" => "PageDown",
+        "up" | "arrowup" => "Up",
+        "down" | "arrowdown" => "Down",
+        "left" | "arrowleft" => "Left",
+        "right" | "arrowright" => "Right",
+        "minus" | "-" => "Minus",
+        "equal" | "=" => "Equal",
+        "comma" | "," => "Comma",
+        "period" | "." => "Period",
+        "slash" | "/" => "Slash",
+        "backslash" | "\\" => "Backslash",
+        "semicolon" | ";" => "Semicolon",
+        "quote" | "'" => "Quote",
+        "backquote" | "`" => "Backquote",
+        "bracketleft" | "[" => "BracketLeft",
+        "bracketright" | "]" => "BracketRight",
+        _ => return None,
+    };
+    Some(key.to_string())
+}
+
+fn is_function_key(key: &str) -> bool {
+    key.len() > 1 && key.starts_with('F') && key[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// What the global shortcut does with the main window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShortcutAction {
+    /// Hide the window if it is focused, otherwise show and focus it
+    #[default]
+    Toggle,
+    /// Show the window without taking focus
+    Show,
+    /// Show the window and focus it
+    Focus,
+}
+
+/// Global shortcut preferences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutSettings {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_accelerator")]
+    pub accelerator: String,
+    #[serde(default)]
+    pub action: ShortcutAction,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_accelerator() -> String {
+    DEFAULT_ACCELERATOR.to_string()
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            accelerator: default_accelerator(),
+            action: ShortcutAction::default(),
+        }
+    }
+}
+
+/// Current shortcut preferences and whether the OS accepted the registration
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutStatus {
+    pub settings: ShortcutSettings,
+    /// Canonical accelerator currently registered with the OS
+    pub registered: Option<String>,
+    /// Why the configured accelerator is not registered, e.g. a conflict
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(s: &str) -> String {
+        s.parse::<Accelerator>().unwrap().to_string()
+    }
+
+    fn rejected(s: &str) -> bool {
+        matches!(s.parse::<Accelerator>(), Err(AppError::Validation(_)))
+    }
+
+    #[test]
+    fn aliases_parse_to_canonical_names() {
+        assert_eq!(canonical("cmd+K"), "Super+K");
+        assert_eq!(canonical("Control+Shift+P"), "Ctrl+Shift+P");
+        assert_eq!(canonical("Option+esc"), "Alt+Escape");
+        assert_eq!(canonical("CommandOrControl+Return"), "CmdOrCtrl+Enter");
+        assert_eq!(canonical("Ctrl+arrowup"), "Ctrl+Up");
+        assert_eq!(canonical("Alt+/"), "Alt+Slash");
+    }
+
+    #[test]
+    fn parsing_ignores_case_and_spacing() {
+        assert_eq!(canonical("cmdorctrl+shift+space"), DEFAULT_ACCELERATOR);
+        assert_eq!(canonical(" CTRL + a "), "Ctrl+A");
+        assert_eq!(
+            "ctrl+a".parse::<Accelerator>().unwrap(),
+            "Ctrl+A".parse::<Accelerator>().unwrap()
+        );
+    }
+
+    #[test]
+    fn modifiers_are_ordered_and_round_trip() {
+        let accelerator: Accelerator = "Shift+Super+Alt+Ctrl+CmdOrCtrl+F12".parse().unwrap();
+        assert_eq!(
+            accelerator.modifiers,
+            [
+                Modifier::CmdOrCtrl,
+                Modifier::Ctrl,
+                Modifier::Alt,
+                Modifier::Shift,
+                Modifier::Super
+            ]
+        );
+        assert_eq!(accelerator.key, "F12");
+
+        let rendered = accelerator.to_string();
+        assert_eq!(rendered, "CmdOrCtrl+Ctrl+Alt+Shift+Super+F12");
+        assert_eq!(rendered.parse::<Accelerator>().unwrap(), accelerator);
+    }
+
+    #[test]
+    fn malformed_shortcuts_are_rejected() {
+        assert!(rejected(""));
+        assert!(rejected("  "));
+        // Repeated modifier, including through an alias
+        assert!(rejected("Ctrl+Ctrl+A"));
+        assert!(rejected("Ctrl+Control+A"));
+        assert!(rejected("A+Ctrl"));
+        assert!(rejected("Ctrl+A+B"));
+        assert!(rejected("Ctrl++A"));
+        assert!(rejected("Ctrl+A+"));
+        assert!(rejected("Ctrl+Shift"));
+        assert!(rejected("Ctrl+Nope"));
+    }
+
+    #[test]
+    fn bare_keys_need_a_modifier_unless_function_keys() {
+        assert!(rejected("A"));
+        assert!(rejected("Space"));
+        assert_eq!(canonical("F5"), "F5");
+        assert_eq!(canonical("f24"), "F24");
+    }
+
+    #[test]
+    fn function_keys_stop_at_f24() {
+        assert_eq!(canonical_key("F1").as_deref(), Some("F1"));
+        assert_eq!(canonical_key("f24").as_deref(), Some("F24"));
+        assert_eq!(canonical_key("F0"), None);
+        assert_eq!(canonical_key("F25"), None);
+        assert!(rejected("F25"));
+        assert!(rejected("Ctrl+F25"));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::types::errors::AppError;
+use crate::types::run::default_agent;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// What a cron trigger does about fire times missed while the app was closed or asleep
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Drop missed fire times
+    #[default]
+    Skip,
+    /// Fire once for all missed fire times together
+    FireOnce,
+    /// Fire once per missed fire time
+    FireAll,
+}
+
+/// Kind of file-system change a `file_watch` trigger reacts to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileEventKind {
+    Create,
+    Modify,
+    Delete,
+}
+
+impl FileEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileEventKind::Create => "create",
+            FileEventKind::Modify => "modify",
+            FileEventKind::Delete => "delete",
+        }
+    }
+}
+
+/// How a webhook trigger authenticates deliveries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureScheme {
+    /// Accept unsigned deliveries
+    None,
+    /// `X-Hub-Signature-256: sha256=<hex HMAC-SHA256 of the body>`, as sent by GitHub
+    #[default]
+    Github,
+    /// `X-Aios-Signature: <hex HMAC-SHA256 of "<timestamp>.<body>">` plus
+    /// `X-Aios-Timestamp: <unix seconds>`, rejected when the timestamp is stale
+    Generic,
+}
+
+/// Kind-specific trigger settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriggerSpec {
+    /// Fire on a cron schedule
+    ///
+    /// `expression` takes 5 fields (minute hour day-of-month month day-of-week)
+    /// or 6–7 fields with leading seconds and trailing year.
+    Cron {
+        expression: String,
+        /// IANA timezone name the expression is evaluated in
+        #[serde(default = "default_timezone")]
+        timezone: String,
+        #[serde(default)]
+        misfire_policy: MisfirePolicy,
+    },
+    /// Fire when files matching `paths` change
+    ///
+    /// Entries are plain paths (a file, or a directory whose contents are watched)
+    /// or glob patterns such as `/home/me/notes/**/*.md`; all must be absolute.
+    FileWatch {
+        paths: Vec<String>,
+        /// Also watch subdirectories of plain directory paths
+        #[serde(default)]
+        recursive: bool,
+        /// Changes to react to; empty means all
+        #[serde(default)]
+        events: Vec<FileEventKind>,
+        /// Quiet period that ends a burst of changes before the trigger fires
+        #[serde(default = "default_debounce_ms")]
+        debounce_ms: u64,
+    },
+    /// Fire when a request is posted to the trigger's local webhook URL
+    Webhook {
+        #[serde(default)]
+        signature: SignatureScheme,
+        /// Keyring key of the HMAC secret; required unless `signature` is `none`
+        #[serde(default)]
+        secret_key: Option<String>,
+        /// Deliveries accepted per minute before answering 429
+        #[serde(default = "default_rate_limit")]
+        rate_limit_per_minute: u32,
+    },
+}
+
+impl TriggerSpec {
+    /// Kind name stored in the `triggers.kind` column
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TriggerSpec::Cron { .. } => "cron",
+            TriggerSpec::FileWatch { .. } => "file_watch",
+            TriggerSpec::Webhook { .. } => "webhook",
+        }
+    }
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn default_debounce_ms() -> u64 {
+    500
+}
+
+fn default_rate_limit() -> u32 {
+    60
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// User-editable part of a trigger, used to create and update triggers
+///
+/// `task_template` may reference `{{name}}` placeholders filled in when the
+/// trigger fires. All triggers provide `trigger_name`; cron triggers add
+/// `scheduled_for` and `fired_at`, file watch triggers add `path` (the first
+/// changed file), `paths` (one per line) and `events`; webhook triggers add
+/// `body` and JSON paths into the request body such as `{{$.pull_request.title}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerDefinition {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub task_template: String,
+    #[serde(default = "default_agent")]
+    pub agent: String,
+    #[serde(default)]
+    pub priority: i64,
+    pub spec: TriggerSpec,
+}
+
+/// A stored trigger
+#[derive(Debug, Clone, Serialize)]
+pub struct Trigger {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub task_template: String,
+    pub agent: String,
+    pub priority: i64,
+    pub spec: TriggerSpec,
+    pub last_fired_at: Option<String>,
+    /// Next scheduled fire time (UTC), for triggers that run on a schedule
+    pub next_fire_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Incremented on every edit
+    pub revision: i64,
+}
+
+/// A record of a trigger firing
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerFiring {
+    pub id: i64,
+    pub trigger_id: String,
+    /// The run created, unless submitting it failed
+    pub run_id: Option<String>,
+    /// The schedule slot this firing was for, for scheduled triggers
+    pub scheduled_for: Option<String>,
+    /// Kind-specific details about what caused the firing
+    pub detail: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub fired_at: String,
+}
+
+/// Outcome of a webhook delivery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryOutcome {
+    Accepted,
+    Disabled,
+    RateLimited,
+    InvalidSignature,
+    Failed,
+}
+
+impl DeliveryOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryOutcome::Accepted => "accepted",
+            DeliveryOutcome::Disabled => "disabled",
+            DeliveryOutcome::RateLimited => "rate_limited",
+            DeliveryOutcome::InvalidSignature => "invalid_signature",
+            DeliveryOutcome::Failed => "failed",
+        }
+    }
+}
+
+impl std::str::FromStr for DeliveryOutcome {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "accepted" => Ok(DeliveryOutcome::Accepted),
+            "disabled" => Ok(DeliveryOutcome::Disabled),
+            "rate_limited" => Ok(DeliveryOutcome::RateLimited),
+            "invalid_signature" => Ok(DeliveryOutcome::InvalidSignature),
+            "failed" => Ok(DeliveryOutcome::Failed),
+            other => Err(AppError::Validation(format!(
+                "Unknown delivery outcome '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Default port of the webhook listener
+pub const DEFAULT_WEBHOOK_PORT: u16 = 17_845;
+
+/// Configuration for the webhook listener
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Address to bind; loopback unless deliveries must come from other machines
+    pub address: SocketAddr,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DEFAULT_WEBHOOK_PORT),
+        }
+    }
+}
+
+/// A logged webhook delivery
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub trigger_id: String,
+    pub outcome: DeliveryOutcome,
+    pub status_code: u16,
+    pub body_size: i64,
+    pub run_id: Option<String>,
+    pub error: Option<String>,
+    pub received_at: String,
+}
@@ -1,48 +1,73 @@
 import { invoke } from '@tauri-apps/api/core';
-import { fetch } from '@tauri-apps/plugin-http';
 
+type BackendStatus =
+  | 'stopped'
+  | 'starting'
+  | 'running'
+  | 'restarting'
+  | { failed: string };
+
+export interface BackendInfo {
+  status: BackendStatus;
+  pid: number | null;
+  restart_count: number;
+  last_error: string | null;
+}
+
+interface BackendResponse<T> {
+  status: number;
+  body: T;
+}
+
+/** Error returned by a rejected Tauri command. */
+export interface AppError {
+  /** Stable machine-readable code, e.g. `not_found` or `keyring_no_entry`. */
+  code: string;
+  message: string;
+  details: Record<string, unknown> | null;
+  /** Whether the same request may succeed if tried again unchanged. */
+  retryable: boolean;
+}
+
+export function isAppError(err: unknown): err is AppError {
+  return (
+    typeof err === 'object' &&
+    err !== null &&
+    'code' in err &&
+    'message' in err
+  );
+}
+
+type HttpMethod = 'GET' | 'POST' | 'PUT' | 'PATCH' | 'DELETE';
+
+/**
+ * Client for the backend sidecar.
+ *
+ * Requests go through the Rust `backend_request` command, which knows the
+ * sidecar's port and attaches its per-launch auth token; the webview never
+ * talks to the backend directly.
+ */
 class BackendClient {
-  private port: number | null = null;
-  private portPromise: Promise<number> | null = null;
-
-  async getPort(): Promise<number> {
-    if (this.port !== null) return this.port;
-    if (this.portPromise) return this.portPromise;
-
-    this.portPromise = (async () => {
-      try {
-        const port = await invoke<number>('get_backend_port');
-        return port;
-      } catch (error) {
-        // Development mode fallback - when running without sidecar
-        console.warn(
-          'Failed to get backend port from Tauri, falling back to localhost:3001',
-          error
-        );
-        return 3001;
-      }
-    })();
-
-    this.port = await this.portPromise;
-    this.portPromise = null;
-    return this.port;
+  async info(): Promise<BackendInfo> {
+    return invoke<BackendInfo>('get_backend_info');
   }
 
-  async call<T>(endpoint: string, init?: RequestInit): Promise<T> {
-    const port = await this.getPort();
-    const url = `http://localhost:${port}${endpoint}`;
-    const response = await fetch(url, {
-      ...init,
-      headers: {
-        'Content-Type': 'application/json',
-        ...init?.headers,
-      },
+  async call<T>(
+    endpoint: string,
+    method: HttpMethod = 'GET',
+    body?: unknown
+  ): Promise<T> {
+    const response = await invoke<BackendResponse<T>>('backend_request', {
+      method,
+      path: endpoint,
+      body: body ?? null,
     });
-    if (!response.ok) {
-      const error = await response.text();
-      throw new Error(`Backend error (${response.status}): ${error}`);
+    if (response.status < 200 || response.status >= 300) {
+      throw new Error(
+        `Backend error (${response.status}): ${JSON.stringify(response.body)}`
+      );
     }
-    return response.json();
+    return response.body;
   }
 
   async healthCheck() {